@@ -9,10 +9,50 @@ pub const RECEIPT_MINT_SEED: &[u8] = b"receipt_mint";
 #[constant]
 pub const OBLIGATION_SEED: &[u8] = b"obligation";
 #[constant]
+pub const INSURANCE_VAULT_SEED: &[u8] = b"insurance_vault";
+#[constant]
+pub const COLLATERAL_ESCROW_SEED: &[u8] = b"collateral_escrow";
+#[constant]
+pub const FLASH_LOAN_FEE_EXEMPTION_SEED: &[u8] = b"flash_loan_fee_exemption";
+#[constant]
+pub const LISTING_APPROVAL_SEED: &[u8] = b"listing_approval";
+#[constant]
+pub const ALLOWLIST_ENTRY_SEED: &[u8] = b"allowlist_entry";
+#[constant]
+pub const OBLIGATION_DELEGATE_SEED: &[u8] = b"obligation_delegate";
+#[constant]
+pub const DELEGATE_PERMISSION_DEPOSIT: u8 = 1 << 0;
+#[constant]
+pub const DELEGATE_PERMISSION_BORROW: u8 = 1 << 1;
+#[constant]
+pub const DELEGATE_PERMISSION_WITHDRAW: u8 = 1 << 2;
+#[constant]
+pub const MINT_EXTENSION_TRANSFER_FEE: u16 = 1 << 0;
+#[constant]
+pub const MINT_EXTENSION_PERMANENT_DELEGATE: u16 = 1 << 1;
+#[constant]
+pub const MINT_EXTENSION_DEFAULT_FROZEN: u16 = 1 << 2;
+#[constant]
+pub const MINT_EXTENSION_CONFIDENTIAL_TRANSFER: u16 = 1 << 3;
+#[constant]
+pub const MINT_EXTENSION_NON_TRANSFERABLE: u16 = 1 << 4;
+#[constant]
+pub const MINT_EXTENSION_PAUSABLE: u16 = 1 << 5;
+#[constant]
+pub const MINT_EXTENSION_MINT_CLOSE_AUTHORITY: u16 = 1 << 6;
+#[constant]
 pub const MAX_BASIS_POINTS: u16 = 10_000;
 #[constant]
+pub const DUST_THRESHOLD_VALUE_SCALE: u64 = 1_000_000; // 10^6, micro-USD
+#[constant]
+pub const DEFAULT_MAX_OBLIGATION_RESERVES: u8 = 5;
+#[constant]
+pub const MAX_OBLIGATION_RESERVES: u8 = 16;
+#[constant]
 pub const SLOTS_PER_YEAR: u64 = 78840000; // 160 / 64 * 86400 * 365
 #[constant]
+pub const INSURANCE_WITHDRAWAL_TIMELOCK: i64 = 604800; // 7 * 86400
+#[constant]
 #[cfg(feature = "no-staleness-check")]
 pub const ORACLE_MAX_AGE: u32 = u32::MAX;
 #[constant]
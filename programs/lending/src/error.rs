@@ -90,7 +90,7 @@ pub enum LendingError {
     InvalidMaxBorrowRate,
     #[msg("Optimal borrow rate must be greater than min borrow rate")]
     InvalidOptimalBorrowRate,
-    #[msg("Only one flash borrow per transaction is allowed")]
+    #[msg("Flash borrows of the same reserve cannot be nested")]
     MultipleFlashBorrowsNotAllowed,
     #[msg("Only one flash repay per transaction is allowed")]
     MultipleFlashRepaysNotAllowed,
@@ -110,4 +110,90 @@ pub enum LendingError {
     InvalidFlashBorrowInstructionData,
     #[msg("No more instructions to introspect")]
     OutOfInstructions,
+    #[msg("Obligation still has collateral deposited")]
+    ObligationDepositsNotEmpty,
+    #[msg("Insurance vault does not have enough liquidity")]
+    InsufficientInsurance,
+    #[msg("No insurance withdrawal has been requested")]
+    InsuranceWithdrawalNotRequested,
+    #[msg("Insurance withdrawal is still timelocked")]
+    InsuranceWithdrawalLocked,
+    #[msg("Liquidation accounts must be passed in complete groups")]
+    InvalidLiquidationAccounts,
+    #[msg("Token account is not owned by the reserve")]
+    InvalidReserveTokenAccount,
+    #[msg("Insurance vault does not match the one in reserve")]
+    InvalidInsuranceVault,
+    #[msg("Auto-deleverage threshold must be below 100%")]
+    InvalidAutoDeleverageThreshold,
+    #[msg("Obligation has not enabled auto-deleverage")]
+    AutoDeleverageDisabled,
+    #[msg("Obligation has not reached its auto-deleverage threshold")]
+    AutoDeleverageThresholdNotReached,
+    #[msg("Auto-deleverage amount is too small")]
+    AutoDeleverageTooSmall,
+    #[msg("Flash loans are disabled for this reserve")]
+    FlashLoansDisabled,
+    #[msg("Flash loan amount exceeds the reserve's max flash loan amount")]
+    FlashLoanTooLarge,
+    #[msg("Flash loan fee exemption does not apply to the signer or calling program")]
+    InvalidFlashLoanFeeExemption,
+    #[msg("Flash loan amount must be greater than zero")]
+    InvalidFlashLoanAmount,
+    #[msg("Flash loan receiver must be an executable program other than this one")]
+    InvalidFlashLoanReceiver,
+    #[msg("Reserve balance does not cover the flash loan amount plus fee")]
+    FlashLoanNotRepaid,
+    #[msg("Obligation is already at or above the target loan to value ratio")]
+    LeverageTargetReached,
+    #[msg("Invalid collateral escrow")]
+    InvalidCollateralEscrow,
+    #[msg("Receipt token program must be Token or Token-2022")]
+    InvalidReceiptTokenProgram,
+    #[msg("Mint has a Token-2022 extension that is not allowed by the market")]
+    MintExtensionNotAllowed,
+    #[msg("Reserve did not receive the full liquidation repay amount")]
+    LiquidationRepayNotReceived,
+    #[msg("Signer is not the market's listing approver")]
+    InvalidListingApprover,
+    #[msg("Mint has not been approved for listing in this market")]
+    ListingNotApproved,
+    #[msg("Price feed does not match the listing approval")]
+    InvalidListingPriceFeed,
+    #[msg("Signer is not the market's compliance authority")]
+    InvalidComplianceAuthority,
+    #[msg("Wallet is not on the market's allowlist")]
+    WalletNotAllowlisted,
+    #[msg("Obligation reserve limit must be between 1 and the maximum supported")]
+    InvalidObligationReserveLimit,
+    #[msg("Obligation has reached its limit of reserves")]
+    ObligationReserveLimit,
+    #[msg("Obligation still has liquidity borrowed")]
+    ObligationBorrowsNotEmpty,
+    #[msg("Signer is not the obligation's pending authority")]
+    InvalidPendingObligationAuthority,
+    #[msg("Delegate permissions must be a non-empty set of known permissions")]
+    InvalidDelegatePermissions,
+    #[msg("Delegate is not permitted to perform this action")]
+    DelegatePermissionDenied,
+    #[msg("Delegate has expired")]
+    DelegateExpired,
+    #[msg("Number of deposit reserve accounts passed does not match the obligation's deposits")]
+    DepositReserveAccountsMismatch,
+    #[msg("Receiver program did not swap the borrowed liquidity into collateral")]
+    LeverageSwapNotReceived,
+    #[msg("Liquidity mint has no metadata symbol, one must be provided")]
+    MissingLiquiditySymbol,
+    #[msg("Transfer hook mints are not supported")]
+    TransferHookNotSupported,
+    #[msg("Reserve did not receive the full repay amount")]
+    RepayNotReceived,
+    #[msg("Collateral escrow accounts must be passed as reserve, escrow and token program groups")]
+    InvalidCollateralEscrowAccounts,
+    #[msg("Collateral escrow still holds receipt tokens")]
+    CollateralEscrowNotEmpty,
+    #[msg("Obligation must be reallocated before use")]
+    ObligationReallocRequired,
+    #[msg("Original obligations holding deposits cannot be migrated")]
+    OriginalObligationDeposits,
 }
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::LendingError, validate_allowlisted, AllowlistEntry, Market, Obligation,
+    ALLOWLIST_ENTRY_SEED, MARKET_SEED,
+};
+
+#[derive(Accounts)]
+pub struct AcceptObligation<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidObligationMarket,
+        constraint = obligation.pending_authority == authority.key() @ LendingError::InvalidPendingObligationAuthority,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    #[account(
+        seeds = [ALLOWLIST_ENTRY_SEED, market.key().as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+}
+
+impl AcceptObligation<'_> {
+    /// The obligation keeps its address, so the new authority finds it by filtering obligations on the authority field.
+    pub fn handler(ctx: Context<AcceptObligation>) -> Result<()> {
+        let AcceptObligation {
+            authority,
+            market,
+            obligation,
+            allowlist_entry,
+        } = ctx.accounts;
+
+        validate_allowlisted(market, allowlist_entry.as_deref(), authority.key())?;
+
+        obligation.authority = authority.key();
+        obligation.pending_authority = Pubkey::default();
+
+        Ok(())
+    }
+}
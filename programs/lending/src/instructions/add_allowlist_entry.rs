@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::LendingError, AllowlistEntry, Market, ALLOWLIST_ENTRY_SEED, MARKET_SEED};
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddAllowlistEntry<'info> {
+    #[account(mut)]
+    pub compliance_authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+        constraint = market.compliance_authority == compliance_authority.key() @ LendingError::InvalidComplianceAuthority,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        init,
+        payer = compliance_authority,
+        space = AllowlistEntry::DISCRIMINATOR.len() + AllowlistEntry::INIT_SPACE,
+        seeds = [ALLOWLIST_ENTRY_SEED, market.key().as_ref(), wallet.as_ref()],
+        bump,
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    pub system_program: Program<'info, System>,
+}
+
+impl AddAllowlistEntry<'_> {
+    pub fn handler(ctx: Context<AddAllowlistEntry>, wallet: Pubkey) -> Result<()> {
+        ctx.accounts.allowlist_entry.set_inner(AllowlistEntry {
+            market: ctx.accounts.market.key(),
+            wallet,
+            bump: ctx.bumps.allowlist_entry,
+        });
+
+        Ok(())
+    }
+}
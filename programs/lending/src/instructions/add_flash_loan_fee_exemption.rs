@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::LendingError, validate_bps, FlashLoanFeeExemption, Market,
+    FLASH_LOAN_FEE_EXEMPTION_SEED, MARKET_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(address: Pubkey)]
+pub struct AddFlashLoanFeeExemption<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+        has_one = authority @ LendingError::InvalidMarketAuthority,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        init,
+        payer = authority,
+        space = FlashLoanFeeExemption::DISCRIMINATOR.len() + FlashLoanFeeExemption::INIT_SPACE,
+        seeds = [FLASH_LOAN_FEE_EXEMPTION_SEED, market.key().as_ref(), address.as_ref()],
+        bump,
+    )]
+    pub fee_exemption: Account<'info, FlashLoanFeeExemption>,
+    pub system_program: Program<'info, System>,
+}
+
+impl AddFlashLoanFeeExemption<'_> {
+    pub fn handler(
+        ctx: Context<AddFlashLoanFeeExemption>,
+        address: Pubkey,
+        fee_waiver_bps: u16,
+    ) -> Result<()> {
+        validate_bps(fee_waiver_bps)?;
+
+        let AddFlashLoanFeeExemption {
+            market,
+            fee_exemption,
+            ..
+        } = ctx.accounts;
+
+        fee_exemption.set_inner(FlashLoanFeeExemption {
+            address,
+            bump: ctx.bumps.fee_exemption,
+            fee_waiver_bps,
+            market: market.key(),
+        });
+
+        Ok(())
+    }
+}
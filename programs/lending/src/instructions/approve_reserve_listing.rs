@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::{error::LendingError, ListingApproval, Market, LISTING_APPROVAL_SEED, MARKET_SEED};
+
+#[derive(Accounts)]
+pub struct ApproveReserveListing<'info> {
+    #[account(mut)]
+    pub approver: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+        constraint = market.listing_approver == approver.key() @ LendingError::InvalidListingApprover,
+    )]
+    pub market: Account<'info, Market>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub price_update_v2: Account<'info, PriceUpdateV2>,
+    #[account(
+        init,
+        payer = approver,
+        space = ListingApproval::DISCRIMINATOR.len() + ListingApproval::INIT_SPACE,
+        seeds = [LISTING_APPROVAL_SEED, market.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub listing_approval: Account<'info, ListingApproval>,
+    pub system_program: Program<'info, System>,
+}
+
+impl ApproveReserveListing<'_> {
+    pub fn handler(ctx: Context<ApproveReserveListing>) -> Result<()> {
+        let ApproveReserveListing {
+            market,
+            mint,
+            price_update_v2,
+            listing_approval,
+            ..
+        } = ctx.accounts;
+
+        listing_approval.set_inner(ListingApproval {
+            market: market.key(),
+            mint: mint.key(),
+            price_update_v2: price_update_v2.key(),
+            bump: ctx.bumps.listing_approval,
+        });
+
+        Ok(())
+    }
+}
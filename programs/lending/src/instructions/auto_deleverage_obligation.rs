@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::{burn_checked, BurnChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::LendingError, reserve_signer, validate_obligation_refreshed, validate_reserve_refreshed,
+    Obligation, Reserve, COLLATERAL_ESCROW_SEED, RECEIPT_MINT_SEED, RESERVE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct AutoDeleverageObligation<'info> {
+    pub keeper: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.market.key().as_ref(), reserve.liquidity.mint.key().as_ref()],
+        bump = reserve.bump,
+    )]
+    pub reserve: Account<'info, Reserve>,
+    #[account(
+        mut,
+        constraint = obligation.market == reserve.market @ LendingError::InvalidObligationMarket,
+        constraint = obligation.auto_deleverage_threshold_bps > 0 @ LendingError::AutoDeleverageDisabled,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    #[account(
+        mut,
+        seeds = [RECEIPT_MINT_SEED, reserve.key().as_ref()],
+        bump = reserve.receipt_mint_bump,
+    )]
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [COLLATERAL_ESCROW_SEED, obligation.key().as_ref(), reserve.key().as_ref()],
+        bump,
+    )]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
+    pub receipt_token_program: Interface<'info, TokenInterface>,
+}
+
+impl AutoDeleverageObligation<'_> {
+    pub fn handler(ctx: Context<AutoDeleverageObligation>) -> Result<()> {
+        let AutoDeleverageObligation {
+            obligation,
+            reserve,
+            receipt_mint,
+            collateral_escrow,
+            receipt_token_program,
+            ..
+        } = ctx.accounts;
+
+        let slot = Clock::get()?.slot;
+
+        validate_reserve_refreshed(reserve.last_update.is_stale(slot)?)?;
+        validate_obligation_refreshed(obligation.last_update.is_stale(slot)?)?;
+
+        require!(
+            obligation.is_auto_deleverageable()?,
+            LendingError::AutoDeleverageThresholdNotReached
+        );
+
+        let (obligation_liquidity, liquidity_index) =
+            obligation.find_liquidity_in_borrows(reserve.key())?;
+        let (obligation_collateral, collateral_index) =
+            obligation.find_collateral_in_deposits(reserve.key())?;
+
+        let exchange_rate = reserve
+            .liquidity
+            .receipt_exchange_rate(receipt_mint.supply)?;
+        let deleverage_amount = reserve.calculate_auto_deleverage(
+            obligation,
+            obligation_liquidity,
+            obligation_collateral,
+            exchange_rate,
+        )?;
+        let receipt_amount = reserve
+            .liquidity
+            .liquidity_to_receipt(deleverage_amount, exchange_rate)?
+            .min(obligation_collateral.deposited_amount);
+
+        // withdrawn collateral stays in the reserve to repay the debt
+        let repay_amount = reserve
+            .liquidity
+            .redeem_receipt(receipt_amount, receipt_mint.supply)?
+            .min(obligation_liquidity.borrowed_amount);
+
+        require!(repay_amount > 0, LendingError::AutoDeleverageTooSmall);
+
+        obligation.repay(repay_amount, liquidity_index)?;
+        obligation.withdraw(receipt_amount, collateral_index)?;
+        reserve.liquidity.repay_liquidity(repay_amount)?;
+        obligation.last_update.mark_stale();
+        reserve.last_update.mark_stale();
+
+        let market_key = reserve.market.key();
+        let liquidity_mint_key = reserve.liquidity.mint.key();
+        let reserve_signer: &[&[u8]] =
+            reserve_signer!(market_key, liquidity_mint_key, reserve.bump);
+
+        burn_checked(
+            CpiContext::new(
+                receipt_token_program.to_account_info(),
+                BurnChecked {
+                    authority: reserve.to_account_info(),
+                    from: collateral_escrow.to_account_info(),
+                    mint: receipt_mint.to_account_info(),
+                },
+            )
+            .with_signer(&[reserve_signer]),
+            receipt_amount,
+            receipt_mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,363 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token_2022::{burn_checked, transfer_checked, BurnChecked, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use fixed::types::I80F48;
+
+use crate::{
+    bps_to_i80f48, calculate_pre_transfer_fee_amount, error::LendingError, reserve_signer,
+    transfer_checked_received, validate_obligation_refreshed, validate_reserve_refreshed, Market,
+    Obligation, Reserve, SafeMath, COLLATERAL_ESCROW_SEED, ID, INSURANCE_VAULT_SEED, MARKET_SEED,
+    RECEIPT_MINT_SEED, RESERVE_SEED,
+};
+
+/// Number of remaining accounts passed for each repay and withdraw reserve pair.
+pub const LIQUIDATION_STEP_ACCOUNTS_LEN: usize = 14;
+
+#[derive(Accounts)]
+pub struct BatchLiquidateObligation<'info> {
+    pub liquidator: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        constraint = obligation.market == market.key() @ LendingError::InvalidObligationMarket,
+        constraint = I80F48::from(obligation.deposited_value) > I80F48::ZERO @ LendingError::ObligationDepositsEmpty,
+        constraint = I80F48::from(obligation.borrowed_value) > I80F48::ZERO @ LendingError::ObligationBorrowsEmpty,
+        constraint = obligation.is_unhealthy() @ LendingError::ObligationHealthy,
+    )]
+    pub obligation: Account<'info, Obligation>,
+}
+
+impl<'info> BatchLiquidateObligation<'info> {
+    /// Remaining accounts are passed in groups of [`LIQUIDATION_STEP_ACCOUNTS_LEN`] per repay and withdraw reserve pair:
+    /// repay reserve, withdraw reserve, liquidity mint, collateral mint, reserve liquidity token account,
+    /// reserve collateral token account, withdraw insurance vault, liquidator liquidity token account,
+    /// liquidator collateral token account, receipt mint, collateral escrow, liquidity token program,
+    /// collateral token program, receipt token program.
+    pub fn handler(
+        ctx: Context<'_, '_, 'info, 'info, BatchLiquidateObligation<'info>>,
+    ) -> Result<()> {
+        let BatchLiquidateObligation {
+            liquidator,
+            market,
+            obligation,
+        } = ctx.accounts;
+
+        require!(
+            !ctx.remaining_accounts.is_empty()
+                && ctx
+                    .remaining_accounts
+                    .len()
+                    .is_multiple_of(LIQUIDATION_STEP_ACCOUNTS_LEN),
+            LendingError::InvalidLiquidationAccounts
+        );
+
+        let slot = Clock::get()?.slot;
+
+        validate_obligation_refreshed(obligation.last_update.is_stale(slot)?)?;
+
+        // reserves are only required to be fresh before the first step, since steps sharing a reserve
+        // cannot refresh it in between
+        for step_accounts in ctx
+            .remaining_accounts
+            .chunks_exact(LIQUIDATION_STEP_ACCOUNTS_LEN)
+        {
+            let [repay_reserve_info, withdraw_reserve_info, ..] = step_accounts else {
+                return err!(LendingError::InvalidLiquidationAccounts);
+            };
+
+            let repay_reserve = Account::<Reserve>::try_from(repay_reserve_info)?;
+            let withdraw_reserve = Account::<Reserve>::try_from(withdraw_reserve_info)?;
+
+            validate_reserve_refreshed(repay_reserve.last_update.is_stale(slot)?)?;
+            validate_reserve_refreshed(withdraw_reserve.last_update.is_stale(slot)?)?;
+        }
+
+        let mut liquidated_steps = 0;
+
+        for step_accounts in ctx
+            .remaining_accounts
+            .chunks_exact(LIQUIDATION_STEP_ACCOUNTS_LEN)
+        {
+            // stop once earlier steps have restored the obligation's health
+            if !obligation.is_unhealthy() {
+                break;
+            }
+
+            let [repay_reserve_info, withdraw_reserve_info, liquidity_mint_info, collateral_mint_info, reserve_liquidity_token_account_info, reserve_collateral_token_account_info, withdraw_insurance_vault_info, liquidator_liquidity_token_account_info, liquidator_collateral_token_account_info, receipt_mint_info, collateral_escrow_info, liquidity_token_program_info, collateral_token_program_info, receipt_token_program_info] =
+                step_accounts
+            else {
+                return err!(LendingError::InvalidLiquidationAccounts);
+            };
+
+            let mut repay_reserve = Account::<Reserve>::try_from(repay_reserve_info)?;
+            let mut withdraw_reserve = Account::<Reserve>::try_from(withdraw_reserve_info)?;
+            let liquidity_mint = InterfaceAccount::<Mint>::try_from(liquidity_mint_info)?;
+            let collateral_mint = InterfaceAccount::<Mint>::try_from(collateral_mint_info)?;
+            let reserve_liquidity_token_account =
+                InterfaceAccount::<TokenAccount>::try_from(reserve_liquidity_token_account_info)?;
+            let reserve_collateral_token_account =
+                InterfaceAccount::<TokenAccount>::try_from(reserve_collateral_token_account_info)?;
+            let liquidity_token_program =
+                Interface::<TokenInterface>::try_from(liquidity_token_program_info)?;
+            let receipt_mint = InterfaceAccount::<Mint>::try_from(receipt_mint_info)?;
+            let collateral_token_program =
+                Interface::<TokenInterface>::try_from(collateral_token_program_info)?;
+            let receipt_token_program =
+                Interface::<TokenInterface>::try_from(receipt_token_program_info)?;
+
+            require_keys_neq!(
+                repay_reserve.key(),
+                withdraw_reserve.key(),
+                LendingError::InvalidReserve
+            );
+
+            require_keys_eq!(
+                repay_reserve.market,
+                market.key(),
+                LendingError::InvalidReserveMarket
+            );
+
+            require_keys_eq!(
+                withdraw_reserve.market,
+                market.key(),
+                LendingError::InvalidReserveMarket
+            );
+
+            require_keys_eq!(
+                repay_reserve.liquidity.mint,
+                liquidity_mint.key(),
+                LendingError::InvalidReserveMint
+            );
+
+            require_keys_eq!(
+                withdraw_reserve.liquidity.mint,
+                collateral_mint.key(),
+                LendingError::InvalidReserveMint
+            );
+
+            // the reserve's associated token accounts, so the insurance vault cannot stand in for them
+            require_keys_eq!(
+                reserve_liquidity_token_account.key(),
+                get_associated_token_address_with_program_id(
+                    &repay_reserve.key(),
+                    &liquidity_mint.key(),
+                    &liquidity_token_program.key(),
+                ),
+                LendingError::InvalidReserveTokenAccount
+            );
+
+            require_keys_eq!(
+                reserve_collateral_token_account.key(),
+                get_associated_token_address_with_program_id(
+                    &withdraw_reserve.key(),
+                    &collateral_mint.key(),
+                    &collateral_token_program.key(),
+                ),
+                LendingError::InvalidReserveTokenAccount
+            );
+
+            let withdraw_insurance_vault_key = Pubkey::create_program_address(
+                &[
+                    INSURANCE_VAULT_SEED,
+                    withdraw_reserve.key().as_ref(),
+                    &[withdraw_reserve.insurance_vault_bump],
+                ],
+                &ID,
+            )
+            .map_err(|_| LendingError::InvalidInsuranceVault)?;
+
+            require_keys_eq!(
+                withdraw_insurance_vault_info.key(),
+                withdraw_insurance_vault_key,
+                LendingError::InvalidInsuranceVault
+            );
+
+            let receipt_mint_key = Pubkey::create_program_address(
+                &[
+                    RECEIPT_MINT_SEED,
+                    withdraw_reserve.key().as_ref(),
+                    &[withdraw_reserve.receipt_mint_bump],
+                ],
+                &ID,
+            )
+            .map_err(|_| LendingError::InvalidReceiptMint)?;
+
+            require_keys_eq!(
+                receipt_mint.key(),
+                receipt_mint_key,
+                LendingError::InvalidReceiptMint
+            );
+
+            let (collateral_escrow_key, _) = Pubkey::find_program_address(
+                &[
+                    COLLATERAL_ESCROW_SEED,
+                    obligation.key().as_ref(),
+                    withdraw_reserve.key().as_ref(),
+                ],
+                &ID,
+            );
+
+            require_keys_eq!(
+                collateral_escrow_info.key(),
+                collateral_escrow_key,
+                LendingError::InvalidCollateralEscrow
+            );
+
+            let (obligation_liquidity, liquidity_index) =
+                obligation.find_liquidity_in_borrows(repay_reserve.key())?;
+
+            require!(
+                I80F48::from(obligation_liquidity.market_value) > I80F48::ZERO,
+                LendingError::ObligationLiquidityEmpty
+            );
+
+            let (obligation_collateral, collateral_index) =
+                obligation.find_collateral_in_deposits(withdraw_reserve.key())?;
+
+            require!(
+                I80F48::from(obligation_collateral.market_value) > I80F48::ZERO,
+                LendingError::ObligationLiquidityEmpty
+            );
+
+            let (repay_amount, withdraw_amount) = withdraw_reserve.calculate_liquidation(
+                obligation,
+                obligation_liquidity,
+                obligation_collateral,
+                market.dust_threshold()?,
+            )?;
+
+            require!(
+                repay_amount > 0 && withdraw_amount > 0,
+                LendingError::LiquidationTooSmall
+            );
+
+            let liquidity_amount = withdraw_reserve
+                .liquidity
+                .redeem_receipt(withdraw_amount, receipt_mint.supply)?;
+            let insurance_fee =
+                withdraw_reserve.calculate_liquidation_insurance_fee(liquidity_amount)?;
+
+            let received_amount = transfer_checked_received(
+                CpiContext::new(
+                    liquidity_token_program.to_account_info(),
+                    TransferChecked {
+                        authority: liquidator.to_account_info(),
+                        from: liquidator_liquidity_token_account_info.to_account_info(),
+                        mint: liquidity_mint.to_account_info(),
+                        to: reserve_liquidity_token_account.to_account_info(),
+                    },
+                ),
+                calculate_pre_transfer_fee_amount(&liquidity_mint.to_account_info(), repay_amount)?,
+                liquidity_mint.decimals,
+            )?;
+
+            require!(
+                received_amount >= repay_amount,
+                LendingError::LiquidationRepayNotReceived
+            );
+
+            let market_key = withdraw_reserve.market;
+            let liquidity_mint_key = withdraw_reserve.liquidity.mint.key();
+            let reserve_signer: &[&[u8]] =
+                reserve_signer!(market_key, liquidity_mint_key, withdraw_reserve.bump);
+
+            burn_checked(
+                CpiContext::new(
+                    receipt_token_program.to_account_info(),
+                    BurnChecked {
+                        authority: withdraw_reserve.to_account_info(),
+                        from: collateral_escrow_info.to_account_info(),
+                        mint: receipt_mint.to_account_info(),
+                    },
+                )
+                .with_signer(&[reserve_signer]),
+                withdraw_amount,
+                receipt_mint.decimals,
+            )?;
+
+            transfer_checked(
+                CpiContext::new(
+                    collateral_token_program.to_account_info(),
+                    TransferChecked {
+                        authority: withdraw_reserve.to_account_info(),
+                        from: reserve_collateral_token_account.to_account_info(),
+                        mint: collateral_mint.to_account_info(),
+                        to: liquidator_collateral_token_account_info.to_account_info(),
+                    },
+                )
+                .with_signer(&[reserve_signer]),
+                liquidity_amount.safe_sub(insurance_fee)?,
+                collateral_mint.decimals,
+            )?;
+
+            // the insurance vault is credited with what it receives after any transfer fee
+            let insurance_amount = if insurance_fee > 0 {
+                transfer_checked_received(
+                    CpiContext::new(
+                        collateral_token_program.to_account_info(),
+                        TransferChecked {
+                            authority: withdraw_reserve.to_account_info(),
+                            from: reserve_collateral_token_account.to_account_info(),
+                            mint: collateral_mint.to_account_info(),
+                            to: withdraw_insurance_vault_info.to_account_info(),
+                        },
+                    )
+                    .with_signer(&[reserve_signer]),
+                    insurance_fee,
+                    collateral_mint.decimals,
+                )?
+            } else {
+                0
+            };
+
+            obligation.liquidate(
+                repay_amount,
+                liquidity_index,
+                withdraw_amount,
+                collateral_index,
+                bps_to_i80f48(withdraw_reserve.config.loan_to_value_bps)?,
+                bps_to_i80f48(withdraw_reserve.config.liquidation_threshold_bps)?,
+            )?;
+            repay_reserve.liquidity.repay_liquidity(repay_amount)?;
+            withdraw_reserve
+                .liquidity
+                .deposit_insurance(insurance_amount)?;
+
+            // persist reserves so later steps using the same reserve read the updated state
+            repay_reserve.exit(&ID)?;
+            withdraw_reserve.exit(&ID)?;
+
+            liquidated_steps += 1;
+        }
+
+        for step_accounts in ctx
+            .remaining_accounts
+            .chunks_exact(LIQUIDATION_STEP_ACCOUNTS_LEN)
+            .take(liquidated_steps)
+        {
+            let [repay_reserve_info, withdraw_reserve_info, ..] = step_accounts else {
+                return err!(LendingError::InvalidLiquidationAccounts);
+            };
+
+            let mut repay_reserve = Account::<Reserve>::try_from(repay_reserve_info)?;
+            let mut withdraw_reserve = Account::<Reserve>::try_from(withdraw_reserve_info)?;
+
+            repay_reserve.last_update.mark_stale();
+            withdraw_reserve.last_update.mark_stale();
+
+            repay_reserve.exit(&ID)?;
+            withdraw_reserve.exit(&ID)?;
+        }
+
+        obligation.last_update.mark_stale();
+
+        Ok(())
+    }
+}
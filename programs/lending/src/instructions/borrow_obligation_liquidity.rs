@@ -7,14 +7,21 @@ use anchor_spl::{
 use fixed::types::I80F48;
 
 use crate::{
-    error::LendingError, reserve_signer, validate_obligation_refreshed, validate_reserve_refreshed,
-    Obligation, Reserve, RESERVE_SEED,
+    error::LendingError, reserve_signer, validate_allowlisted, validate_obligation_authority,
+    validate_obligation_refreshed, validate_reserve_refreshed, AllowlistEntry, Market, Obligation,
+    ObligationDelegate, Reserve, ALLOWLIST_ENTRY_SEED, DELEGATE_PERMISSION_BORROW, MARKET_SEED,
+    OBLIGATION_DELEGATE_SEED, RESERVE_SEED,
 };
 
 #[derive(Accounts)]
 pub struct BorrowObligationLiquidity<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
     #[account(
         mut,
         seeds = [RESERVE_SEED, reserve.market.key().as_ref(), liquidity_mint.key().as_ref()],
@@ -23,23 +30,41 @@ pub struct BorrowObligationLiquidity<'info> {
     pub reserve: Account<'info, Reserve>,
     #[account(
         mut,
-        has_one = authority @ LendingError::InvalidObligationAuthority,
+        has_one = market @ LendingError::InvalidObligationMarket,
         constraint = obligation.market == reserve.market @ LendingError::InvalidObligationMarket,
         constraint = !obligation.deposits.is_empty() @ LendingError::ObligationDepositsEmpty,
         constraint = I80F48::from(obligation.deposited_value) != I80F48::ZERO @ LendingError::ObligationDepositsValueZero,
     )]
     pub obligation: Account<'info, Obligation>,
+    #[account(
+        seeds = [OBLIGATION_DELEGATE_SEED, obligation.key().as_ref(), obligation_delegate.delegate.as_ref()],
+        bump = obligation_delegate.bump,
+    )]
+    pub obligation_delegate: Option<Account<'info, ObligationDelegate>>,
+    /// CHECK: obligation's authority, which receives funds even when a delegate signs
+    #[account(address = obligation.authority @ LendingError::InvalidObligationAuthority)]
+    pub obligation_authority: UncheckedAccount<'info>,
     pub liquidity_mint: InterfaceAccount<'info, Mint>,
     #[account(
         init_if_needed,
         payer = authority,
         associated_token::mint = liquidity_mint,
-        associated_token::authority = authority,
+        associated_token::authority = obligation_authority,
         associated_token::token_program = token_program,
     )]
     pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        associated_token::mint = liquidity_mint,
+        associated_token::authority = reserve,
+        associated_token::token_program = token_program,
+    )]
     pub reserve_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [ALLOWLIST_ENTRY_SEED, market.key().as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -56,9 +81,22 @@ impl BorrowObligationLiquidity<'_> {
             reserve,
             reserve_token_account,
             token_program,
+            authority,
+            market,
+            allowlist_entry,
+            obligation_delegate,
             ..
         } = ctx.accounts;
 
+        validate_obligation_authority(
+            obligation,
+            authority.key(),
+            obligation_delegate.as_deref(),
+            DELEGATE_PERMISSION_BORROW,
+        )?;
+
+        validate_allowlisted(market, allowlist_entry.as_deref(), obligation.authority)?;
+
         let slot = Clock::get()?.slot;
 
         validate_reserve_refreshed(reserve.last_update.is_stale(slot)?)?;
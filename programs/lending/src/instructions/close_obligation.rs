@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{close_account, CloseAccount, TokenAccount, TokenInterface};
+
+use crate::{
+    error::LendingError, reserve_signer, Obligation, Reserve, COLLATERAL_ESCROW_SEED, ID,
+    RESERVE_SEED,
+};
+
+/// Number of remaining accounts passed for each collateral escrow closed with the obligation.
+pub const CLOSE_ESCROW_ACCOUNTS_LEN: usize = 3;
+
+#[derive(Accounts)]
+pub struct CloseObligation<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        close = receiver,
+        has_one = authority @ LendingError::InvalidObligationAuthority,
+        constraint = obligation.deposits.is_empty() @ LendingError::ObligationDepositsNotEmpty,
+        constraint = obligation.borrows.is_empty() @ LendingError::ObligationBorrowsNotEmpty,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    /// CHECK: any account chosen by the authority to receive the obligation's rent
+    #[account(mut)]
+    pub receiver: UncheckedAccount<'info>,
+}
+
+impl<'info> CloseObligation<'info> {
+    /// Remaining accounts are passed in groups of [`CLOSE_ESCROW_ACCOUNTS_LEN`] per collateral escrow to close:
+    /// reserve, collateral escrow, receipt token program. Escrow rent is refunded to the receiver.
+    pub fn handler(ctx: Context<'_, '_, 'info, 'info, CloseObligation<'info>>) -> Result<()> {
+        let CloseObligation {
+            obligation,
+            receiver,
+            ..
+        } = ctx.accounts;
+
+        require!(
+            ctx.remaining_accounts
+                .len()
+                .is_multiple_of(CLOSE_ESCROW_ACCOUNTS_LEN),
+            LendingError::InvalidCollateralEscrowAccounts
+        );
+
+        for escrow_accounts in ctx
+            .remaining_accounts
+            .chunks_exact(CLOSE_ESCROW_ACCOUNTS_LEN)
+        {
+            let [reserve_info, collateral_escrow_info, receipt_token_program_info] =
+                escrow_accounts
+            else {
+                return err!(LendingError::InvalidCollateralEscrowAccounts);
+            };
+
+            let reserve = Account::<Reserve>::try_from(reserve_info)?;
+            let collateral_escrow =
+                InterfaceAccount::<TokenAccount>::try_from(collateral_escrow_info)?;
+            let receipt_token_program =
+                Interface::<TokenInterface>::try_from(receipt_token_program_info)?;
+
+            require_keys_eq!(
+                reserve.market,
+                obligation.market,
+                LendingError::InvalidReserveMarket
+            );
+
+            let (collateral_escrow_key, _) = Pubkey::find_program_address(
+                &[
+                    COLLATERAL_ESCROW_SEED,
+                    obligation.key().as_ref(),
+                    reserve.key().as_ref(),
+                ],
+                &ID,
+            );
+
+            require_keys_eq!(
+                collateral_escrow.key(),
+                collateral_escrow_key,
+                LendingError::InvalidCollateralEscrow
+            );
+
+            require!(
+                collateral_escrow.amount == 0,
+                LendingError::CollateralEscrowNotEmpty
+            );
+
+            let market_key = reserve.market;
+            let liquidity_mint_key = reserve.liquidity.mint;
+            let reserve_signer: &[&[u8]] =
+                reserve_signer!(market_key, liquidity_mint_key, reserve.bump);
+
+            close_account(
+                CpiContext::new(
+                    receipt_token_program.to_account_info(),
+                    CloseAccount {
+                        account: collateral_escrow.to_account_info(),
+                        destination: receiver.to_account_info(),
+                        authority: reserve.to_account_info(),
+                    },
+                )
+                .with_signer(&[reserve_signer]),
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::TransferChecked,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::LendingError, reserve_signer, transfer_checked_received, validate_obligation_refreshed,
+    validate_reserve_refreshed, Obligation, Reserve, INSURANCE_VAULT_SEED, RESERVE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct CoverBadDebt<'info> {
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.market.key().as_ref(), liquidity_mint.key().as_ref()],
+        bump = reserve.bump,
+    )]
+    pub reserve: Account<'info, Reserve>,
+    #[account(
+        mut,
+        constraint = obligation.market == reserve.market @ LendingError::InvalidObligationMarket,
+        constraint = obligation.deposits.is_empty() @ LendingError::ObligationDepositsNotEmpty,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, reserve.key().as_ref()],
+        bump = reserve.insurance_vault_bump,
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = liquidity_mint,
+        associated_token::authority = reserve,
+        associated_token::token_program = token_program,
+    )]
+    pub reserve_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl CoverBadDebt<'_> {
+    pub fn handler(ctx: Context<CoverBadDebt>) -> Result<()> {
+        let CoverBadDebt {
+            insurance_vault,
+            liquidity_mint,
+            obligation,
+            reserve,
+            reserve_token_account,
+            token_program,
+        } = ctx.accounts;
+
+        let slot = Clock::get()?.slot;
+
+        validate_reserve_refreshed(reserve.last_update.is_stale(slot)?)?;
+        validate_obligation_refreshed(obligation.last_update.is_stale(slot)?)?;
+
+        let (obligation_liquidity, index) = obligation.find_liquidity_in_borrows(reserve.key())?;
+
+        require!(
+            obligation_liquidity.borrowed_amount > 0,
+            LendingError::ObligationLiquidityEmpty
+        );
+
+        let cover_amount = obligation_liquidity
+            .borrowed_amount
+            .min(reserve.liquidity.insurance_amount);
+
+        require!(cover_amount > 0, LendingError::InsufficientInsurance);
+
+        let market_key = reserve.market;
+        let liquidity_mint_key = reserve.liquidity.mint.key();
+        let reserve_signer: &[&[u8]] =
+            reserve_signer!(market_key, liquidity_mint_key, reserve.bump);
+
+        // only the amount received after any transfer fee repays the debt
+        let covered_amount = transfer_checked_received(
+            CpiContext::new(
+                token_program.to_account_info(),
+                TransferChecked {
+                    authority: reserve.to_account_info(),
+                    from: insurance_vault.to_account_info(),
+                    mint: liquidity_mint.to_account_info(),
+                    to: reserve_token_account.to_account_info(),
+                },
+            )
+            .with_signer(&[reserve_signer]),
+            cover_amount,
+            liquidity_mint.decimals,
+        )?;
+
+        obligation.repay(covered_amount, index)?;
+        reserve
+            .liquidity
+            .cover_bad_debt(cover_amount, covered_amount)?;
+        obligation.last_update.mark_stale();
+        reserve.last_update.mark_stale();
+
+        Ok(())
+    }
+}
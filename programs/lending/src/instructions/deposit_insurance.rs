@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::TransferChecked,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::LendingError, transfer_checked_received, Reserve, INSURANCE_VAULT_SEED, RESERVE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct DepositInsurance<'info> {
+    pub depositor: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.market.key().as_ref(), liquidity_mint.key().as_ref()],
+        bump = reserve.bump,
+    )]
+    pub reserve: Account<'info, Reserve>,
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, reserve.key().as_ref()],
+        bump = reserve.insurance_vault_bump,
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl DepositInsurance<'_> {
+    pub fn handler(ctx: Context<DepositInsurance>, amount: u64) -> Result<()> {
+        require!(amount > 0, LendingError::InvalidDepositAmount);
+
+        let DepositInsurance {
+            depositor,
+            depositor_token_account,
+            insurance_vault,
+            liquidity_mint,
+            reserve,
+            token_program,
+        } = ctx.accounts;
+
+        // only the amount received after any transfer fee is credited
+        let deposit_amount = transfer_checked_received(
+            CpiContext::new(
+                token_program.to_account_info(),
+                TransferChecked {
+                    authority: depositor.to_account_info(),
+                    from: depositor_token_account.to_account_info(),
+                    mint: liquidity_mint.to_account_info(),
+                    to: insurance_vault.to_account_info(),
+                },
+            ),
+            amount,
+            liquidity_mint.decimals,
+        )?;
+
+        reserve.liquidity.deposit_insurance(deposit_amount)?;
+
+        Ok(())
+    }
+}
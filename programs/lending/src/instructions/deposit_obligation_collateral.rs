@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::{transfer_checked, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::LendingError, validate_allowlisted, validate_obligation_authority,
+    validate_reserve_refreshed, AllowlistEntry, Market, Obligation, ObligationDelegate, Reserve,
+    ALLOWLIST_ENTRY_SEED, COLLATERAL_ESCROW_SEED, DELEGATE_PERMISSION_DEPOSIT, MARKET_SEED,
+    OBLIGATION_DELEGATE_SEED, RECEIPT_MINT_SEED, RESERVE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct DepositObligationCollateral<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        seeds = [RESERVE_SEED, reserve.market.key().as_ref(), reserve.liquidity.mint.key().as_ref()],
+        bump = reserve.bump,
+    )]
+    pub reserve: Account<'info, Reserve>,
+    #[account(
+        mut,
+        has_one = market @ LendingError::InvalidObligationMarket,
+        constraint = obligation.market == reserve.market @ LendingError::InvalidObligationMarket,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    #[account(
+        seeds = [OBLIGATION_DELEGATE_SEED, obligation.key().as_ref(), obligation_delegate.delegate.as_ref()],
+        bump = obligation_delegate.bump,
+    )]
+    pub obligation_delegate: Option<Account<'info, ObligationDelegate>>,
+    #[account(
+        seeds = [RECEIPT_MINT_SEED, reserve.key().as_ref()],
+        bump = reserve.receipt_mint_bump,
+    )]
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = receipt_token_program,
+    )]
+    pub receipt_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [COLLATERAL_ESCROW_SEED, obligation.key().as_ref(), reserve.key().as_ref()],
+        bump,
+        token::mint = receipt_mint,
+        token::authority = reserve,
+        token::token_program = receipt_token_program,
+    )]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [ALLOWLIST_ENTRY_SEED, market.key().as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    pub system_program: Program<'info, System>,
+    pub receipt_token_program: Interface<'info, TokenInterface>,
+}
+
+impl DepositObligationCollateral<'_> {
+    pub fn handler(ctx: Context<DepositObligationCollateral>, receipt_amount: u64) -> Result<()> {
+        require!(receipt_amount > 0, LendingError::InvalidDepositAmount);
+
+        let DepositObligationCollateral {
+            authority,
+            reserve,
+            obligation,
+            receipt_mint,
+            receipt_token_account,
+            collateral_escrow,
+            receipt_token_program,
+            market,
+            allowlist_entry,
+            obligation_delegate,
+            ..
+        } = ctx.accounts;
+
+        validate_obligation_authority(
+            obligation,
+            authority.key(),
+            obligation_delegate.as_deref(),
+            DELEGATE_PERMISSION_DEPOSIT,
+        )?;
+
+        validate_allowlisted(market, allowlist_entry.as_deref(), obligation.authority)?;
+
+        let slot = Clock::get()?.slot;
+
+        validate_reserve_refreshed(reserve.last_update.is_stale(slot)?)?;
+
+        require!(
+            reserve.config.loan_to_value_bps > 0,
+            LendingError::ReserveCollateralDisabled
+        );
+
+        obligation
+            .find_or_add_collateral_to_deposits(reserve.key())?
+            .deposit(receipt_amount)?;
+        obligation.last_update.mark_stale();
+
+        transfer_checked(
+            CpiContext::new(
+                receipt_token_program.to_account_info(),
+                TransferChecked {
+                    authority: authority.to_account_info(),
+                    from: receipt_token_account.to_account_info(),
+                    mint: receipt_mint.to_account_info(),
+                    to: collateral_escrow.to_account_info(),
+                },
+            ),
+            receipt_amount,
+            receipt_mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
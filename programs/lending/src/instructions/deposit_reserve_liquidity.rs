@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::{mint_to_checked, MintToChecked, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::LendingError, reserve_signer, transfer_checked_received, validate_allowlisted,
+    validate_reserve_refreshed, AllowlistEntry, Market, Reserve, ALLOWLIST_ENTRY_SEED, MARKET_SEED,
+    RECEIPT_MINT_SEED, RESERVE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct DepositReserveLiquidity<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.market.key().as_ref(), liquidity_mint.key().as_ref()],
+        bump = reserve.bump,
+        has_one = market @ LendingError::InvalidReserveMarket,
+    )]
+    pub reserve: Account<'info, Reserve>,
+    #[account(
+        address = reserve.liquidity.mint @ LendingError::InvalidReserveMint
+    )]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [RECEIPT_MINT_SEED, reserve.key().as_ref()],
+        bump = reserve.receipt_mint_bump,
+    )]
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = liquidity_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = liquidity_token_program,
+    )]
+    pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = receipt_token_program,
+    )]
+    pub receipt_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = liquidity_mint,
+        associated_token::authority = reserve,
+        associated_token::token_program = liquidity_token_program,
+    )]
+    pub reserve_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [ALLOWLIST_ENTRY_SEED, market.key().as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    pub system_program: Program<'info, System>,
+    pub liquidity_token_program: Interface<'info, TokenInterface>,
+    pub receipt_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl DepositReserveLiquidity<'_> {
+    pub fn handler(ctx: Context<DepositReserveLiquidity>, liquidity_amount: u64) -> Result<()> {
+        require!(liquidity_amount > 0, LendingError::InvalidDepositAmount);
+
+        let DepositReserveLiquidity {
+            authority,
+            authority_token_account,
+            liquidity_mint,
+            reserve,
+            reserve_token_account,
+            liquidity_token_program,
+            receipt_mint,
+            receipt_token_account,
+            receipt_token_program,
+            market,
+            allowlist_entry,
+            ..
+        } = ctx.accounts;
+
+        validate_allowlisted(market, allowlist_entry.as_deref(), authority.key())?;
+
+        let slot = Clock::get()?.slot;
+
+        validate_reserve_refreshed(reserve.last_update.is_stale(slot)?)?;
+
+        let deposit_amount = transfer_checked_received(
+            CpiContext::new(
+                liquidity_token_program.to_account_info(),
+                TransferChecked {
+                    authority: authority.to_account_info(),
+                    from: authority_token_account.to_account_info(),
+                    mint: liquidity_mint.to_account_info(),
+                    to: reserve_token_account.to_account_info(),
+                },
+            ),
+            liquidity_amount,
+            liquidity_mint.decimals,
+        )?;
+
+        let receipt_mint_amount = reserve
+            .liquidity
+            .deposit_liquidity(deposit_amount, receipt_mint.supply)?;
+        reserve.last_update.mark_stale();
+
+        let market_key = reserve.market.key();
+        let liquidity_mint_key = reserve.liquidity.mint.key();
+        let reserve_signer: &[&[u8]] =
+            reserve_signer!(market_key, liquidity_mint_key, reserve.bump);
+
+        mint_to_checked(
+            CpiContext::new(
+                receipt_token_program.to_account_info(),
+                MintToChecked {
+                    authority: reserve.to_account_info(),
+                    mint: receipt_mint.to_account_info(),
+                    to: receipt_token_account.to_account_info(),
+                },
+            )
+            .with_signer(&[reserve_signer]),
+            receipt_mint_amount,
+            receipt_mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
@@ -1,20 +1,26 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::Token,
-    token_2022::{mint_to_checked, transfer_checked, MintToChecked, TransferChecked},
+    token_2022::{mint_to_checked, MintToChecked, TransferChecked},
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
 use crate::{
-    error::LendingError, reserve_signer, validate_reserve_refreshed, Obligation, Reserve,
-    RECEIPT_MINT_SEED, RESERVE_SEED,
+    error::LendingError, reserve_signer, transfer_checked_received, validate_allowlisted,
+    validate_obligation_authority, validate_reserve_refreshed, AllowlistEntry, Market, Obligation,
+    ObligationDelegate, Reserve, ALLOWLIST_ENTRY_SEED, COLLATERAL_ESCROW_SEED,
+    DELEGATE_PERMISSION_DEPOSIT, MARKET_SEED, OBLIGATION_DELEGATE_SEED, RECEIPT_MINT_SEED,
+    RESERVE_SEED,
 };
 
 #[derive(Accounts)]
 pub struct DepositReserveLiquidityAndObligationCollateral<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
     #[account(
         mut,
         seeds = [RESERVE_SEED, reserve.market.key().as_ref(), collateral_mint.key().as_ref()],
@@ -23,10 +29,15 @@ pub struct DepositReserveLiquidityAndObligationCollateral<'info> {
     pub reserve: Account<'info, Reserve>,
     #[account(
         mut,
-        has_one = authority @ LendingError::InvalidObligationAuthority,
+        has_one = market @ LendingError::InvalidObligationMarket,
         constraint = obligation.market == reserve.market @ LendingError::InvalidObligationMarket,
     )]
     pub obligation: Account<'info, Obligation>,
+    #[account(
+        seeds = [OBLIGATION_DELEGATE_SEED, obligation.key().as_ref(), obligation_delegate.delegate.as_ref()],
+        bump = obligation_delegate.bump,
+    )]
+    pub obligation_delegate: Option<Account<'info, ObligationDelegate>>,
     #[account(
         address = reserve.liquidity.mint @ LendingError::InvalidReserveMint
     )]
@@ -46,21 +57,28 @@ pub struct DepositReserveLiquidityAndObligationCollateral<'info> {
     #[account(
         init_if_needed,
         payer = authority,
-        associated_token::mint = receipt_mint,
-        associated_token::authority = authority,
-        associated_token::token_program = receipt_token_program,
+        seeds = [COLLATERAL_ESCROW_SEED, obligation.key().as_ref(), reserve.key().as_ref()],
+        bump,
+        token::mint = receipt_mint,
+        token::authority = reserve,
+        token::token_program = receipt_token_program,
     )]
-    pub receipt_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         associated_token::mint = collateral_mint,
         associated_token::authority = reserve,
+        associated_token::token_program = collateral_token_program,
     )]
     pub reserve_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [ALLOWLIST_ENTRY_SEED, market.key().as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
     pub system_program: Program<'info, System>,
     pub collateral_token_program: Interface<'info, TokenInterface>,
-    pub receipt_token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub receipt_token_program: Interface<'info, TokenInterface>,
 }
 
 impl DepositReserveLiquidityAndObligationCollateral<'_> {
@@ -79,11 +97,23 @@ impl DepositReserveLiquidityAndObligationCollateral<'_> {
             reserve_token_account,
             collateral_token_program,
             receipt_mint,
-            receipt_token_account,
+            collateral_escrow,
             receipt_token_program,
+            market,
+            allowlist_entry,
+            obligation_delegate,
             ..
         } = ctx.accounts;
 
+        validate_obligation_authority(
+            obligation,
+            authority.key(),
+            obligation_delegate.as_deref(),
+            DELEGATE_PERMISSION_DEPOSIT,
+        )?;
+
+        validate_allowlisted(market, allowlist_entry.as_deref(), obligation.authority)?;
+
         let slot = Clock::get()?.slot;
 
         validate_reserve_refreshed(reserve.last_update.is_stale(slot)?)?;
@@ -93,7 +123,7 @@ impl DepositReserveLiquidityAndObligationCollateral<'_> {
             LendingError::ReserveCollateralDisabled
         );
 
-        transfer_checked(
+        let deposit_amount = transfer_checked_received(
             CpiContext::new(
                 collateral_token_program.to_account_info(),
                 TransferChecked {
@@ -107,17 +137,17 @@ impl DepositReserveLiquidityAndObligationCollateral<'_> {
             collateral_mint.decimals,
         )?;
 
+        let receipt_mint_amount = reserve
+            .liquidity
+            .deposit_liquidity(deposit_amount, receipt_mint.supply)?;
+
         obligation
             .find_or_add_collateral_to_deposits(reserve.key())?
-            .deposit(collateral_amount)?;
+            .deposit(receipt_mint_amount)?;
 
         obligation.last_update.mark_stale();
         reserve.last_update.mark_stale();
 
-        let receipt_mint_amount = reserve
-            .liquidity
-            .deposit_liquidity(collateral_amount, receipt_mint.supply)?;
-
         let market_key = reserve.market.key();
         let liquidity_mint_key = reserve.liquidity.mint.key();
         let reserve_signer: &[&[u8]] =
@@ -129,7 +159,7 @@ impl DepositReserveLiquidityAndObligationCollateral<'_> {
                 MintToChecked {
                     authority: reserve.to_account_info(),
                     mint: receipt_mint.to_account_info(),
-                    to: receipt_token_account.to_account_info(),
+                    to: collateral_escrow.to_account_info(),
                 },
             )
             .with_signer(&[reserve_signer]),
@@ -1,6 +1,6 @@
 use crate::{
     error::LendingError, reserve_signer, BpfInstructionLoader, InstructionLoader, IxIterator,
-    Reserve, ID, RESERVE_SEED,
+    Reserve, FLASH_REPAY_RESERVE_ACCOUNT_INDEX, ID, RESERVE_SEED,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
@@ -9,6 +9,9 @@ use anchor_spl::{
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
+/// Position of the reserve in the flash borrow instruction accounts.
+pub const FLASH_BORROW_RESERVE_ACCOUNT_INDEX: usize = 1;
+
 #[derive(Accounts)]
 pub struct FlashBorrowReserveLiquidity<'info> {
     #[account(mut)]
@@ -31,7 +34,8 @@ pub struct FlashBorrowReserveLiquidity<'info> {
     #[account(
         mut,
         associated_token::mint = liquidity_mint,
-        associated_token::authority = reserve
+        associated_token::authority = reserve,
+        associated_token::token_program = liquidity_token_program,
     )]
     pub reserve_token_account: InterfaceAccount<'info, TokenAccount>,
     pub system_program: Program<'info, System>,
@@ -54,6 +58,17 @@ impl FlashBorrowReserveLiquidity<'_> {
             ..
         } = ctx.accounts;
 
+        require!(
+            reserve.config.flash_loans_enabled,
+            LendingError::FlashLoansDisabled
+        );
+
+        require_gte!(
+            reserve.config.max_flash_loan_amount,
+            borrow_amount,
+            LendingError::FlashLoanTooLarge
+        );
+
         let instruction_loader = BpfInstructionLoader {
             instruction_sysvar_account_info: &instruction_sysvar.to_account_info(),
         };
@@ -64,6 +79,7 @@ impl FlashBorrowReserveLiquidity<'_> {
         let borrow_discriminator = crate::instruction::FlashBorrowReserveLiquidity::DISCRIMINATOR;
         let repay_discriminator = crate::instruction::FlashRepayReserveLiquidity::DISCRIMINATOR;
 
+        // each flash borrow pairs with exactly one later flash repay pointing back at its index
         for ix in ix_iterator {
             let ix = ix?;
 
@@ -72,17 +88,38 @@ impl FlashBorrowReserveLiquidity<'_> {
                 continue;
             }
 
-            require!(
-                ix.data[0..borrow_discriminator.len()].ne(borrow_discriminator),
-                LendingError::MultipleFlashBorrowsNotAllowed
-            );
+            if ix.data[0..borrow_discriminator.len()].eq(borrow_discriminator) {
+                // borrowing the same reserve again before repaying would nest the loans
+                require!(
+                    found_repay_ix
+                        || ix.accounts[FLASH_BORROW_RESERVE_ACCOUNT_INDEX].pubkey != reserve.key(),
+                    LendingError::MultipleFlashBorrowsNotAllowed
+                );
+
+                continue;
+            }
 
             if ix.data[0..repay_discriminator.len()].eq(repay_discriminator) {
+                let repay_ix_data = &ix.data[8..];
+                let borrow_ix_index = *repay_ix_data
+                    .get(8)
+                    .ok_or(LendingError::InvalidFlashRepayInstructionData)?;
+
+                // repay belongs to another flash borrow
+                if borrow_ix_index as usize != current_index {
+                    continue;
+                }
+
                 if found_repay_ix {
                     return Err(LendingError::MultipleFlashRepaysNotAllowed.into());
                 }
 
-                let repay_ix_data = &ix.data[8..];
+                require_keys_eq!(
+                    ix.accounts[FLASH_REPAY_RESERVE_ACCOUNT_INDEX].pubkey,
+                    reserve.key(),
+                    LendingError::InvalidFlashRepayReserve
+                );
+
                 let repay_amount = u64::from_le_bytes(
                     repay_ix_data[0..8]
                         .try_into()
@@ -95,14 +132,6 @@ impl FlashBorrowReserveLiquidity<'_> {
                     LendingError::InvalidFlashRepayAmount
                 );
 
-                let borrow_ix_index = repay_ix_data[8];
-
-                require_eq!(
-                    borrow_ix_index as usize,
-                    current_index,
-                    LendingError::InvalidFlashRepayInstructionData
-                );
-
                 found_repay_ix = true;
             }
         }
@@ -0,0 +1,157 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program::invoke},
+};
+use anchor_spl::{
+    token_2022::{transfer_checked, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::LendingError, reserve_signer, FlashLoanFeeExemption, Reserve, SafeMath,
+    FLASH_LOAN_FEE_EXEMPTION_SEED, RESERVE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct FlashLoan<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.market.key().as_ref(), liquidity_mint.key().as_ref()],
+        bump = reserve.bump,
+    )]
+    pub reserve: Account<'info, Reserve>,
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = liquidity_mint,
+        token::token_program = liquidity_token_program,
+    )]
+    pub receiver_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = liquidity_mint,
+        associated_token::authority = reserve,
+        associated_token::token_program = liquidity_token_program,
+    )]
+    pub reserve_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: program invoked with the loaned liquidity, which must repay it before returning
+    #[account(
+        executable,
+        constraint = receiver_program.key() != crate::ID @ LendingError::InvalidFlashLoanReceiver,
+    )]
+    pub receiver_program: UncheckedAccount<'info>,
+    #[account(
+        seeds = [FLASH_LOAN_FEE_EXEMPTION_SEED, reserve.market.as_ref(), fee_exemption.address.as_ref()],
+        bump = fee_exemption.bump,
+    )]
+    pub fee_exemption: Option<Account<'info, FlashLoanFeeExemption>>,
+    pub liquidity_token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> FlashLoan<'info> {
+    /// Remaining accounts and `data` are passed through to the receiver program as its instruction.
+    pub fn handler(
+        ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>,
+        amount: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        require!(amount > 0, LendingError::InvalidFlashLoanAmount);
+
+        let FlashLoan {
+            authority,
+            reserve,
+            liquidity_mint,
+            receiver_token_account,
+            reserve_token_account,
+            receiver_program,
+            fee_exemption,
+            liquidity_token_program,
+        } = ctx.accounts;
+
+        require!(
+            reserve.config.flash_loans_enabled,
+            LendingError::FlashLoansDisabled
+        );
+
+        require_gte!(
+            reserve.config.max_flash_loan_amount,
+            amount,
+            LendingError::FlashLoanTooLarge
+        );
+
+        if let Some(fee_exemption) = fee_exemption {
+            // the signer picks the receiver program, so only the signer can be exempted
+            require_keys_eq!(
+                fee_exemption.address,
+                authority.key(),
+                LendingError::InvalidFlashLoanFeeExemption
+            );
+        }
+
+        let (flash_loan_fee, platform_fee, insurance_fee) =
+            reserve.calculate_flash_loan_fees(amount, fee_exemption.as_deref())?;
+
+        let pre_balance = reserve_token_account.amount;
+
+        reserve.liquidity.borrow_liquidity(amount)?;
+
+        let market_key = reserve.market;
+        let liquidity_mint_key = reserve.liquidity.mint.key();
+        let reserve_signer: &[&[u8]] =
+            reserve_signer!(market_key, liquidity_mint_key, reserve.bump);
+
+        transfer_checked(
+            CpiContext::new(
+                liquidity_token_program.to_account_info(),
+                TransferChecked {
+                    authority: reserve.to_account_info(),
+                    from: reserve_token_account.to_account_info(),
+                    mint: liquidity_mint.to_account_info(),
+                    to: receiver_token_account.to_account_info(),
+                },
+            )
+            .with_signer(&[reserve_signer]),
+            amount,
+            liquidity_mint.decimals,
+        )?;
+
+        // the receiver cannot reenter this program, so the reserve is not modified during the callback
+        let receiver_ix = Instruction {
+            program_id: receiver_program.key(),
+            accounts: ctx
+                .remaining_accounts
+                .iter()
+                .map(|account| {
+                    if account.is_writable {
+                        AccountMeta::new(account.key(), account.is_signer)
+                    } else {
+                        AccountMeta::new_readonly(account.key(), account.is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        };
+
+        let mut receiver_accounts = ctx.remaining_accounts.to_vec();
+        receiver_accounts.push(receiver_program.to_account_info());
+
+        invoke(&receiver_ix, &receiver_accounts)?;
+
+        reserve_token_account.reload()?;
+
+        require_gte!(
+            reserve_token_account.amount,
+            pre_balance.safe_add(flash_loan_fee)?,
+            LendingError::FlashLoanNotRepaid
+        );
+
+        reserve.liquidity.repay_liquidity(amount)?;
+        reserve
+            .liquidity
+            .collect_flash_loan_fee(flash_loan_fee, platform_fee, insurance_fee)?;
+        reserve.last_update.mark_stale();
+
+        Ok(())
+    }
+}
@@ -1,27 +1,21 @@
 use crate::{
-    error::LendingError, BpfInstructionLoader, InstructionLoader, Market, Reserve, ID, MARKET_SEED,
-    RESERVE_SEED,
+    calculate_pre_transfer_fee_amount, error::LendingError, transfer_checked_received,
+    BpfInstructionLoader, FlashLoanFeeExemption, InstructionLoader, Reserve, SafeMath,
+    FLASH_BORROW_RESERVE_ACCOUNT_INDEX, FLASH_LOAN_FEE_EXEMPTION_SEED, ID, RESERVE_SEED,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token_2022::{transfer_checked, TransferChecked},
+    token_2022::TransferChecked,
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 use core::mem::size_of;
 
+/// Position of the reserve in the flash repay instruction accounts.
+pub const FLASH_REPAY_RESERVE_ACCOUNT_INDEX: usize = 1;
+
 #[derive(Accounts)]
 pub struct FlashRepayReserveLiquidity<'info> {
-    #[account(mut)]
     pub authority: Signer<'info>,
-    /// CHECK: no deserialization needed
-    pub market_authority: UncheckedAccount<'info>,
-    #[account(
-        seeds = [MARKET_SEED, market.name.as_bytes()],
-        bump = market.bump,
-        constraint = market.authority.key() == market_authority.key() @ LendingError::InvalidMarketAuthority,
-    )]
-    pub market: Account<'info, Market>,
     #[account(
         mut,
         seeds = [RESERVE_SEED, reserve.market.key().as_ref(), liquidity_mint.key().as_ref()],
@@ -34,20 +28,16 @@ pub struct FlashRepayReserveLiquidity<'info> {
     #[account(
         mut,
         associated_token::mint = liquidity_mint,
-        associated_token::authority = reserve
+        associated_token::authority = reserve,
+        associated_token::token_program = liquidity_token_program,
     )]
     pub reserve_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
-        init_if_needed,
-        payer = authority,
-        associated_token::mint = liquidity_mint,
-        associated_token::authority = market_authority,
-        associated_token::token_program = liquidity_token_program
+        seeds = [FLASH_LOAN_FEE_EXEMPTION_SEED, reserve.market.as_ref(), fee_exemption.address.as_ref()],
+        bump = fee_exemption.bump,
     )]
-    pub market_authority_token_account: InterfaceAccount<'info, TokenAccount>,
-    pub system_program: Program<'info, System>,
+    pub fee_exemption: Option<Account<'info, FlashLoanFeeExemption>>,
     pub liquidity_token_program: Interface<'info, TokenInterface>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
     /// CHECK: Instruction sysvar
     #[account(address = sysvar::instructions::ID)]
     pub instruction_sysvar: UncheckedAccount<'info>,
@@ -59,9 +49,6 @@ impl FlashRepayReserveLiquidity<'_> {
         repay_amount: u64,
         borrow_ix_index: u8,
     ) -> Result<()> {
-        // check borrow ix
-        // transfer fee
-        // transfer repay amount
         let FlashRepayReserveLiquidity {
             instruction_sysvar,
             reserve,
@@ -70,8 +57,7 @@ impl FlashRepayReserveLiquidity<'_> {
             liquidity_mint,
             liquidity_token_program,
             reserve_token_account,
-            market_authority_token_account,
-            ..
+            fee_exemption,
         } = ctx.accounts;
 
         let instruction_loader = BpfInstructionLoader {
@@ -99,7 +85,7 @@ impl FlashRepayReserveLiquidity<'_> {
 
         require_keys_eq!(
             reserve.key(),
-            borrow_ix.accounts[1].pubkey,
+            borrow_ix.accounts[FLASH_BORROW_RESERVE_ACCOUNT_INDEX].pubkey,
             LendingError::InvalidFlashRepayReserve
         );
 
@@ -115,15 +101,21 @@ impl FlashRepayReserveLiquidity<'_> {
             LendingError::InvalidFlashRepayAmount
         );
 
-        let flash_loan_fee = reserve
-            .config
-            .fees
-            .calculate_flash_loan_fee(borrowed_amount)?;
+        if let Some(fee_exemption) = fee_exemption {
+            // any program can invoke the flash repay, so only the signer can be exempted
+            require_keys_eq!(
+                fee_exemption.address,
+                authority.key(),
+                LendingError::InvalidFlashLoanFeeExemption
+            );
+        }
 
-        reserve.liquidity.repay_liquidity(repay_amount)?;
-        reserve.last_update.mark_stale();
+        let (flash_loan_fee, platform_fee, insurance_fee) =
+            reserve.calculate_flash_loan_fees(borrowed_amount, fee_exemption.as_deref())?;
 
-        transfer_checked(
+        // transfer fees are paid on top so the reserve receives the full repayment
+        let total_repay_amount = repay_amount.safe_add(flash_loan_fee)?;
+        let received_amount = transfer_checked_received(
             CpiContext::new(
                 liquidity_token_program.to_account_info(),
                 TransferChecked {
@@ -133,25 +125,23 @@ impl FlashRepayReserveLiquidity<'_> {
                     to: reserve_token_account.to_account_info(),
                 },
             ),
-            repay_amount,
+            calculate_pre_transfer_fee_amount(
+                &liquidity_mint.to_account_info(),
+                total_repay_amount,
+            )?,
             liquidity_mint.decimals,
         )?;
 
-        if flash_loan_fee > 0 {
-            transfer_checked(
-                CpiContext::new(
-                    liquidity_token_program.to_account_info(),
-                    TransferChecked {
-                        authority: authority.to_account_info(),
-                        from: authority_token_account.to_account_info(),
-                        mint: liquidity_mint.to_account_info(),
-                        to: market_authority_token_account.to_account_info(),
-                    },
-                ),
-                flash_loan_fee,
-                liquidity_mint.decimals,
-            )?;
-        }
+        require!(
+            received_amount >= total_repay_amount,
+            LendingError::FlashLoanNotRepaid
+        );
+
+        reserve.liquidity.repay_liquidity(repay_amount)?;
+        reserve
+            .liquidity
+            .collect_flash_loan_fee(flash_loan_fee, platform_fee, insurance_fee)?;
+        reserve.last_update.mark_stale();
 
         Ok(())
     }
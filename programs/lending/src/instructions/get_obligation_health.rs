@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use fixed::types::I80F48;
+
+use crate::{
+    error::LendingError, validate_obligation_refreshed, validate_reserve_refreshed, Market,
+    Obligation, Reserve, WrappedI80F48, ID, MARKET_SEED,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BorrowLiquidation {
+    /// Reserve where liquidity is borrowed from.
+    pub reserve: Pubkey,
+    /// Maximum amount of liquidity a liquidator can repay.
+    pub max_repay_amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DepositLiquidation {
+    /// Reserve where collateral is deposited to.
+    pub reserve: Pubkey,
+    /// Maximum amount of collateral receipts a liquidator can seize.
+    pub max_withdraw_amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ObligationHealth {
+    pub health_factor: WrappedI80F48,
+    pub is_unhealthy: bool,
+    pub borrows: Vec<BorrowLiquidation>,
+    pub deposits: Vec<DepositLiquidation>,
+}
+
+#[derive(Accounts)]
+pub struct GetObligationHealth<'info> {
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        constraint = obligation.market == market.key() @ LendingError::InvalidObligationMarket,
+    )]
+    pub obligation: Account<'info, Obligation>,
+}
+
+impl GetObligationHealth<'_> {
+    /// Remaining accounts are the deposit reserves of the obligation, in the same order as its deposits.
+    pub fn handler(ctx: Context<GetObligationHealth>) -> Result<ObligationHealth> {
+        let GetObligationHealth { market, obligation } = ctx.accounts;
+
+        let slot = Clock::get()?.slot;
+
+        validate_obligation_refreshed(obligation.last_update.is_stale(slot)?)?;
+
+        require!(
+            ctx.remaining_accounts.len() == obligation.deposits.len(),
+            LendingError::DepositReserveAccountsMismatch
+        );
+
+        let is_unhealthy = obligation.is_unhealthy();
+
+        let mut borrows: Vec<BorrowLiquidation> = obligation
+            .borrows
+            .iter()
+            .map(|liquidity| BorrowLiquidation {
+                reserve: liquidity.reserve,
+                max_repay_amount: 0,
+            })
+            .collect();
+        let mut deposits: Vec<DepositLiquidation> = obligation
+            .deposits
+            .iter()
+            .map(|collateral| DepositLiquidation {
+                reserve: collateral.reserve,
+                max_withdraw_amount: 0,
+            })
+            .collect();
+
+        if is_unhealthy {
+            for (obligation_collateral, (deposit_reserve_info, deposit)) in obligation
+                .deposits
+                .iter()
+                .zip(ctx.remaining_accounts.iter().zip(deposits.iter_mut()))
+            {
+                require_keys_eq!(
+                    *deposit_reserve_info.owner,
+                    ID,
+                    LendingError::InvalidAccountOwner
+                );
+
+                require_keys_eq!(
+                    obligation_collateral.reserve,
+                    deposit_reserve_info.key(),
+                    LendingError::InvalidReserve
+                );
+
+                let deposit_reserve =
+                    Reserve::try_deserialize(&mut deposit_reserve_info.data.borrow().as_ref())?;
+
+                validate_reserve_refreshed(deposit_reserve.last_update.is_stale(slot)?)?;
+
+                if I80F48::from(obligation_collateral.market_value) <= I80F48::ZERO {
+                    continue;
+                }
+
+                for (obligation_liquidity, borrow) in
+                    obligation.borrows.iter().zip(borrows.iter_mut())
+                {
+                    if I80F48::from(obligation_liquidity.market_value) <= I80F48::ZERO {
+                        continue;
+                    }
+
+                    let (repay_amount, withdraw_amount) = deposit_reserve.calculate_liquidation(
+                        obligation,
+                        obligation_liquidity,
+                        obligation_collateral,
+                        market.dust_threshold()?,
+                    )?;
+
+                    borrow.max_repay_amount = borrow.max_repay_amount.max(repay_amount);
+                    deposit.max_withdraw_amount = deposit.max_withdraw_amount.max(withdraw_amount);
+                }
+            }
+        }
+
+        Ok(ObligationHealth {
+            health_factor: obligation.health_factor,
+            is_unhealthy,
+            borrows,
+            deposits,
+        })
+    }
+}
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token;
 
-use crate::{Market, MARKET_SEED};
+use crate::{Market, DEFAULT_MAX_OBLIGATION_RESERVES, MARKET_SEED};
 
 #[derive(Accounts)]
 #[instruction(name: String)]
@@ -28,6 +29,15 @@ impl InitializeMarket<'_> {
             bump: ctx.bumps.market,
             authority: authority.key(),
             name,
+            dust_threshold_value: 0,
+            receipt_token_program: token::ID,
+            allowed_mint_extensions: 0,
+            listing_approver: authority.key(),
+            permissioned_listing: false,
+            compliance_authority: authority.key(),
+            permissioned_obligations: false,
+            max_obligation_deposits: DEFAULT_MAX_OBLIGATION_RESERVES,
+            max_obligation_borrows: DEFAULT_MAX_OBLIGATION_RESERVES,
         });
 
         Ok(())
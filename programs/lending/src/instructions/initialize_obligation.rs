@@ -1,42 +1,62 @@
 use anchor_lang::prelude::*;
 
 use crate::{
-    LastUpdate, NewLastUpdateArgs, NewObligationArgs, Obligation, Reserve, OBLIGATION_SEED,
-    RESERVE_SEED,
+    error::LendingError, validate_allowlisted, AllowlistEntry, LastUpdate, Market,
+    NewLastUpdateArgs, NewObligationArgs, Obligation, Reserve, ALLOWLIST_ENTRY_SEED, MARKET_SEED,
+    OBLIGATION_SEED, RESERVE_SEED,
 };
 
 #[derive(Accounts)]
+#[instruction(index: u16)]
 pub struct InitializeObligation<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
     #[account(
         mut,
         seeds = [RESERVE_SEED, reserve.market.key().as_ref(), reserve.liquidity.mint.key().as_ref()],
         bump = reserve.bump,
+        has_one = market @ LendingError::InvalidReserveMarket,
     )]
     pub reserve: Account<'info, Reserve>,
     #[account(
         init,
         payer = authority,
-        space = Obligation::DISCRIMINATOR.len() + Obligation::INIT_SPACE,
-        seeds = [OBLIGATION_SEED, authority.key().as_ref(), reserve.market.key().as_ref()],
+        space = Obligation::space(market.max_obligation_deposits, market.max_obligation_borrows),
+        seeds = [OBLIGATION_SEED, authority.key().as_ref(), reserve.market.key().as_ref(), &Obligation::index_seed(index)],
         bump
     )]
     pub obligation: Account<'info, Obligation>,
+    #[account(
+        seeds = [ALLOWLIST_ENTRY_SEED, market.key().as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
     pub system_program: Program<'info, System>,
 }
 
 impl InitializeObligation<'_> {
-    pub fn handler(ctx: Context<InitializeObligation>) -> Result<()> {
+    pub fn handler(ctx: Context<InitializeObligation>, index: u16) -> Result<()> {
         let InitializeObligation {
             obligation,
             reserve,
             authority,
+            market,
+            allowlist_entry,
             ..
         } = ctx.accounts;
 
+        validate_allowlisted(market, allowlist_entry.as_deref(), authority.key())?;
+
         **obligation = Obligation::new(NewObligationArgs {
             authority: authority.key(),
+            index,
+            max_deposits: market.max_obligation_deposits,
+            max_borrows: market.max_obligation_borrows,
             bump: ctx.bumps.obligation,
             last_update: LastUpdate::new(NewLastUpdateArgs {
                 slot: Clock::get()?.slot,
@@ -1,18 +1,36 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{
+    prelude::*,
+    system_program::{
+        allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+    },
+};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::Token,
-    token_interface::{Mint, TokenAccount, TokenInterface},
+    token_2022::{
+        self, initialize_mint2,
+        spl_token_2022::{
+            extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+            state::Mint as MintState,
+        },
+        InitializeMint2,
+    },
+    token_interface::{
+        metadata_pointer_initialize, spl_pod::optional_keys::OptionalNonZeroPubkey,
+        spl_token_metadata_interface::state::TokenMetadata, token_metadata_initialize,
+        MetadataPointerInitialize, Mint, TokenAccount, TokenInterface, TokenMetadataInitialize,
+    },
 };
 use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 
 use crate::{
-    error::LendingError, validate_bps, LastUpdate, Market, NewLastUpdateArgs, NewReserveConfigArgs,
-    NewReserveFeesArgs, NewReserveLiquidityArgs, Reserve, ReserveConfig, ReserveFees,
-    ReserveLiquidity, MARKET_SEED, RECEIPT_MINT_SEED, RESERVE_SEED,
+    error::LendingError, reserve_signer, validate_bps, validate_mint_extensions,
+    InsuranceWithdrawal, LastUpdate, ListingApproval, Market, NewLastUpdateArgs,
+    NewReserveConfigArgs, NewReserveFeesArgs, NewReserveLiquidityArgs, Reserve, ReserveConfig,
+    ReserveFees, ReserveLiquidity, INSURANCE_VAULT_SEED, LISTING_APPROVAL_SEED, MARKET_SEED,
+    RECEIPT_MINT_SEED, RESERVE_SEED,
 };
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct InitializeReserveArgs {
     pub optimal_utilization_rate_bps: u16,
     pub loan_to_value_bps: u16,
@@ -24,6 +42,12 @@ pub struct InitializeReserveArgs {
     pub max_borrow_rate_bps: u16,
     pub flash_loan_fee_bps: u16,
     pub platform_fee_bps: u16,
+    pub insurance_fee_bps: u16,
+    pub liquidation_insurance_fee_bps: u16,
+    pub flash_loans_enabled: bool,
+    pub max_flash_loan_amount: u64,
+    /// Symbol for the receipt metadata, required when the liquidity mint has no Token-2022 metadata
+    pub liquidity_symbol: Option<String>,
 }
 
 #[derive(Accounts)]
@@ -47,15 +71,17 @@ pub struct InitializeReserve<'info> {
     pub price_update_v2: Account<'info, PriceUpdateV2>,
     pub liquidity_mint: InterfaceAccount<'info, Mint>,
     #[account(
-        init,
-        payer = authority,
+        seeds = [LISTING_APPROVAL_SEED, market.key().as_ref(), liquidity_mint.key().as_ref()],
+        bump = listing_approval.bump,
+    )]
+    pub listing_approval: Option<Account<'info, ListingApproval>>,
+    /// CHECK: created by the handler with the market's receipt token program
+    #[account(
+        mut,
         seeds = [RECEIPT_MINT_SEED, reserve.key().as_ref()],
         bump,
-        mint::decimals = 6,
-        mint::authority = reserve,
-        mint::token_program = token_program,
     )]
-    pub receipt_mint: InterfaceAccount<'info, Mint>,
+    pub receipt_mint: UncheckedAccount<'info>,
     #[account(
         init_if_needed,
         payer = authority,
@@ -64,8 +90,21 @@ pub struct InitializeReserve<'info> {
         associated_token::token_program = liquidity_token_program,
     )]
     pub reserve_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = authority,
+        seeds = [INSURANCE_VAULT_SEED, reserve.key().as_ref()],
+        bump,
+        token::mint = liquidity_mint,
+        token::authority = reserve,
+        token::token_program = liquidity_token_program,
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    #[account(
+        address = market.receipt_token_program @ LendingError::InvalidReceiptTokenProgram,
+    )]
+    pub receipt_token_program: Interface<'info, TokenInterface>,
     pub liquidity_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
@@ -83,6 +122,11 @@ impl InitializeReserve<'_> {
             max_borrow_rate_bps,
             flash_loan_fee_bps,
             platform_fee_bps,
+            insurance_fee_bps,
+            liquidation_insurance_fee_bps,
+            flash_loans_enabled,
+            max_flash_loan_amount,
+            liquidity_symbol,
         } = args;
 
         validate_bps(optimal_utilization_rate_bps)?;
@@ -95,6 +139,8 @@ impl InitializeReserve<'_> {
         validate_bps(max_borrow_rate_bps)?;
         validate_bps(flash_loan_fee_bps)?;
         validate_bps(platform_fee_bps)?;
+        validate_bps(insurance_fee_bps)?;
+        validate_bps(liquidation_insurance_fee_bps)?;
 
         require_gt!(
             liquidation_threshold_bps,
@@ -115,26 +161,196 @@ impl InitializeReserve<'_> {
         );
 
         let InitializeReserve {
+            authority,
+            listing_approval,
             liquidity_mint,
             market,
             price_update_v2,
+            receipt_mint,
             reserve,
+            system_program,
+            receipt_token_program,
             ..
         } = ctx.accounts;
 
+        if market.permissioned_listing {
+            let listing_approval = listing_approval
+                .as_ref()
+                .ok_or(LendingError::ListingNotApproved)?;
+
+            require_keys_eq!(
+                listing_approval.price_update_v2,
+                price_update_v2.key(),
+                LendingError::InvalidListingPriceFeed
+            );
+        }
+
+        validate_mint_extensions(
+            &liquidity_mint.to_account_info(),
+            market.allowed_mint_extensions,
+        )?;
+
+        let market_key = market.key();
+        let liquidity_mint_key = liquidity_mint.key();
+        let reserve_key = reserve.key();
+        let reserve_signer: &[&[u8]] =
+            reserve_signer!(market_key, liquidity_mint_key, ctx.bumps.reserve);
+        let receipt_mint_signer: &[&[u8]] = &[
+            RECEIPT_MINT_SEED,
+            reserve_key.as_ref(),
+            &[ctx.bumps.receipt_mint],
+        ];
+
+        // Token-2022 receipt mints carry their own metadata, named after the market and liquidity mint
+        let receipt_metadata = if receipt_token_program.key() == token_2022::ID {
+            let (liquidity_symbol, uri) =
+                liquidity_mint_symbol_and_uri(liquidity_mint, liquidity_symbol)?;
+
+            Some(TokenMetadata {
+                update_authority: OptionalNonZeroPubkey::try_from(Some(reserve_key))?,
+                mint: receipt_mint.key(),
+                name: format!("{} lend-{}", market.name, liquidity_symbol),
+                symbol: format!("l{}", liquidity_symbol),
+                uri,
+                additional_metadata: vec![],
+            })
+        } else {
+            None
+        };
+
+        let extensions: &[ExtensionType] = if receipt_metadata.is_some() {
+            &[ExtensionType::MetadataPointer]
+        } else {
+            &[]
+        };
+        let space = ExtensionType::try_calculate_account_len::<MintState>(extensions)?;
+        // fund the metadata upfront as initializing it reallocates the mint
+        let metadata_space = match &receipt_metadata {
+            Some(metadata) => metadata.tlv_size_of()?,
+            None => 0,
+        };
+        let lamports = Rent::get()?.minimum_balance(space + metadata_space);
+
+        // anyone can send lamports to the receipt mint address ahead of time, so top up and
+        // claim a pre-funded account instead of failing in create_account
+        let receipt_mint_lamports = receipt_mint.lamports();
+
+        if receipt_mint_lamports == 0 {
+            create_account(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    CreateAccount {
+                        from: authority.to_account_info(),
+                        to: receipt_mint.to_account_info(),
+                    },
+                )
+                .with_signer(&[receipt_mint_signer]),
+                lamports,
+                space as u64,
+                &receipt_token_program.key(),
+            )?;
+        } else {
+            let shortfall = lamports.saturating_sub(receipt_mint_lamports);
+
+            if shortfall > 0 {
+                transfer(
+                    CpiContext::new(
+                        system_program.to_account_info(),
+                        Transfer {
+                            from: authority.to_account_info(),
+                            to: receipt_mint.to_account_info(),
+                        },
+                    ),
+                    shortfall,
+                )?;
+            }
+
+            allocate(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    Allocate {
+                        account_to_allocate: receipt_mint.to_account_info(),
+                    },
+                )
+                .with_signer(&[receipt_mint_signer]),
+                space as u64,
+            )?;
+
+            assign(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    Assign {
+                        account_to_assign: receipt_mint.to_account_info(),
+                    },
+                )
+                .with_signer(&[receipt_mint_signer]),
+                &receipt_token_program.key(),
+            )?;
+        }
+
+        if receipt_metadata.is_some() {
+            metadata_pointer_initialize(
+                CpiContext::new(
+                    receipt_token_program.to_account_info(),
+                    MetadataPointerInitialize {
+                        token_program_id: receipt_token_program.to_account_info(),
+                        mint: receipt_mint.to_account_info(),
+                    },
+                ),
+                Some(reserve_key),
+                Some(receipt_mint.key()),
+            )?;
+        }
+
+        initialize_mint2(
+            CpiContext::new(
+                receipt_token_program.to_account_info(),
+                InitializeMint2 {
+                    mint: receipt_mint.to_account_info(),
+                },
+            ),
+            liquidity_mint.decimals,
+            &reserve_key,
+            None,
+        )?;
+
+        if let Some(metadata) = receipt_metadata {
+            token_metadata_initialize(
+                CpiContext::new(
+                    receipt_token_program.to_account_info(),
+                    TokenMetadataInitialize {
+                        program_id: receipt_token_program.to_account_info(),
+                        metadata: receipt_mint.to_account_info(),
+                        update_authority: reserve.to_account_info(),
+                        mint_authority: reserve.to_account_info(),
+                        mint: receipt_mint.to_account_info(),
+                    },
+                )
+                .with_signer(&[reserve_signer]),
+                metadata.name,
+                metadata.symbol,
+                metadata.uri,
+            )?;
+        }
+
         reserve.set_inner(Reserve {
             bump: ctx.bumps.reserve,
             receipt_mint_bump: ctx.bumps.receipt_mint,
+            insurance_vault_bump: ctx.bumps.insurance_vault,
             config: ReserveConfig::new(NewReserveConfigArgs {
                 fees: ReserveFees::new(NewReserveFeesArgs {
                     flash_loan_fee_bps,
                     platform_fee_bps,
+                    insurance_fee_bps,
+                    liquidation_insurance_fee_bps,
                 }),
+                flash_loans_enabled,
                 liquidation_bonus_bps,
                 liquidation_threshold_bps,
                 liquidation_close_factor_bps,
                 loan_to_value_bps,
                 max_borrow_rate_bps,
+                max_flash_loan_amount,
                 optimal_borrow_rate_bps,
                 min_borrow_rate_bps,
                 optimal_utilization_rate_bps,
@@ -142,6 +358,7 @@ impl InitializeReserve<'_> {
             last_update: LastUpdate::new(NewLastUpdateArgs {
                 slot: Clock::get()?.slot,
             }),
+            insurance_withdrawal: InsuranceWithdrawal::default(),
             liquidity: ReserveLiquidity::new(NewReserveLiquidityArgs {
                 mint: liquidity_mint.key(),
                 price_update_v2: price_update_v2.key(),
@@ -153,3 +370,28 @@ impl InitializeReserve<'_> {
         Ok(())
     }
 }
+
+/// Reads the symbol and URI from the liquidity mint's Token-2022 metadata, falling back to the symbol passed by the market authority.
+fn liquidity_mint_symbol_and_uri(
+    liquidity_mint: &InterfaceAccount<Mint>,
+    liquidity_symbol: Option<String>,
+) -> Result<(String, String)> {
+    let liquidity_mint_info = liquidity_mint.to_account_info();
+
+    if *liquidity_mint_info.owner == token_2022::ID {
+        let data = liquidity_mint_info.try_borrow_data()?;
+        let mint = StateWithExtensions::<MintState>::unpack(&data)?;
+
+        if let Ok(metadata) = mint.get_variable_len_extension::<TokenMetadata>() {
+            if !metadata.symbol.is_empty() {
+                return Ok((metadata.symbol, metadata.uri));
+            }
+        }
+    }
+
+    let liquidity_symbol = liquidity_symbol
+        .filter(|symbol| !symbol.is_empty())
+        .ok_or(LendingError::MissingLiquiditySymbol)?;
+
+    Ok((liquidity_symbol, String::new()))
+}
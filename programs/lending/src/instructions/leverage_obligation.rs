@@ -0,0 +1,306 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program::invoke},
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::{mint_to_checked, transfer_checked, MintToChecked, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use fixed::types::I80F48;
+
+use crate::{
+    bps_to_i80f48, error::LendingError, reserve_signer, transfer_checked_received,
+    validate_allowlisted, validate_obligation_refreshed, validate_reserve_refreshed,
+    AllowlistEntry, Market, Obligation, Reserve, SafeMath, ALLOWLIST_ENTRY_SEED,
+    COLLATERAL_ESCROW_SEED, MARKET_SEED, MAX_BASIS_POINTS, RECEIPT_MINT_SEED, RESERVE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct LeverageObligation<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        has_one = authority @ LendingError::InvalidObligationAuthority,
+        has_one = market @ LendingError::InvalidObligationMarket,
+        constraint = obligation.market == deposit_reserve.market @ LendingError::InvalidObligationMarket,
+        constraint = obligation.market == borrow_reserve.market @ LendingError::InvalidObligationMarket,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, deposit_reserve.market.key().as_ref(), collateral_mint.key().as_ref()],
+        bump = deposit_reserve.bump,
+    )]
+    pub deposit_reserve: Account<'info, Reserve>,
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, borrow_reserve.market.key().as_ref(), liquidity_mint.key().as_ref()],
+        bump = borrow_reserve.bump,
+        constraint = borrow_reserve.key() != deposit_reserve.key() @ LendingError::InvalidReserve,
+    )]
+    pub borrow_reserve: Account<'info, Reserve>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [RECEIPT_MINT_SEED, deposit_reserve.key().as_ref()],
+        bump = deposit_reserve.receipt_mint_bump,
+    )]
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = collateral_token_program,
+    )]
+    pub authority_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = liquidity_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = liquidity_token_program,
+    )]
+    pub authority_liquidity_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [COLLATERAL_ESCROW_SEED, obligation.key().as_ref(), deposit_reserve.key().as_ref()],
+        bump,
+        token::mint = receipt_mint,
+        token::authority = deposit_reserve,
+        token::token_program = receipt_token_program,
+    )]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = deposit_reserve,
+        associated_token::token_program = collateral_token_program,
+    )]
+    pub deposit_reserve_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = liquidity_mint,
+        associated_token::authority = borrow_reserve,
+        associated_token::token_program = liquidity_token_program,
+    )]
+    pub borrow_reserve_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: program invoked with the borrowed liquidity, which must swap it into collateral before returning
+    #[account(
+        executable,
+        constraint = receiver_program.key() != crate::ID @ LendingError::InvalidFlashLoanReceiver,
+    )]
+    pub receiver_program: UncheckedAccount<'info>,
+    #[account(
+        seeds = [ALLOWLIST_ENTRY_SEED, market.key().as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+    pub system_program: Program<'info, System>,
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+    pub liquidity_token_program: Interface<'info, TokenInterface>,
+    pub receipt_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> LeverageObligation<'info> {
+    /// Flash borrows liquidity into the authority's token account and invokes the receiver program to swap it into collateral.
+    /// The collateral is deposited, and the flash loan is repaid by recording it as the obligation's borrow, so health is only checked once at the end.
+    /// Remaining accounts and `data` are passed through to the receiver program as its instruction.
+    /// Delegates are rejected: the signer picks the receiver and holds the borrowed liquidity during the swap, so it could keep the loan.
+    pub fn handler(
+        ctx: Context<'_, '_, 'info, 'info, LeverageObligation<'info>>,
+        collateral_amount: u64,
+        target_ltv_bps: u16,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        require!(collateral_amount > 0, LendingError::InvalidDepositAmount);
+        require_gt!(
+            MAX_BASIS_POINTS,
+            target_ltv_bps,
+            LendingError::InvalidBasisPoints
+        );
+
+        let LeverageObligation {
+            authority,
+            obligation,
+            deposit_reserve,
+            borrow_reserve,
+            collateral_mint,
+            liquidity_mint,
+            receipt_mint,
+            authority_collateral_token_account,
+            authority_liquidity_token_account,
+            collateral_escrow,
+            deposit_reserve_token_account,
+            borrow_reserve_token_account,
+            receiver_program,
+            collateral_token_program,
+            liquidity_token_program,
+            receipt_token_program,
+            market,
+            allowlist_entry,
+            ..
+        } = ctx.accounts;
+
+        validate_allowlisted(market, allowlist_entry.as_deref(), obligation.authority)?;
+
+        let slot = Clock::get()?.slot;
+
+        validate_reserve_refreshed(deposit_reserve.last_update.is_stale(slot)?)?;
+        validate_reserve_refreshed(borrow_reserve.last_update.is_stale(slot)?)?;
+        validate_obligation_refreshed(obligation.last_update.is_stale(slot)?)?;
+
+        require!(
+            deposit_reserve.config.loan_to_value_bps > 0,
+            LendingError::ReserveCollateralDisabled
+        );
+
+        // borrowed value is swapped into the same value of collateral, so the target is reached when
+        // (borrowed + borrow) / (deposited + borrow) = target ltv
+        let target_ltv = bps_to_i80f48(target_ltv_bps)?;
+        let target_borrow_value = I80F48::from(obligation.deposited_value)
+            .safe_add(deposit_reserve.market_value(collateral_amount)?)?
+            .safe_mul(target_ltv)?
+            .safe_sub(obligation.borrowed_value.into())?;
+
+        require!(
+            target_borrow_value > I80F48::ZERO,
+            LendingError::LeverageTargetReached
+        );
+
+        let borrow_amount = borrow_reserve
+            .market_amount(target_borrow_value.safe_div(I80F48::ONE.safe_sub(target_ltv)?)?)?;
+
+        require!(borrow_amount > 0, LendingError::BorrowTooSmall);
+
+        borrow_reserve.liquidity.borrow_liquidity(borrow_amount)?;
+
+        let market_key = deposit_reserve.market.key();
+        let liquidity_mint_key = borrow_reserve.liquidity.mint.key();
+        let borrow_reserve_signer: &[&[u8]] =
+            reserve_signer!(market_key, liquidity_mint_key, borrow_reserve.bump);
+
+        transfer_checked(
+            CpiContext::new(
+                liquidity_token_program.to_account_info(),
+                TransferChecked {
+                    authority: borrow_reserve.to_account_info(),
+                    from: borrow_reserve_token_account.to_account_info(),
+                    mint: liquidity_mint.to_account_info(),
+                    to: authority_liquidity_token_account.to_account_info(),
+                },
+            )
+            .with_signer(&[borrow_reserve_signer]),
+            borrow_amount,
+            liquidity_mint.decimals,
+        )?;
+
+        let pre_collateral_balance = authority_collateral_token_account.amount;
+
+        // the receiver cannot reenter this program, so the reserves and obligation are not modified during the callback
+        let receiver_ix = Instruction {
+            program_id: receiver_program.key(),
+            accounts: ctx
+                .remaining_accounts
+                .iter()
+                .map(|account| {
+                    if account.is_writable {
+                        AccountMeta::new(account.key(), account.is_signer)
+                    } else {
+                        AccountMeta::new_readonly(account.key(), account.is_signer)
+                    }
+                })
+                .collect(),
+            data,
+        };
+
+        let mut receiver_accounts = ctx.remaining_accounts.to_vec();
+        receiver_accounts.push(receiver_program.to_account_info());
+
+        invoke(&receiver_ix, &receiver_accounts)?;
+
+        authority_collateral_token_account.reload()?;
+
+        require_gt!(
+            authority_collateral_token_account.amount,
+            pre_collateral_balance,
+            LendingError::LeverageSwapNotReceived
+        );
+
+        let swap_amount = authority_collateral_token_account
+            .amount
+            .safe_sub(pre_collateral_balance)?;
+
+        let deposit_amount = transfer_checked_received(
+            CpiContext::new(
+                collateral_token_program.to_account_info(),
+                TransferChecked {
+                    authority: authority.to_account_info(),
+                    from: authority_collateral_token_account.to_account_info(),
+                    mint: collateral_mint.to_account_info(),
+                    to: deposit_reserve_token_account.to_account_info(),
+                },
+            ),
+            collateral_amount.safe_add(swap_amount)?,
+            collateral_mint.decimals,
+        )?;
+
+        let receipt_mint_amount = deposit_reserve
+            .liquidity
+            .deposit_liquidity(deposit_amount, receipt_mint.supply)?;
+
+        obligation.deposit_refreshed(
+            deposit_reserve.key(),
+            receipt_mint_amount,
+            deposit_reserve.market_value(deposit_amount)?,
+            bps_to_i80f48(deposit_reserve.config.loan_to_value_bps)?,
+            bps_to_i80f48(deposit_reserve.config.liquidation_threshold_bps)?,
+        )?;
+
+        // the flash loan is repaid by the obligation taking on the borrowed liquidity as debt
+        obligation.borrow_refreshed(
+            borrow_reserve.key(),
+            borrow_amount,
+            borrow_reserve.market_value(borrow_amount)?,
+        )?;
+
+        // single health check for the combined deposit and borrow
+        require!(
+            obligation.remaining_borrow_value()? >= I80F48::ZERO,
+            LendingError::BorrowTooLarge
+        );
+
+        obligation.last_update.mark_stale();
+        deposit_reserve.last_update.mark_stale();
+        borrow_reserve.last_update.mark_stale();
+
+        let collateral_mint_key = deposit_reserve.liquidity.mint.key();
+        let deposit_reserve_signer: &[&[u8]] =
+            reserve_signer!(market_key, collateral_mint_key, deposit_reserve.bump);
+
+        mint_to_checked(
+            CpiContext::new(
+                receipt_token_program.to_account_info(),
+                MintToChecked {
+                    authority: deposit_reserve.to_account_info(),
+                    mint: receipt_mint.to_account_info(),
+                    to: collateral_escrow.to_account_info(),
+                },
+            )
+            .with_signer(&[deposit_reserve_signer]),
+            receipt_mint_amount,
+            receipt_mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
@@ -1,20 +1,27 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_2022::{transfer_checked, TransferChecked},
+    token_2022::{burn_checked, transfer_checked, BurnChecked, TransferChecked},
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 use fixed::types::I80F48;
 
 use crate::{
-    error::LendingError, reserve_signer, validate_obligation_refreshed, validate_reserve_refreshed,
-    Obligation, Reserve, RESERVE_SEED,
+    calculate_pre_transfer_fee_amount, error::LendingError, reserve_signer,
+    transfer_checked_received, validate_obligation_refreshed, validate_reserve_refreshed, Market,
+    Obligation, Reserve, SafeMath, COLLATERAL_ESCROW_SEED, INSURANCE_VAULT_SEED, MARKET_SEED,
+    RECEIPT_MINT_SEED, RESERVE_SEED,
 };
 
 #[derive(Accounts)]
 pub struct LiquidateObligation<'info> {
     #[account(mut)]
     pub liquidator: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
     #[account(
         mut,
         seeds = [RESERVE_SEED, repay_reserve.market.key().as_ref(), liquidity_mint.key().as_ref()],
@@ -29,23 +36,48 @@ pub struct LiquidateObligation<'info> {
     pub withdraw_reserve: Account<'info, Reserve>,
     #[account(
         mut,
+        constraint = obligation.market == market.key() @ LendingError::InvalidObligationMarket,
         constraint = obligation.market == withdraw_reserve.market @ LendingError::InvalidObligationMarket,
         constraint = obligation.market == repay_reserve.market @ LendingError::InvalidObligationMarket,
         constraint = I80F48::from(obligation.deposited_value) > I80F48::ZERO @ LendingError::ObligationDepositsEmpty,
         constraint = I80F48::from(obligation.borrowed_value) > I80F48::ZERO @ LendingError::ObligationBorrowsEmpty,
-        constraint = obligation.borrowed_value < obligation.weighted_unhealthy_borrow_value @ LendingError::ObligationHealthy,
+        constraint = obligation.is_unhealthy() @ LendingError::ObligationHealthy,
     )]
     pub obligation: Account<'info, Obligation>,
     pub liquidity_mint: InterfaceAccount<'info, Mint>,
     pub collateral_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [RECEIPT_MINT_SEED, withdraw_reserve.key().as_ref()],
+        bump = withdraw_reserve.receipt_mint_bump,
+    )]
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [COLLATERAL_ESCROW_SEED, obligation.key().as_ref(), withdraw_reserve.key().as_ref()],
+        bump,
+    )]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         associated_token::mint = liquidity_mint,
-        associated_token::authority = repay_reserve
+        associated_token::authority = repay_reserve,
+        associated_token::token_program = liquidity_token_program,
     )]
     pub reserve_liquidity_token_account: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = withdraw_reserve,
+        associated_token::token_program = collateral_token_program,
+    )]
     pub reserve_collateral_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, withdraw_reserve.key().as_ref()],
+        bump = withdraw_reserve.insurance_vault_bump,
+    )]
+    pub withdraw_insurance_vault: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
     pub liquidator_liquidity_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
@@ -59,24 +91,30 @@ pub struct LiquidateObligation<'info> {
     pub system_program: Program<'info, System>,
     pub liquidity_token_program: Interface<'info, TokenInterface>,
     pub collateral_token_program: Interface<'info, TokenInterface>,
+    pub receipt_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl LiquidateObligation<'_> {
     pub fn handler(ctx: Context<LiquidateObligation>) -> Result<()> {
         let LiquidateObligation {
+            collateral_escrow,
             collateral_mint,
             collateral_token_program,
             liquidator,
             liquidator_collateral_token_account,
             liquidator_liquidity_token_account,
             liquidity_mint,
+            market,
             obligation,
             repay_reserve,
             reserve_collateral_token_account,
             reserve_liquidity_token_account,
             withdraw_reserve,
+            withdraw_insurance_vault,
             liquidity_token_program,
+            receipt_mint,
+            receipt_token_program,
             ..
         } = ctx.accounts;
 
@@ -106,6 +144,7 @@ impl LiquidateObligation<'_> {
             &obligation,
             obligation_liquidity,
             obligation_collateral,
+            market.dust_threshold()?,
         )?;
 
         require!(
@@ -113,7 +152,14 @@ impl LiquidateObligation<'_> {
             LendingError::LiquidationTooSmall
         );
 
-        transfer_checked(
+        let liquidity_amount = withdraw_reserve
+            .liquidity
+            .redeem_receipt(withdraw_amount, receipt_mint.supply)?;
+        let insurance_fee =
+            withdraw_reserve.calculate_liquidation_insurance_fee(liquidity_amount)?;
+
+        // liquidator covers any transfer fee so the full repay amount reaches the reserve
+        let received_amount = transfer_checked_received(
             CpiContext::new(
                 liquidity_token_program.to_account_info(),
                 TransferChecked {
@@ -123,15 +169,34 @@ impl LiquidateObligation<'_> {
                     to: reserve_liquidity_token_account.to_account_info(),
                 },
             ),
-            repay_amount,
+            calculate_pre_transfer_fee_amount(&liquidity_mint.to_account_info(), repay_amount)?,
             liquidity_mint.decimals,
         )?;
 
+        require!(
+            received_amount >= repay_amount,
+            LendingError::LiquidationRepayNotReceived
+        );
+
         let market_key = withdraw_reserve.market;
         let liquidity_mint_key = withdraw_reserve.liquidity.mint.key();
         let reserve_signer: &[&[u8]] =
             reserve_signer!(market_key, liquidity_mint_key, withdraw_reserve.bump);
 
+        burn_checked(
+            CpiContext::new(
+                receipt_token_program.to_account_info(),
+                BurnChecked {
+                    authority: withdraw_reserve.to_account_info(),
+                    from: collateral_escrow.to_account_info(),
+                    mint: receipt_mint.to_account_info(),
+                },
+            )
+            .with_signer(&[reserve_signer]),
+            withdraw_amount,
+            receipt_mint.decimals,
+        )?;
+
         transfer_checked(
             CpiContext::new(
                 collateral_token_program.to_account_info(),
@@ -143,15 +208,39 @@ impl LiquidateObligation<'_> {
                 },
             )
             .with_signer(&[reserve_signer]),
-            withdraw_amount,
+            liquidity_amount.safe_sub(insurance_fee)?,
             collateral_mint.decimals,
         )?;
 
+        // the insurance vault is credited with what it receives after any transfer fee
+        let insurance_amount = if insurance_fee > 0 {
+            transfer_checked_received(
+                CpiContext::new(
+                    collateral_token_program.to_account_info(),
+                    TransferChecked {
+                        authority: withdraw_reserve.to_account_info(),
+                        from: reserve_collateral_token_account.to_account_info(),
+                        mint: collateral_mint.to_account_info(),
+                        to: withdraw_insurance_vault.to_account_info(),
+                    },
+                )
+                .with_signer(&[reserve_signer]),
+                insurance_fee,
+                collateral_mint.decimals,
+            )?
+        } else {
+            0
+        };
+
         obligation.repay(repay_amount, liquidity_index)?;
         obligation.withdraw(withdraw_amount, collateral_index)?;
         repay_reserve.liquidity.repay_liquidity(repay_amount)?;
+        withdraw_reserve
+            .liquidity
+            .deposit_insurance(insurance_amount)?;
         obligation.last_update.mark_stale();
         repay_reserve.last_update.mark_stale();
+        withdraw_reserve.last_update.mark_stale();
 
         Ok(())
     }
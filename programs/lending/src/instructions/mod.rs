@@ -1,15 +1,54 @@
+pub mod accept_obligation;
+pub use accept_obligation::*;
+
+pub mod add_allowlist_entry;
+pub use add_allowlist_entry::*;
+
+pub mod add_flash_loan_fee_exemption;
+pub use add_flash_loan_fee_exemption::*;
+
+pub mod approve_reserve_listing;
+pub use approve_reserve_listing::*;
+
+pub mod auto_deleverage_obligation;
+pub use auto_deleverage_obligation::*;
+
+pub mod batch_liquidate_obligation;
+pub use batch_liquidate_obligation::*;
+
 pub mod borrow_obligation_liquidity;
 pub use borrow_obligation_liquidity::*;
 
+pub mod close_obligation;
+pub use close_obligation::*;
+
+pub mod cover_bad_debt;
+pub use cover_bad_debt::*;
+
+pub mod deposit_insurance;
+pub use deposit_insurance::*;
+
+pub mod deposit_obligation_collateral;
+pub use deposit_obligation_collateral::*;
+
+pub mod deposit_reserve_liquidity;
+pub use deposit_reserve_liquidity::*;
+
 pub mod deposit_reserve_liquidity_and_obligation_collateral;
 pub use deposit_reserve_liquidity_and_obligation_collateral::*;
 
 pub mod flash_borrow_reserve_liquidity;
 pub use flash_borrow_reserve_liquidity::*;
 
+pub mod flash_loan;
+pub use flash_loan::*;
+
 pub mod flash_repay_reserve_liquidity;
 pub use flash_repay_reserve_liquidity::*;
 
+pub mod get_obligation_health;
+pub use get_obligation_health::*;
+
 pub mod initialize_market;
 pub use initialize_market::*;
 
@@ -19,23 +58,71 @@ pub use initialize_obligation::*;
 pub mod initialize_reserve;
 pub use initialize_reserve::*;
 
+pub mod leverage_obligation;
+pub use leverage_obligation::*;
+
 pub mod liquidate_obligation;
 pub use liquidate_obligation::*;
 
+pub mod realloc_market;
+pub use realloc_market::*;
+
+pub mod realloc_obligation;
+pub use realloc_obligation::*;
+
+pub mod realloc_reserve;
+pub use realloc_reserve::*;
+
 pub mod redeem_fees;
 pub use redeem_fees::*;
 
+pub mod redeem_reserve_receipt;
+pub use redeem_reserve_receipt::*;
+
 pub mod refresh_obligation;
 pub use refresh_obligation::*;
 
 pub mod refresh_reserve;
 pub use refresh_reserve::*;
 
+pub mod remove_allowlist_entry;
+pub use remove_allowlist_entry::*;
+
+pub mod remove_flash_loan_fee_exemption;
+pub use remove_flash_loan_fee_exemption::*;
+
+pub mod remove_obligation_delegate;
+pub use remove_obligation_delegate::*;
+
 pub mod repay_obligation_liquidity;
 pub use repay_obligation_liquidity::*;
 
+pub mod request_insurance_withdrawal;
+pub use request_insurance_withdrawal::*;
+
+pub mod revoke_reserve_listing;
+pub use revoke_reserve_listing::*;
+
+pub mod set_auto_deleverage;
+pub use set_auto_deleverage::*;
+
+pub mod set_obligation_delegate;
+pub use set_obligation_delegate::*;
+
+pub mod transfer_obligation;
+pub use transfer_obligation::*;
+
+pub mod update_market;
+pub use update_market::*;
+
 pub mod update_reserve;
 pub use update_reserve::*;
 
+pub mod withdraw_insurance;
+pub use withdraw_insurance::*;
+
 pub mod withdraw_obligation_collateral;
 pub use withdraw_obligation_collateral::*;
+
+pub mod withdraw_obligation_collateral_receipt;
+pub use withdraw_obligation_collateral_receipt::*;
@@ -0,0 +1,56 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::Market;
+
+#[derive(Accounts)]
+pub struct ReallocMarket<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: deserialized by the handler, which migrates markets created with the original layout
+    #[account(mut, owner = crate::ID)]
+    pub market: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl ReallocMarket<'_> {
+    /// Markets created before fields were appended to the layout are too small to be read with it, so every
+    /// instruction taking them fails until they are migrated and resized here. The appended fields take the defaults
+    /// of `initialize_market`, which the market authority can then change with `update_market`.
+    pub fn handler(ctx: Context<ReallocMarket>) -> Result<()> {
+        let ReallocMarket {
+            payer,
+            market,
+            system_program,
+        } = ctx.accounts;
+
+        let market_state = Market::try_deserialize_migrated(&market.try_borrow_data()?)?;
+
+        let space = market
+            .data_len()
+            .max(Market::space(market_state.name.as_str()));
+        let shortfall = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(market.lamports());
+
+        if shortfall > 0 {
+            transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    Transfer {
+                        from: payer.to_account_info(),
+                        to: market.to_account_info(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+
+        market.resize(space)?;
+        market_state.try_serialize(&mut &mut market.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+use crate::{error::LendingError, Market, Obligation, MARKET_SEED};
+
+#[derive(Accounts)]
+pub struct ReallocObligation<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+    /// CHECK: deserialized by the handler, which migrates obligations created with the original layout
+    #[account(mut, owner = crate::ID)]
+    pub obligation: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl ReallocObligation<'_> {
+    /// Obligations created before fields were appended to the layout may not fit them, so the obligation is migrated
+    /// and resized here before being written back.
+    /// Until then an original obligation reads zero reserve limits, so refreshing it or adding a deposit or borrow
+    /// fails with `ObligationReallocRequired`, and obligations too full to fit the appended fields cannot be read at all.
+    /// Original obligations holding deposits are rejected: those deposits were recorded in liquidity units while the
+    /// receipt tokens stayed in the owner's wallet rather than a collateral escrow, so they are redeemed from there.
+    pub fn handler(ctx: Context<ReallocObligation>) -> Result<()> {
+        let ReallocObligation {
+            payer,
+            market,
+            obligation,
+            system_program,
+        } = ctx.accounts;
+
+        let mut obligation_state =
+            Obligation::try_deserialize_migrated(&obligation.try_borrow_data()?)?;
+
+        require_keys_eq!(
+            obligation_state.market,
+            market.key(),
+            LendingError::InvalidObligationMarket
+        );
+
+        if obligation_state.requires_realloc() {
+            require!(
+                obligation_state.deposits.is_empty(),
+                LendingError::OriginalObligationDeposits
+            );
+        }
+
+        // never shrink below the positions the obligation already holds
+        obligation_state.max_deposits = market
+            .max_obligation_deposits
+            .max(obligation_state.deposits.len() as u8);
+        obligation_state.max_borrows = market
+            .max_obligation_borrows
+            .max(obligation_state.borrows.len() as u8);
+        obligation_state.health_factor = obligation_state.calculate_health_factor()?.into();
+
+        let space = obligation.data_len().max(Obligation::space(
+            obligation_state.max_deposits,
+            obligation_state.max_borrows,
+        ));
+        let shortfall = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(obligation.lamports());
+
+        if shortfall > 0 {
+            transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    Transfer {
+                        from: payer.to_account_info(),
+                        to: obligation.to_account_info(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+
+        obligation.resize(space)?;
+        obligation_state.try_serialize(&mut &mut obligation.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+}
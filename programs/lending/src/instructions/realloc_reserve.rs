@@ -0,0 +1,79 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{error::LendingError, Reserve, INSURANCE_VAULT_SEED};
+
+#[derive(Accounts)]
+pub struct ReallocReserve<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: deserialized by the handler, which migrates reserves created with the original layout
+    #[account(mut, owner = crate::ID)]
+    pub reserve: UncheckedAccount<'info>,
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        seeds = [INSURANCE_VAULT_SEED, reserve.key().as_ref()],
+        bump,
+        token::mint = liquidity_mint,
+        token::authority = reserve,
+        token::token_program = liquidity_token_program,
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub liquidity_token_program: Interface<'info, TokenInterface>,
+}
+
+impl ReallocReserve<'_> {
+    /// Reserves created before the insurance vault and flash loan limits were added are too small to be read with the
+    /// current layout, so every instruction taking them fails until they are migrated and resized here. The insurance
+    /// vault those reserves lack is created alongside.
+    pub fn handler(ctx: Context<ReallocReserve>) -> Result<()> {
+        let ReallocReserve {
+            payer,
+            reserve,
+            liquidity_mint,
+            system_program,
+            ..
+        } = ctx.accounts;
+
+        let mut reserve_state = Reserve::try_deserialize_migrated(&reserve.try_borrow_data()?)?;
+
+        require_keys_eq!(
+            reserve_state.liquidity.mint,
+            liquidity_mint.key(),
+            LendingError::InvalidReserveMint
+        );
+
+        reserve_state.insurance_vault_bump = ctx.bumps.insurance_vault;
+
+        let space = reserve
+            .data_len()
+            .max(Reserve::DISCRIMINATOR.len() + Reserve::INIT_SPACE);
+        let shortfall = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(reserve.lamports());
+
+        if shortfall > 0 {
+            transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    Transfer {
+                        from: payer.to_account_info(),
+                        to: reserve.to_account_info(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+
+        reserve.resize(space)?;
+        reserve_state.try_serialize(&mut &mut reserve.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
+}
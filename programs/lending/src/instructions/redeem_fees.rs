@@ -6,8 +6,8 @@ use anchor_spl::{
 };
 
 use crate::{
-    error::LendingError, reserve_signer, validate_reserve_refreshed, Market, Reserve, MARKET_SEED,
-    RESERVE_SEED,
+    error::LendingError, reserve_signer, transfer_checked_received, validate_reserve_refreshed,
+    Market, Reserve, INSURANCE_VAULT_SEED, MARKET_SEED, RESERVE_SEED,
 };
 
 #[derive(Accounts)]
@@ -36,8 +36,19 @@ pub struct RedeemFees<'info> {
         associated_token::authority = authority,
     )]
     pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        associated_token::mint = liquidity_mint,
+        associated_token::authority = reserve,
+        associated_token::token_program = token_program,
+    )]
     pub reserve_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, reserve.key().as_ref()],
+        bump = reserve.insurance_vault_bump,
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -47,6 +58,7 @@ impl RedeemFees<'_> {
     pub fn handler(ctx: Context<RedeemFees>) -> Result<()> {
         let RedeemFees {
             authority_token_account,
+            insurance_vault,
             liquidity_mint,
             reserve,
             reserve_token_account,
@@ -58,6 +70,7 @@ impl RedeemFees<'_> {
         validate_reserve_refreshed(reserve.last_update.is_stale(slot)?)?;
 
         let redeemable_fees = reserve.liquidity.redeem_fees()?;
+        let sweepable_fees = reserve.liquidity.sweep_insurance_fees()?;
 
         let market_key = reserve.market.key();
         let liquidity_mint_key = reserve.liquidity.mint.key();
@@ -79,6 +92,26 @@ impl RedeemFees<'_> {
             liquidity_mint.decimals,
         )?;
 
+        if sweepable_fees > 0 {
+            // the insurance vault is credited with what it receives after any transfer fee
+            let insurance_amount = transfer_checked_received(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        authority: reserve.to_account_info(),
+                        from: reserve_token_account.to_account_info(),
+                        mint: liquidity_mint.to_account_info(),
+                        to: insurance_vault.to_account_info(),
+                    },
+                )
+                .with_signer(&[reserve_signer]),
+                sweepable_fees,
+                liquidity_mint.decimals,
+            )?;
+
+            reserve.liquidity.deposit_insurance(insurance_amount)?;
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::{burn_checked, transfer_checked, BurnChecked, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::LendingError, reserve_signer, validate_reserve_refreshed, Reserve, RECEIPT_MINT_SEED,
+    RESERVE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct RedeemReserveReceipt<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, reserve.market.key().as_ref(), liquidity_mint.key().as_ref()],
+        bump = reserve.bump,
+    )]
+    pub reserve: Account<'info, Reserve>,
+    #[account(
+        address = reserve.liquidity.mint @ LendingError::InvalidReserveMint
+    )]
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [RECEIPT_MINT_SEED, reserve.key().as_ref()],
+        bump = reserve.receipt_mint_bump,
+    )]
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = liquidity_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = liquidity_token_program,
+    )]
+    pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = receipt_token_program,
+    )]
+    pub receipt_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = liquidity_mint,
+        associated_token::authority = reserve,
+        associated_token::token_program = liquidity_token_program,
+    )]
+    pub reserve_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub liquidity_token_program: Interface<'info, TokenInterface>,
+    pub receipt_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl RedeemReserveReceipt<'_> {
+    pub fn handler(ctx: Context<RedeemReserveReceipt>, receipt_amount: u64) -> Result<()> {
+        require!(receipt_amount > 0, LendingError::InvalidWithdrawAmount);
+
+        let RedeemReserveReceipt {
+            authority,
+            authority_token_account,
+            liquidity_mint,
+            reserve,
+            reserve_token_account,
+            liquidity_token_program,
+            receipt_mint,
+            receipt_token_account,
+            receipt_token_program,
+            ..
+        } = ctx.accounts;
+
+        let slot = Clock::get()?.slot;
+
+        validate_reserve_refreshed(reserve.last_update.is_stale(slot)?)?;
+
+        let liquidity_amount = reserve
+            .liquidity
+            .redeem_receipt(receipt_amount, receipt_mint.supply)?;
+
+        require!(liquidity_amount > 0, LendingError::WithdrawTooSmall);
+
+        reserve.last_update.mark_stale();
+
+        burn_checked(
+            CpiContext::new(
+                receipt_token_program.to_account_info(),
+                BurnChecked {
+                    authority: authority.to_account_info(),
+                    from: receipt_token_account.to_account_info(),
+                    mint: receipt_mint.to_account_info(),
+                },
+            ),
+            receipt_amount,
+            receipt_mint.decimals,
+        )?;
+
+        let market_key = reserve.market.key();
+        let liquidity_mint_key = reserve.liquidity.mint.key();
+        let reserve_signer: &[&[u8]] =
+            reserve_signer!(market_key, liquidity_mint_key, reserve.bump);
+
+        transfer_checked(
+            CpiContext::new(
+                liquidity_token_program.to_account_info(),
+                TransferChecked {
+                    authority: reserve.to_account_info(),
+                    from: reserve_token_account.to_account_info(),
+                    mint: liquidity_mint.to_account_info(),
+                    to: authority_token_account.to_account_info(),
+                },
+            )
+            .with_signer(&[reserve_signer]),
+            liquidity_amount,
+            liquidity_mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
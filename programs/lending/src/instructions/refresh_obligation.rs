@@ -17,6 +17,11 @@ impl RefreshObligation<'_> {
     pub fn handler(ctx: Context<RefreshObligation>) -> Result<()> {
         let obligation = &mut ctx.accounts.obligation;
 
+        require!(
+            !obligation.requires_realloc(),
+            LendingError::ObligationReallocRequired
+        );
+
         let slot = Clock::get()?.slot;
         let account_info_iter = &mut ctx.remaining_accounts.iter().peekable();
 
@@ -57,7 +62,7 @@ impl RefreshObligation<'_> {
                 ],
                 &ID,
             )
-            .unwrap();
+            .map_err(|_| LendingError::InvalidReceiptMint)?;
 
             require_keys_eq!(
                 receipt_mint_info.key(),
@@ -133,6 +138,7 @@ impl RefreshObligation<'_> {
         obligation.borrowed_value = borrowed_value.into();
         obligation.weighted_allowed_borrow_value = weighted_allowed_borrow_value.into();
         obligation.weighted_unhealthy_borrow_value = weighted_unhealthy_borrow_value.into();
+        obligation.health_factor = obligation.calculate_health_factor()?.into();
 
         obligation.last_update.update_slot(slot);
 
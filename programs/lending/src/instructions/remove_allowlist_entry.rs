@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::LendingError, AllowlistEntry, Market, ALLOWLIST_ENTRY_SEED, MARKET_SEED};
+
+#[derive(Accounts)]
+pub struct RemoveAllowlistEntry<'info> {
+    #[account(mut)]
+    pub compliance_authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+        constraint = market.compliance_authority == compliance_authority.key() @ LendingError::InvalidComplianceAuthority,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        close = compliance_authority,
+        seeds = [ALLOWLIST_ENTRY_SEED, market.key().as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump,
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+}
+
+impl RemoveAllowlistEntry<'_> {
+    pub fn handler(_ctx: Context<RemoveAllowlistEntry>) -> Result<()> {
+        Ok(())
+    }
+}
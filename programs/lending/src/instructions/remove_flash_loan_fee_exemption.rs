@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::LendingError, FlashLoanFeeExemption, Market, FLASH_LOAN_FEE_EXEMPTION_SEED, MARKET_SEED,
+};
+
+#[derive(Accounts)]
+pub struct RemoveFlashLoanFeeExemption<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+        has_one = authority @ LendingError::InvalidMarketAuthority,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [FLASH_LOAN_FEE_EXEMPTION_SEED, market.key().as_ref(), fee_exemption.address.as_ref()],
+        bump = fee_exemption.bump,
+    )]
+    pub fee_exemption: Account<'info, FlashLoanFeeExemption>,
+}
+
+impl RemoveFlashLoanFeeExemption<'_> {
+    pub fn handler(_ctx: Context<RemoveFlashLoanFeeExemption>) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::LendingError, Obligation, ObligationDelegate, OBLIGATION_DELEGATE_SEED};
+
+#[derive(Accounts)]
+pub struct RemoveObligationDelegate<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ LendingError::InvalidObligationAuthority,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    #[account(
+        mut,
+        close = authority,
+        seeds = [OBLIGATION_DELEGATE_SEED, obligation.key().as_ref(), obligation_delegate.delegate.as_ref()],
+        bump = obligation_delegate.bump,
+    )]
+    pub obligation_delegate: Account<'info, ObligationDelegate>,
+}
+
+impl RemoveObligationDelegate<'_> {
+    pub fn handler(_ctx: Context<RemoveObligationDelegate>) -> Result<()> {
+        Ok(())
+    }
+}
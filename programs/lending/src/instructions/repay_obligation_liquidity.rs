@@ -1,14 +1,17 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token_2022::{transfer_checked, TransferChecked},
+    token_2022::TransferChecked,
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use crate::{error::LendingError, validate_reserve_refreshed, Obligation, Reserve, RESERVE_SEED};
+use crate::{
+    calculate_pre_transfer_fee_amount, error::LendingError, transfer_checked_received,
+    validate_reserve_refreshed, Obligation, Reserve, RESERVE_SEED,
+};
 
 #[derive(Accounts)]
 pub struct RepayObligationLiquidity<'info> {
-    pub authority: Signer<'info>,
+    pub payer: Signer<'info>,
     #[account(
         mut,
         seeds = [RESERVE_SEED, reserve.market.key().as_ref(), liquidity_mint.key().as_ref()],
@@ -17,35 +20,35 @@ pub struct RepayObligationLiquidity<'info> {
     pub reserve: Account<'info, Reserve>,
     #[account(
         mut,
-        has_one = authority @ LendingError::InvalidObligationAuthority,
         constraint = obligation.market == reserve.market @ LendingError::InvalidObligationMarket,
     )]
     pub obligation: Account<'info, Obligation>,
     pub liquidity_mint: InterfaceAccount<'info, Mint>,
     #[account(mut)]
-    pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         associated_token::mint = liquidity_mint,
-        associated_token::authority = reserve
+        associated_token::authority = reserve,
+        associated_token::token_program = token_program,
     )]
     pub reserve_token_account: InterfaceAccount<'info, TokenAccount>,
     pub token_program: Interface<'info, TokenInterface>,
 }
 
 impl RepayObligationLiquidity<'_> {
+    /// Repayment is permissionless, so anyone can pay down an obligation's debt from their own token account.
     pub fn handler(ctx: Context<RepayObligationLiquidity>, liquidity_amount: u64) -> Result<()> {
         require!(liquidity_amount > 0, LendingError::InvalidRepayAmount);
 
         let RepayObligationLiquidity {
-            authority,
-            authority_token_account,
+            payer,
+            payer_token_account,
             liquidity_mint,
             obligation,
             reserve,
             reserve_token_account,
             token_program,
-            ..
         } = ctx.accounts;
 
         let slot = Clock::get()?.slot;
@@ -65,25 +68,31 @@ impl RepayObligationLiquidity<'_> {
 
         require!(repay_amount > 0, LendingError::RepayTooSmall);
 
-        obligation.repay(repay_amount, index)?;
-        reserve.liquidity.repay_liquidity(repay_amount)?;
-        obligation.last_update.mark_stale();
-        reserve.last_update.mark_stale();
-
-        transfer_checked(
+        // payer covers any transfer fee so the full repay amount reaches the reserve
+        let received_amount = transfer_checked_received(
             CpiContext::new(
                 token_program.to_account_info(),
                 TransferChecked {
-                    authority: authority.to_account_info(),
-                    from: authority_token_account.to_account_info(),
+                    authority: payer.to_account_info(),
+                    from: payer_token_account.to_account_info(),
                     mint: liquidity_mint.to_account_info(),
                     to: reserve_token_account.to_account_info(),
                 },
             ),
-            repay_amount,
+            calculate_pre_transfer_fee_amount(&liquidity_mint.to_account_info(), repay_amount)?,
             liquidity_mint.decimals,
         )?;
 
+        require!(
+            received_amount >= repay_amount,
+            LendingError::RepayNotReceived
+        );
+
+        obligation.repay(repay_amount, index)?;
+        reserve.liquidity.repay_liquidity(repay_amount)?;
+        obligation.last_update.mark_stale();
+        reserve.last_update.mark_stale();
+
         Ok(())
     }
 }
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::LendingError, InsuranceWithdrawal, Market, Reserve, SafeMath,
+    INSURANCE_WITHDRAWAL_TIMELOCK, MARKET_SEED, RESERVE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct RequestInsuranceWithdrawal<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+        has_one = authority @ LendingError::InvalidMarketAuthority,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, market.key().as_ref(), reserve.liquidity.mint.key().as_ref()],
+        bump = reserve.bump,
+    )]
+    pub reserve: Account<'info, Reserve>,
+}
+
+impl RequestInsuranceWithdrawal<'_> {
+    pub fn handler(ctx: Context<RequestInsuranceWithdrawal>, amount: u64) -> Result<()> {
+        require!(amount > 0, LendingError::InvalidWithdrawAmount);
+
+        let reserve = &mut ctx.accounts.reserve;
+
+        require!(
+            amount <= reserve.liquidity.insurance_amount,
+            LendingError::InsufficientInsurance
+        );
+
+        // a new request replaces any pending one and restarts the timelock
+        reserve.insurance_withdrawal = InsuranceWithdrawal {
+            amount,
+            unlock_timestamp: Clock::get()?
+                .unix_timestamp
+                .safe_add(INSURANCE_WITHDRAWAL_TIMELOCK)?,
+        };
+
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::LendingError, ListingApproval, Market, LISTING_APPROVAL_SEED, MARKET_SEED};
+
+#[derive(Accounts)]
+pub struct RevokeReserveListing<'info> {
+    #[account(mut)]
+    pub approver: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+        constraint = market.listing_approver == approver.key() @ LendingError::InvalidListingApprover,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        close = approver,
+        seeds = [LISTING_APPROVAL_SEED, market.key().as_ref(), listing_approval.mint.as_ref()],
+        bump = listing_approval.bump,
+    )]
+    pub listing_approval: Account<'info, ListingApproval>,
+}
+
+impl RevokeReserveListing<'_> {
+    pub fn handler(_ctx: Context<RevokeReserveListing>) -> Result<()> {
+        Ok(())
+    }
+}
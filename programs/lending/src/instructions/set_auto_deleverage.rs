@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::LendingError, validate_obligation_authority, Obligation, ObligationDelegate,
+    DELEGATE_PERMISSION_WITHDRAW, MAX_BASIS_POINTS, OBLIGATION_DELEGATE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct SetAutoDeleverage<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub obligation: Account<'info, Obligation>,
+    #[account(
+        seeds = [OBLIGATION_DELEGATE_SEED, obligation.key().as_ref(), obligation_delegate.delegate.as_ref()],
+        bump = obligation_delegate.bump,
+    )]
+    pub obligation_delegate: Option<Account<'info, ObligationDelegate>>,
+}
+
+impl SetAutoDeleverage<'_> {
+    /// Escrowed collateral is already held under the reserve's authority, so enabling auto-deleveraging only records the threshold.
+    /// Deleveraging withdraws collateral, so a delegate needs the withdraw permission.
+    pub fn handler(ctx: Context<SetAutoDeleverage>, threshold_bps: u16) -> Result<()> {
+        require!(
+            threshold_bps < MAX_BASIS_POINTS,
+            LendingError::InvalidAutoDeleverageThreshold
+        );
+
+        let SetAutoDeleverage {
+            authority,
+            obligation,
+            obligation_delegate,
+        } = ctx.accounts;
+
+        validate_obligation_authority(
+            obligation,
+            authority.key(),
+            obligation_delegate.as_deref(),
+            DELEGATE_PERMISSION_WITHDRAW,
+        )?;
+
+        obligation.auto_deleverage_threshold_bps = threshold_bps;
+
+        Ok(())
+    }
+}
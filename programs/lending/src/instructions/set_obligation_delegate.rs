@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::LendingError, Obligation, ObligationDelegate, DELEGATE_PERMISSION_BORROW,
+    DELEGATE_PERMISSION_DEPOSIT, DELEGATE_PERMISSION_WITHDRAW, OBLIGATION_DELEGATE_SEED,
+};
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct SetObligationDelegate<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        has_one = authority @ LendingError::InvalidObligationAuthority,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = ObligationDelegate::DISCRIMINATOR.len() + ObligationDelegate::INIT_SPACE,
+        seeds = [OBLIGATION_DELEGATE_SEED, obligation.key().as_ref(), delegate.as_ref()],
+        bump,
+    )]
+    pub obligation_delegate: Account<'info, ObligationDelegate>,
+    pub system_program: Program<'info, System>,
+}
+
+impl SetObligationDelegate<'_> {
+    /// Repaying is permissionless, so there is no repay permission to delegate.
+    pub fn handler(
+        ctx: Context<SetObligationDelegate>,
+        delegate: Pubkey,
+        permissions: u8,
+        expires_at: i64,
+    ) -> Result<()> {
+        let all_permissions =
+            DELEGATE_PERMISSION_DEPOSIT | DELEGATE_PERMISSION_BORROW | DELEGATE_PERMISSION_WITHDRAW;
+
+        require!(
+            permissions != 0 && permissions & !all_permissions == 0,
+            LendingError::InvalidDelegatePermissions
+        );
+
+        ctx.accounts
+            .obligation_delegate
+            .set_inner(ObligationDelegate {
+                obligation: ctx.accounts.obligation.key(),
+                authority: ctx.accounts.authority.key(),
+                delegate,
+                permissions,
+                expires_at,
+                bump: ctx.bumps.obligation_delegate,
+            });
+
+        Ok(())
+    }
+}
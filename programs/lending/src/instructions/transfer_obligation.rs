@@ -0,0 +1,22 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::LendingError, Obligation};
+
+#[derive(Accounts)]
+pub struct TransferObligation<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority @ LendingError::InvalidObligationAuthority,
+    )]
+    pub obligation: Account<'info, Obligation>,
+}
+
+impl TransferObligation<'_> {
+    /// Nominates a new authority, which takes over the obligation once it accepts. Passing the default address cancels a pending transfer.
+    pub fn handler(ctx: Context<TransferObligation>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.obligation.pending_authority = new_authority;
+
+        Ok(())
+    }
+}
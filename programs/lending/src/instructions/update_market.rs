@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{token, token_2022};
+
+use crate::{error::LendingError, validate_obligation_reserve_limit, Market, MARKET_SEED};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct UpdateMarketArgs {
+    pub dust_threshold_value: Option<u64>,
+    pub receipt_token_program: Option<Pubkey>,
+    pub allowed_mint_extensions: Option<u16>,
+    pub listing_approver: Option<Pubkey>,
+    pub permissioned_listing: Option<bool>,
+    pub compliance_authority: Option<Pubkey>,
+    pub permissioned_obligations: Option<bool>,
+    pub max_obligation_deposits: Option<u8>,
+    pub max_obligation_borrows: Option<u8>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarket<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+        has_one = authority @ LendingError::InvalidMarketAuthority,
+    )]
+    pub market: Account<'info, Market>,
+}
+
+impl UpdateMarket<'_> {
+    pub fn handler(ctx: Context<UpdateMarket>, args: UpdateMarketArgs) -> Result<()> {
+        let UpdateMarketArgs {
+            dust_threshold_value,
+            receipt_token_program,
+            allowed_mint_extensions,
+            listing_approver,
+            permissioned_listing,
+            compliance_authority,
+            permissioned_obligations,
+            max_obligation_deposits,
+            max_obligation_borrows,
+        } = args;
+
+        let market = &mut ctx.accounts.market;
+
+        if let Some(dust_threshold_value) = dust_threshold_value {
+            market.dust_threshold_value = dust_threshold_value;
+        }
+
+        if let Some(receipt_token_program) = receipt_token_program {
+            require!(
+                receipt_token_program == token::ID || receipt_token_program == token_2022::ID,
+                LendingError::InvalidReceiptTokenProgram
+            );
+
+            market.receipt_token_program = receipt_token_program;
+        }
+
+        if let Some(allowed_mint_extensions) = allowed_mint_extensions {
+            market.allowed_mint_extensions = allowed_mint_extensions;
+        }
+
+        if let Some(listing_approver) = listing_approver {
+            market.listing_approver = listing_approver;
+        }
+
+        if let Some(permissioned_listing) = permissioned_listing {
+            market.permissioned_listing = permissioned_listing;
+        }
+
+        if let Some(compliance_authority) = compliance_authority {
+            market.compliance_authority = compliance_authority;
+        }
+
+        if let Some(permissioned_obligations) = permissioned_obligations {
+            market.permissioned_obligations = permissioned_obligations;
+        }
+
+        if let Some(max_obligation_deposits) = max_obligation_deposits {
+            validate_obligation_reserve_limit(max_obligation_deposits)?;
+            market.max_obligation_deposits = max_obligation_deposits;
+        }
+
+        if let Some(max_obligation_borrows) = max_obligation_borrows {
+            validate_obligation_reserve_limit(max_obligation_borrows)?;
+            market.max_obligation_borrows = max_obligation_borrows;
+        }
+
+        Ok(())
+    }
+}
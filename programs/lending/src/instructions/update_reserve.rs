@@ -14,6 +14,10 @@ pub struct UpdateReserveArgs {
     pub max_borrow_rate_bps: Option<u16>,
     pub flash_loan_fee_bps: Option<u16>,
     pub platform_fee_bps: Option<u16>,
+    pub insurance_fee_bps: Option<u16>,
+    pub liquidation_insurance_fee_bps: Option<u16>,
+    pub flash_loans_enabled: Option<bool>,
+    pub max_flash_loan_amount: Option<u64>,
 }
 
 #[derive(Accounts)]
@@ -46,6 +50,10 @@ impl UpdateReserve<'_> {
             max_borrow_rate_bps,
             flash_loan_fee_bps,
             platform_fee_bps,
+            insurance_fee_bps,
+            liquidation_insurance_fee_bps,
+            flash_loans_enabled,
+            max_flash_loan_amount,
         } = args;
 
         let reserve = &mut ctx.accounts.reserve;
@@ -110,6 +118,26 @@ impl UpdateReserve<'_> {
             reserve.config.fees.platform_fee_bps = platform_fee_bps;
         }
 
+        if let Some(insurance_fee_bps) = insurance_fee_bps {
+            validate_bps(insurance_fee_bps)?;
+
+            reserve.config.fees.insurance_fee_bps = insurance_fee_bps;
+        }
+
+        if let Some(liquidation_insurance_fee_bps) = liquidation_insurance_fee_bps {
+            validate_bps(liquidation_insurance_fee_bps)?;
+
+            reserve.config.fees.liquidation_insurance_fee_bps = liquidation_insurance_fee_bps;
+        }
+
+        if let Some(flash_loans_enabled) = flash_loans_enabled {
+            reserve.config.flash_loans_enabled = flash_loans_enabled;
+        }
+
+        if let Some(max_flash_loan_amount) = max_flash_loan_amount {
+            reserve.config.max_flash_loan_amount = max_flash_loan_amount;
+        }
+
         Ok(())
     }
 }
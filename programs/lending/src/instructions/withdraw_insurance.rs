@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::{transfer_checked, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::LendingError, reserve_signer, InsuranceWithdrawal, Market, Reserve,
+    INSURANCE_VAULT_SEED, MARKET_SEED, RESERVE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct WithdrawInsurance<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [MARKET_SEED, market.name.as_bytes()],
+        bump = market.bump,
+        has_one = authority @ LendingError::InvalidMarketAuthority,
+    )]
+    pub market: Account<'info, Market>,
+    #[account(
+        mut,
+        seeds = [RESERVE_SEED, market.key().as_ref(), liquidity_mint.key().as_ref()],
+        bump = reserve.bump,
+    )]
+    pub reserve: Account<'info, Reserve>,
+    pub liquidity_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = liquidity_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = token_program,
+    )]
+    pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, reserve.key().as_ref()],
+        bump = reserve.insurance_vault_bump,
+    )]
+    pub insurance_vault: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl WithdrawInsurance<'_> {
+    pub fn handler(ctx: Context<WithdrawInsurance>) -> Result<()> {
+        let WithdrawInsurance {
+            authority_token_account,
+            insurance_vault,
+            liquidity_mint,
+            reserve,
+            token_program,
+            ..
+        } = ctx.accounts;
+
+        require!(
+            reserve.insurance_withdrawal.is_requested(),
+            LendingError::InsuranceWithdrawalNotRequested
+        );
+
+        require!(
+            reserve
+                .insurance_withdrawal
+                .is_unlocked(Clock::get()?.unix_timestamp),
+            LendingError::InsuranceWithdrawalLocked
+        );
+
+        // insurance may have been drawn on to cover bad debt since the request
+        let withdraw_amount = reserve
+            .insurance_withdrawal
+            .amount
+            .min(reserve.liquidity.insurance_amount);
+
+        reserve.liquidity.withdraw_insurance(withdraw_amount)?;
+        reserve.insurance_withdrawal = InsuranceWithdrawal::default();
+
+        let market_key = reserve.market;
+        let liquidity_mint_key = reserve.liquidity.mint.key();
+        let reserve_signer: &[&[u8]] =
+            reserve_signer!(market_key, liquidity_mint_key, reserve.bump);
+
+        transfer_checked(
+            CpiContext::new(
+                token_program.to_account_info(),
+                TransferChecked {
+                    authority: reserve.to_account_info(),
+                    from: insurance_vault.to_account_info(),
+                    mint: liquidity_mint.to_account_info(),
+                    to: authority_token_account.to_account_info(),
+                },
+            )
+            .with_signer(&[reserve_signer]),
+            withdraw_amount,
+            liquidity_mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
@@ -1,15 +1,14 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::Token,
     token_2022::{burn_checked, transfer_checked, BurnChecked, TransferChecked},
     token_interface::{Mint, TokenAccount, TokenInterface},
 };
-use fixed::types::I80F48;
 
 use crate::{
-    bps_to_i80f48, error::LendingError, reserve_signer, validate_reserve_refreshed, Obligation,
-    Reserve, SafeConvert, SafeMath, RECEIPT_MINT_SEED, RESERVE_SEED,
+    error::LendingError, reserve_signer, validate_obligation_authority, validate_reserve_refreshed,
+    Obligation, ObligationDelegate, Reserve, COLLATERAL_ESCROW_SEED, DELEGATE_PERMISSION_WITHDRAW,
+    OBLIGATION_DELEGATE_SEED, RECEIPT_MINT_SEED, RESERVE_SEED,
 };
 
 #[derive(Accounts)]
@@ -24,10 +23,17 @@ pub struct WithdrawObligationCollateral<'info> {
     pub reserve: Account<'info, Reserve>,
     #[account(
         mut,
-        has_one = authority @ LendingError::InvalidObligationAuthority,
         constraint = obligation.market == reserve.market @ LendingError::InvalidObligationMarket,
     )]
     pub obligation: Account<'info, Obligation>,
+    #[account(
+        seeds = [OBLIGATION_DELEGATE_SEED, obligation.key().as_ref(), obligation_delegate.delegate.as_ref()],
+        bump = obligation_delegate.bump,
+    )]
+    pub obligation_delegate: Option<Account<'info, ObligationDelegate>>,
+    /// CHECK: obligation's authority, which receives funds even when a delegate signs
+    #[account(address = obligation.authority @ LendingError::InvalidObligationAuthority)]
+    pub obligation_authority: UncheckedAccount<'info>,
     pub collateral_mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
@@ -39,17 +45,26 @@ pub struct WithdrawObligationCollateral<'info> {
         init_if_needed,
         payer = authority,
         associated_token::mint = collateral_mint,
-        associated_token::authority = authority,
+        associated_token::authority = obligation_authority,
         associated_token::token_program = collateral_token_program,
     )]
     pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(
+        mut,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = reserve,
+        associated_token::token_program = collateral_token_program,
+    )]
     pub reserve_token_account: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut)]
-    pub receipt_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [COLLATERAL_ESCROW_SEED, obligation.key().as_ref(), reserve.key().as_ref()],
+        bump,
+    )]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
     pub system_program: Program<'info, System>,
     pub collateral_token_program: Interface<'info, TokenInterface>,
-    pub receipt_token_program: Program<'info, Token>,
+    pub receipt_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
@@ -58,7 +73,6 @@ impl WithdrawObligationCollateral<'_> {
         require!(receipt_amount > 0, LendingError::InvalidWithdrawAmount);
 
         let WithdrawObligationCollateral {
-            authority,
             authority_token_account,
             collateral_mint,
             obligation,
@@ -66,29 +80,25 @@ impl WithdrawObligationCollateral<'_> {
             reserve_token_account,
             collateral_token_program,
             receipt_mint,
-            receipt_token_account,
+            collateral_escrow,
             receipt_token_program,
+            authority,
+            obligation_delegate,
             ..
         } = ctx.accounts;
 
+        validate_obligation_authority(
+            obligation,
+            authority.key(),
+            obligation_delegate.as_deref(),
+            DELEGATE_PERMISSION_WITHDRAW,
+        )?;
+
         let slot = Clock::get()?.slot;
 
         validate_reserve_refreshed(reserve.last_update.is_stale(slot)?)?;
         validate_reserve_refreshed(obligation.last_update.is_stale(slot)?)?;
 
-        burn_checked(
-            CpiContext::new(
-                receipt_token_program.to_account_info(),
-                BurnChecked {
-                    authority: authority.to_account_info(),
-                    from: receipt_token_account.to_account_info(),
-                    mint: receipt_mint.to_account_info(),
-                },
-            ),
-            receipt_amount,
-            receipt_mint.decimals,
-        )?;
-
         let (obligation_collateral, index) =
             obligation.find_collateral_in_deposits(reserve.key())?;
 
@@ -97,61 +107,16 @@ impl WithdrawObligationCollateral<'_> {
             LendingError::ObligationCollateralEmpty
         );
 
-        let liquidity_amount = reserve
-            .liquidity
-            .redeem_receipt(receipt_amount, receipt_mint.supply)?;
-
-        let withdrawable_amount = if obligation.borrows.is_empty() {
-            if receipt_amount == u64::MAX {
-                obligation_collateral.deposited_amount
-            } else {
-                obligation_collateral.deposited_amount.min(liquidity_amount)
-            }
-        } else {
-            require!(
-                I80F48::from(obligation.deposited_value) > I80F48::ZERO,
-                LendingError::ObligationDepositsValueZero
-            );
-
-            let max_withdraw_value =
-                obligation.max_withdraw_value(bps_to_i80f48(reserve.config.loan_to_value_bps)?)?;
-
-            require!(
-                max_withdraw_value > I80F48::ZERO,
-                LendingError::MaxWithdrawValueZero
-            );
-
-            let withdraw_amount = if receipt_amount == u64::MAX {
-                let withdraw_value =
-                    max_withdraw_value.min(obligation_collateral.market_value.into());
-                let withdraw_pct =
-                    withdraw_value.safe_div(obligation_collateral.market_value.into())?;
-                withdraw_pct
-                    .safe_mul(obligation_collateral.deposited_amount.into())?
-                    .floor()
-                    .safe_to_u64()?
-                    .min(obligation_collateral.deposited_amount)
-            } else {
-                let withdraw_amount = liquidity_amount.min(obligation_collateral.deposited_amount);
-                let withdraw_pct = I80F48::from(withdraw_amount)
-                    .safe_div(obligation_collateral.deposited_amount.into())?;
-                let withdraw_value =
-                    I80F48::from(obligation_collateral.market_value).safe_mul(withdraw_pct)?;
-
-                require!(
-                    withdraw_value <= max_withdraw_value,
-                    LendingError::WithdrawTooLarge
-                );
-
-                withdraw_amount
-            };
-
-            withdraw_amount
-        };
+        let withdrawable_amount =
+            reserve.calculate_withdraw(receipt_amount, obligation, obligation_collateral)?;
 
         require!(withdrawable_amount > 0, LendingError::WithdrawTooSmall);
 
-        obligation.withdraw(liquidity_amount, index)?;
+        let liquidity_amount = reserve
+            .liquidity
+            .redeem_receipt(withdrawable_amount, receipt_mint.supply)?;
+
+        obligation.withdraw(withdrawable_amount, index)?;
         obligation.last_update.mark_stale();
         reserve.last_update.mark_stale();
 
@@ -160,6 +125,20 @@ impl WithdrawObligationCollateral<'_> {
         let reserve_signer: &[&[u8]] =
             reserve_signer!(market_key, liquidity_mint_key, reserve.bump);
 
+        burn_checked(
+            CpiContext::new(
+                receipt_token_program.to_account_info(),
+                BurnChecked {
+                    authority: reserve.to_account_info(),
+                    from: collateral_escrow.to_account_info(),
+                    mint: receipt_mint.to_account_info(),
+                },
+            )
+            .with_signer(&[reserve_signer]),
+            withdrawable_amount,
+            receipt_mint.decimals,
+        )?;
+
         transfer_checked(
             CpiContext::new(
                 collateral_token_program.to_account_info(),
@@ -171,7 +150,7 @@ impl WithdrawObligationCollateral<'_> {
                 },
             )
             .with_signer(&[reserve_signer]),
-            withdrawable_amount,
+            liquidity_amount,
             collateral_mint.decimals,
         )?;
 
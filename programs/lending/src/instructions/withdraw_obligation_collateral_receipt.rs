@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::{transfer_checked, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::{
+    error::LendingError, reserve_signer, validate_obligation_authority,
+    validate_obligation_refreshed, validate_reserve_refreshed, Obligation, ObligationDelegate,
+    Reserve, COLLATERAL_ESCROW_SEED, DELEGATE_PERMISSION_WITHDRAW, OBLIGATION_DELEGATE_SEED,
+    RECEIPT_MINT_SEED, RESERVE_SEED,
+};
+
+#[derive(Accounts)]
+pub struct WithdrawObligationCollateralReceipt<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [RESERVE_SEED, reserve.market.key().as_ref(), reserve.liquidity.mint.key().as_ref()],
+        bump = reserve.bump,
+    )]
+    pub reserve: Account<'info, Reserve>,
+    #[account(
+        mut,
+        constraint = obligation.market == reserve.market @ LendingError::InvalidObligationMarket,
+    )]
+    pub obligation: Account<'info, Obligation>,
+    #[account(
+        seeds = [OBLIGATION_DELEGATE_SEED, obligation.key().as_ref(), obligation_delegate.delegate.as_ref()],
+        bump = obligation_delegate.bump,
+    )]
+    pub obligation_delegate: Option<Account<'info, ObligationDelegate>>,
+    /// CHECK: obligation's authority, which receives funds even when a delegate signs
+    #[account(address = obligation.authority @ LendingError::InvalidObligationAuthority)]
+    pub obligation_authority: UncheckedAccount<'info>,
+    #[account(
+        seeds = [RECEIPT_MINT_SEED, reserve.key().as_ref()],
+        bump = reserve.receipt_mint_bump,
+    )]
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = receipt_mint,
+        associated_token::authority = obligation_authority,
+        associated_token::token_program = receipt_token_program,
+    )]
+    pub receipt_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [COLLATERAL_ESCROW_SEED, obligation.key().as_ref(), reserve.key().as_ref()],
+        bump,
+    )]
+    pub collateral_escrow: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub receipt_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl WithdrawObligationCollateralReceipt<'_> {
+    pub fn handler(
+        ctx: Context<WithdrawObligationCollateralReceipt>,
+        receipt_amount: u64,
+    ) -> Result<()> {
+        require!(receipt_amount > 0, LendingError::InvalidWithdrawAmount);
+
+        let WithdrawObligationCollateralReceipt {
+            reserve,
+            obligation,
+            receipt_mint,
+            receipt_token_account,
+            collateral_escrow,
+            receipt_token_program,
+            authority,
+            obligation_delegate,
+            ..
+        } = ctx.accounts;
+
+        validate_obligation_authority(
+            obligation,
+            authority.key(),
+            obligation_delegate.as_deref(),
+            DELEGATE_PERMISSION_WITHDRAW,
+        )?;
+
+        let slot = Clock::get()?.slot;
+
+        validate_reserve_refreshed(reserve.last_update.is_stale(slot)?)?;
+        validate_obligation_refreshed(obligation.last_update.is_stale(slot)?)?;
+
+        let (obligation_collateral, index) =
+            obligation.find_collateral_in_deposits(reserve.key())?;
+
+        require!(
+            obligation_collateral.deposited_amount > 0,
+            LendingError::ObligationCollateralEmpty
+        );
+
+        let withdraw_amount =
+            reserve.calculate_withdraw(receipt_amount, obligation, obligation_collateral)?;
+
+        require!(withdraw_amount > 0, LendingError::WithdrawTooSmall);
+
+        obligation.withdraw(withdraw_amount, index)?;
+        obligation.last_update.mark_stale();
+
+        let market_key = reserve.market.key();
+        let liquidity_mint_key = reserve.liquidity.mint.key();
+        let reserve_signer: &[&[u8]] =
+            reserve_signer!(market_key, liquidity_mint_key, reserve.bump);
+
+        transfer_checked(
+            CpiContext::new(
+                receipt_token_program.to_account_info(),
+                TransferChecked {
+                    authority: reserve.to_account_info(),
+                    from: collateral_escrow.to_account_info(),
+                    mint: receipt_mint.to_account_info(),
+                    to: receipt_token_account.to_account_info(),
+                },
+            )
+            .with_signer(&[reserve_signer]),
+            withdraw_amount,
+            receipt_mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
@@ -21,6 +21,14 @@ pub mod lending {
         InitializeMarket::handler(ctx, name)
     }
 
+    pub fn update_market(ctx: Context<UpdateMarket>, args: UpdateMarketArgs) -> Result<()> {
+        UpdateMarket::handler(ctx, args)
+    }
+
+    pub fn realloc_market(ctx: Context<ReallocMarket>) -> Result<()> {
+        ReallocMarket::handler(ctx)
+    }
+
     pub fn initialize_reserve(
         ctx: Context<InitializeReserve>,
         args: InitializeReserveArgs,
@@ -28,6 +36,14 @@ pub mod lending {
         InitializeReserve::handler(ctx, args)
     }
 
+    pub fn approve_reserve_listing(ctx: Context<ApproveReserveListing>) -> Result<()> {
+        ApproveReserveListing::handler(ctx)
+    }
+
+    pub fn revoke_reserve_listing(ctx: Context<RevokeReserveListing>) -> Result<()> {
+        RevokeReserveListing::handler(ctx)
+    }
+
     pub fn update_reserve(ctx: Context<UpdateReserve>, args: UpdateReserveArgs) -> Result<()> {
         UpdateReserve::handler(ctx, args)
     }
@@ -36,14 +52,54 @@ pub mod lending {
         RefreshReserve::handler(ctx)
     }
 
-    pub fn initialize_obligation(ctx: Context<InitializeObligation>) -> Result<()> {
-        InitializeObligation::handler(ctx)
+    pub fn realloc_reserve(ctx: Context<ReallocReserve>) -> Result<()> {
+        ReallocReserve::handler(ctx)
+    }
+
+    pub fn add_allowlist_entry(ctx: Context<AddAllowlistEntry>, wallet: Pubkey) -> Result<()> {
+        AddAllowlistEntry::handler(ctx, wallet)
+    }
+
+    pub fn remove_allowlist_entry(ctx: Context<RemoveAllowlistEntry>) -> Result<()> {
+        RemoveAllowlistEntry::handler(ctx)
+    }
+
+    pub fn initialize_obligation(ctx: Context<InitializeObligation>, index: u16) -> Result<()> {
+        InitializeObligation::handler(ctx, index)
+    }
+
+    pub fn realloc_obligation(ctx: Context<ReallocObligation>) -> Result<()> {
+        ReallocObligation::handler(ctx)
+    }
+
+    pub fn close_obligation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseObligation<'info>>,
+    ) -> Result<()> {
+        CloseObligation::handler(ctx)
     }
 
     pub fn refresh_obligation(ctx: Context<RefreshObligation>) -> Result<()> {
         RefreshObligation::handler(ctx)
     }
 
+    pub fn get_obligation_health(ctx: Context<GetObligationHealth>) -> Result<ObligationHealth> {
+        GetObligationHealth::handler(ctx)
+    }
+
+    pub fn deposit_reserve_liquidity(
+        ctx: Context<DepositReserveLiquidity>,
+        liquidity_amount: u64,
+    ) -> Result<()> {
+        DepositReserveLiquidity::handler(ctx, liquidity_amount)
+    }
+
+    pub fn redeem_reserve_receipt(
+        ctx: Context<RedeemReserveReceipt>,
+        receipt_amount: u64,
+    ) -> Result<()> {
+        RedeemReserveReceipt::handler(ctx, receipt_amount)
+    }
+
     pub fn deposit_reserve_liquidity_and_obligation_collateral(
         ctx: Context<DepositReserveLiquidityAndObligationCollateral>,
         collateral_amount: u64,
@@ -58,6 +114,20 @@ pub mod lending {
         WithdrawObligationCollateral::handler(ctx, collateral_amount)
     }
 
+    pub fn deposit_obligation_collateral(
+        ctx: Context<DepositObligationCollateral>,
+        receipt_amount: u64,
+    ) -> Result<()> {
+        DepositObligationCollateral::handler(ctx, receipt_amount)
+    }
+
+    pub fn withdraw_obligation_collateral_receipt(
+        ctx: Context<WithdrawObligationCollateralReceipt>,
+        receipt_amount: u64,
+    ) -> Result<()> {
+        WithdrawObligationCollateralReceipt::handler(ctx, receipt_amount)
+    }
+
     pub fn borrow_obligation_liquidity(
         ctx: Context<BorrowObligationLiquidity>,
         liquidity_amount: u64,
@@ -72,10 +142,57 @@ pub mod lending {
         RepayObligationLiquidity::handler(ctx, liquidity_amount)
     }
 
+    pub fn leverage_obligation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LeverageObligation<'info>>,
+        collateral_amount: u64,
+        target_ltv_bps: u16,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        LeverageObligation::handler(ctx, collateral_amount, target_ltv_bps, data)
+    }
+
     pub fn liquidate_obligation(ctx: Context<LiquidateObligation>) -> Result<()> {
         LiquidateObligation::handler(ctx)
     }
 
+    pub fn batch_liquidate_obligation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchLiquidateObligation<'info>>,
+    ) -> Result<()> {
+        BatchLiquidateObligation::handler(ctx)
+    }
+
+    pub fn transfer_obligation(
+        ctx: Context<TransferObligation>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        TransferObligation::handler(ctx, new_authority)
+    }
+
+    pub fn accept_obligation(ctx: Context<AcceptObligation>) -> Result<()> {
+        AcceptObligation::handler(ctx)
+    }
+
+    pub fn set_obligation_delegate(
+        ctx: Context<SetObligationDelegate>,
+        delegate: Pubkey,
+        permissions: u8,
+        expires_at: i64,
+    ) -> Result<()> {
+        SetObligationDelegate::handler(ctx, delegate, permissions, expires_at)
+    }
+
+    pub fn remove_obligation_delegate(ctx: Context<RemoveObligationDelegate>) -> Result<()> {
+        RemoveObligationDelegate::handler(ctx)
+    }
+
+    pub fn set_auto_deleverage(ctx: Context<SetAutoDeleverage>, threshold_bps: u16) -> Result<()> {
+        SetAutoDeleverage::handler(ctx, threshold_bps)
+    }
+
+    pub fn auto_deleverage_obligation(ctx: Context<AutoDeleverageObligation>) -> Result<()> {
+        AutoDeleverageObligation::handler(ctx)
+    }
+
     pub fn flash_borrow_reserve_liquidity(
         ctx: Context<FlashBorrowReserveLiquidity>,
         borrow_amount: u64,
@@ -91,7 +208,48 @@ pub mod lending {
         FlashRepayReserveLiquidity::handler(ctx, repay_amount, borrow_ix_index)
     }
 
+    pub fn flash_loan<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FlashLoan<'info>>,
+        amount: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        FlashLoan::handler(ctx, amount, data)
+    }
+
+    pub fn add_flash_loan_fee_exemption(
+        ctx: Context<AddFlashLoanFeeExemption>,
+        address: Pubkey,
+        fee_waiver_bps: u16,
+    ) -> Result<()> {
+        AddFlashLoanFeeExemption::handler(ctx, address, fee_waiver_bps)
+    }
+
+    pub fn remove_flash_loan_fee_exemption(
+        ctx: Context<RemoveFlashLoanFeeExemption>,
+    ) -> Result<()> {
+        RemoveFlashLoanFeeExemption::handler(ctx)
+    }
+
     pub fn redeem_fees(ctx: Context<RedeemFees>) -> Result<()> {
         RedeemFees::handler(ctx)
     }
+
+    pub fn deposit_insurance(ctx: Context<DepositInsurance>, amount: u64) -> Result<()> {
+        DepositInsurance::handler(ctx, amount)
+    }
+
+    pub fn cover_bad_debt(ctx: Context<CoverBadDebt>) -> Result<()> {
+        CoverBadDebt::handler(ctx)
+    }
+
+    pub fn request_insurance_withdrawal(
+        ctx: Context<RequestInsuranceWithdrawal>,
+        amount: u64,
+    ) -> Result<()> {
+        RequestInsuranceWithdrawal::handler(ctx, amount)
+    }
+
+    pub fn withdraw_insurance(ctx: Context<WithdrawInsurance>) -> Result<()> {
+        WithdrawInsurance::handler(ctx)
+    }
 }
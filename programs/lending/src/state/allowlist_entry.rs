@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+/// Wallet approved by the market's compliance authority to open and use obligations in a permissioned market.
+#[account]
+#[derive(InitSpace)]
+pub struct AllowlistEntry {
+    /// Address of market.
+    pub market: Pubkey,
+    /// Address of allowlisted wallet.
+    pub wallet: Pubkey,
+    /// Bump used for deriving signer seeds.
+    pub bump: u8,
+}
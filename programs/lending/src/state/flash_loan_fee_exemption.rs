@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+use fixed::types::I80F48;
+
+use crate::{bps_to_i80f48, SafeConvert, SafeMath};
+
+/// Signers in a market that pay a reduced flash loan fee.
+#[account]
+#[derive(InitSpace)]
+pub struct FlashLoanFeeExemption {
+    /// Address of market.
+    pub market: Pubkey,
+    /// Signer that is exempted.
+    pub address: Pubkey,
+    /// Portion of the flash loan fee that is waived, in basis points.
+    pub fee_waiver_bps: u16,
+    /// Bump used for deriving signer seeds.
+    pub bump: u8,
+}
+
+impl FlashLoanFeeExemption {
+    pub fn apply_waiver(&self, fee: u64) -> Result<u64> {
+        let waived_fee = I80F48::from(fee)
+            .safe_mul(bps_to_i80f48(self.fee_waiver_bps)?)?
+            .floor()
+            .safe_to_u64()?;
+
+        fee.safe_sub(waived_fee)
+    }
+}
@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+/// Approval from the market's listing approver for a mint and its oracle feed to be listed as a reserve.
+#[account]
+#[derive(InitSpace)]
+pub struct ListingApproval {
+    /// Address of market.
+    pub market: Pubkey,
+    /// Mint approved to be listed.
+    pub mint: Pubkey,
+    /// Pyth price update account approved for the mint.
+    pub price_update_v2: Pubkey,
+    /// Bump used for deriving signer seeds.
+    pub bump: u8,
+}
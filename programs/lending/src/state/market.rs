@@ -1,6 +1,11 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token;
+use fixed::types::I80F48;
+
+use crate::{SafeMath, DEFAULT_MAX_OBLIGATION_RESERVES, DUST_THRESHOLD_VALUE_SCALE};
 
 /// Markets are group of reserves which may be borrowed against and supplied to.
+/// Markets created with the original layout cannot be read until `realloc_market` migrates them.
 #[account]
 #[derive(InitSpace)]
 pub struct Market {
@@ -11,10 +16,72 @@ pub struct Market {
     /// Name of market
     #[max_len(0)] // used only for InitSpace.
     pub name: String,
+    // fields added after the original layout are appended below, so `realloc_market` can migrate original markets
+    /// Value in micro-USD (USD scaled by 10^6) below which a position is considered dust and is fully liquidated.
+    pub dust_threshold_value: u64,
+    /// Token program used to create receipt mints for new reserves, either Token or Token-2022.
+    pub receipt_token_program: Pubkey,
+    /// Bitmask of otherwise rejected Token-2022 extensions that liquidity mints in this market may have.
+    pub allowed_mint_extensions: u16,
+    /// Address which approves mints and oracle feeds for listing in a permissioned market.
+    pub listing_approver: Pubkey,
+    /// Whether new reserves require a listing approval.
+    pub permissioned_listing: bool,
+    /// Address which manages the obligation owner allowlist.
+    pub compliance_authority: Pubkey,
+    /// Whether only allowlisted wallets may open obligations, supply and borrow.
+    pub permissioned_obligations: bool,
+    /// Maximum number of reserves an obligation may deposit collateral to.
+    pub max_obligation_deposits: u8,
+    /// Maximum number of reserves an obligation may borrow liquidity from.
+    pub max_obligation_borrows: u8,
+}
+
+/// Layout markets were created with before fields were appended after `name`.
+#[derive(AnchorDeserialize)]
+struct OriginalMarket {
+    authority: Pubkey,
+    bump: u8,
+    name: String,
 }
 
 impl Market {
     pub fn space(name: &str) -> usize {
         Market::DISCRIMINATOR.len() + Market::INIT_SPACE + name.len()
     }
+
+    /// Deserializes a market, reading markets without room for the appended fields with the original layout and
+    /// giving those fields the defaults of `initialize_market`.
+    pub fn try_deserialize_migrated(data: &[u8]) -> Result<Self> {
+        if let Ok(market) = Market::try_deserialize(&mut &data[..]) {
+            return Ok(market);
+        }
+
+        require!(
+            data.starts_with(Market::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+
+        let original = OriginalMarket::deserialize(&mut &data[Market::DISCRIMINATOR.len()..])
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+
+        Ok(Self {
+            authority: original.authority,
+            bump: original.bump,
+            name: original.name,
+            dust_threshold_value: 0,
+            receipt_token_program: token::ID,
+            allowed_mint_extensions: 0,
+            listing_approver: original.authority,
+            permissioned_listing: false,
+            compliance_authority: original.authority,
+            permissioned_obligations: false,
+            max_obligation_deposits: DEFAULT_MAX_OBLIGATION_RESERVES,
+            max_obligation_borrows: DEFAULT_MAX_OBLIGATION_RESERVES,
+        })
+    }
+
+    pub fn dust_threshold(&self) -> Result<I80F48> {
+        I80F48::from(self.dust_threshold_value).safe_div(I80F48::from(DUST_THRESHOLD_VALUE_SCALE))
+    }
 }
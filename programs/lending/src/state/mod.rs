@@ -1,11 +1,23 @@
+pub mod allowlist_entry;
+pub use allowlist_entry::*;
+
+pub mod flash_loan_fee_exemption;
+pub use flash_loan_fee_exemption::*;
+
 pub mod last_update;
 pub use last_update::*;
 
+pub mod listing_approval;
+pub use listing_approval::*;
+
 pub mod market;
 pub use market::*;
 
 pub mod obligation;
 pub use obligation::*;
 
+pub mod obligation_delegate;
+pub use obligation_delegate::*;
+
 pub mod reserve;
 pub use reserve::*;
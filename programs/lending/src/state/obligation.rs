@@ -4,14 +4,15 @@ use anchor_lang::prelude::*;
 use fixed::types::I80F48;
 
 use crate::{
-    error::LendingError, LastUpdate, SafeConvert, SafeMath, SafeMathAssign, WrappedI80F48,
+    bps_to_i80f48, error::LendingError, LastUpdate, SafeConvert, SafeMath, SafeMathAssign,
+    WrappedI80F48,
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Default)]
 pub struct ObligationCollateral {
     /// Reserve where collateral is deposited to.
     pub reserve: Pubkey,
-    /// Amount of collateral deposited.
+    /// Amount of receipt tokens deposited as collateral, held in the obligation's collateral escrow.
     pub deposited_amount: u64,
     /// Last refreshed value of deposited collateral.
     pub market_value: WrappedI80F48,
@@ -79,13 +80,14 @@ pub struct Obligation {
     pub last_update: LastUpdate,
     /// Address of market.
     pub market: Pubkey,
-    /// Address which can deposit collateral and borrow liquidity.
+    /// Address which can deposit collateral and borrow liquidity. Kept at a fixed offset so owners can look up
+    /// obligations transferred to them, whose address stays derived from the authority that initialized them.
     pub authority: Pubkey,
     /// Deposited collateral for the obligation, unique by deposit reserve address
-    #[max_len(5)]
+    #[max_len(0)] // used only for InitSpace.
     pub deposits: Vec<ObligationCollateral>,
     /// Borrowed liquidity for the obligation, unique by borrow reserve address
-    #[max_len(5)]
+    #[max_len(0)] // used only for InitSpace.
     pub borrows: Vec<ObligationLiquidity>,
     /// Last refreshed value of deposits.
     pub deposited_value: WrappedI80F48,
@@ -97,12 +99,45 @@ pub struct Obligation {
     pub weighted_unhealthy_borrow_value: WrappedI80F48,
     /// Bump used for deriving signer seeds.
     pub bump: u8,
+    // fields added after the original layout are appended below, so original obligations with spare space still
+    // deserialize until `realloc_obligation` migrates them
+    /// Percentage of the unhealthy borrow value at which keepers may auto-deleverage the obligation, 0 if disabled.
+    pub auto_deleverage_threshold_bps: u16,
+    /// Ratio of unhealthy borrow value to borrowed value, eligible for liquidation at or below 1.
+    pub health_factor: WrappedI80F48,
+    /// Address nominated to take over the obligation, default if no transfer is pending.
+    pub pending_authority: Pubkey,
+    /// Index distinguishing obligations of the same initializing authority in a market.
+    pub index: u16,
+    /// Maximum number of deposits the obligation has space for, synced from the market on reallocation.
+    /// Zero for obligations created before reserve limits were configurable until they are reallocated.
+    pub max_deposits: u8,
+    /// Maximum number of borrows the obligation has space for, synced from the market on reallocation.
+    pub max_borrows: u8,
+}
+
+/// Layout obligations were created with before fields were appended after `bump`.
+#[derive(AnchorDeserialize)]
+struct OriginalObligation {
+    last_update: LastUpdate,
+    market: Pubkey,
+    authority: Pubkey,
+    deposits: Vec<ObligationCollateral>,
+    borrows: Vec<ObligationLiquidity>,
+    deposited_value: WrappedI80F48,
+    borrowed_value: WrappedI80F48,
+    weighted_allowed_borrow_value: WrappedI80F48,
+    weighted_unhealthy_borrow_value: WrappedI80F48,
+    bump: u8,
 }
 
 pub struct NewObligationArgs {
     pub last_update: LastUpdate,
     pub market: Pubkey,
     pub authority: Pubkey,
+    pub index: u16,
+    pub max_deposits: u8,
+    pub max_borrows: u8,
     pub bump: u8,
 }
 
@@ -111,17 +146,81 @@ impl Obligation {
         Self {
             weighted_allowed_borrow_value: I80F48::ZERO.into(),
             authority: args.authority,
+            auto_deleverage_threshold_bps: 0,
             borrowed_value: I80F48::ZERO.into(),
             borrows: Vec::new(),
             bump: args.bump,
             deposited_value: I80F48::ZERO.into(),
             deposits: Vec::new(),
+            health_factor: I80F48::MAX.into(),
+            index: args.index,
             last_update: args.last_update,
             market: args.market,
+            max_borrows: args.max_borrows,
+            max_deposits: args.max_deposits,
+            pending_authority: Pubkey::default(),
             weighted_unhealthy_borrow_value: I80F48::ZERO.into(),
         }
     }
 
+    pub fn space(max_deposits: u8, max_borrows: u8) -> usize {
+        Obligation::DISCRIMINATOR.len()
+            + Obligation::INIT_SPACE
+            + usize::from(max_deposits) * ObligationCollateral::INIT_SPACE
+            + usize::from(max_borrows) * ObligationLiquidity::INIT_SPACE
+    }
+
+    /// Deserializes an obligation, reading obligations without room for the appended fields with the original layout
+    /// and leaving those fields zeroed, as they are when an original obligation's spare space is read.
+    pub fn try_deserialize_migrated(data: &[u8]) -> Result<Self> {
+        if let Ok(obligation) = Obligation::try_deserialize(&mut &data[..]) {
+            return Ok(obligation);
+        }
+
+        require!(
+            data.starts_with(Obligation::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+
+        let original =
+            OriginalObligation::deserialize(&mut &data[Obligation::DISCRIMINATOR.len()..])
+                .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+
+        Ok(Self {
+            weighted_allowed_borrow_value: original.weighted_allowed_borrow_value,
+            authority: original.authority,
+            auto_deleverage_threshold_bps: 0,
+            borrowed_value: original.borrowed_value,
+            borrows: original.borrows,
+            bump: original.bump,
+            deposited_value: original.deposited_value,
+            deposits: original.deposits,
+            health_factor: I80F48::ZERO.into(),
+            index: 0,
+            last_update: original.last_update,
+            market: original.market,
+            max_borrows: 0,
+            max_deposits: 0,
+            pending_authority: Pubkey::default(),
+            weighted_unhealthy_borrow_value: original.weighted_unhealthy_borrow_value,
+        })
+    }
+
+    /// Original obligations read zero reserve limits until `realloc_obligation` sets them from the market, which
+    /// never allows fewer than one position.
+    pub fn requires_realloc(&self) -> bool {
+        self.max_deposits == 0
+    }
+
+    /// Seed for the obligation index, empty for index 0 so that first obligations keep their original address.
+    pub fn index_seed(index: u16) -> Vec<u8> {
+        if index == 0 {
+            Vec::new()
+        } else {
+            index.to_le_bytes().to_vec()
+        }
+    }
+
     fn find_collateral_index_in_deposits(&self, deposit_reserve: Pubkey) -> Option<usize> {
         self.deposits
             .iter()
@@ -132,9 +231,19 @@ impl Obligation {
         &mut self,
         deposit_reserve: Pubkey,
     ) -> Result<&mut ObligationCollateral> {
+        require!(
+            !self.requires_realloc(),
+            LendingError::ObligationReallocRequired
+        );
+
         if let Some(index) = self.find_collateral_index_in_deposits(deposit_reserve) {
             Ok(&mut self.deposits[index])
         } else {
+            require!(
+                self.deposits.len() < usize::from(self.max_deposits),
+                LendingError::ObligationReserveLimit
+            );
+
             let collateral = ObligationCollateral {
                 deposited_amount: 0,
                 reserve: deposit_reserve,
@@ -197,10 +306,20 @@ impl Obligation {
         &mut self,
         borrow_reserve: Pubkey,
     ) -> Result<&mut ObligationLiquidity> {
+        require!(
+            !self.requires_realloc(),
+            LendingError::ObligationReallocRequired
+        );
+
         if let Some(liquidity_index) = self.find_liquidity_index_in_borrows(borrow_reserve) {
             return Ok(&mut self.borrows[liquidity_index]);
         }
 
+        require!(
+            self.borrows.len() < usize::from(self.max_borrows),
+            LendingError::ObligationReserveLimit
+        );
+
         let liquidity = ObligationLiquidity {
             borrowed_amount: 0,
             cumulative_borrow_index: I80F48::ONE.into(),
@@ -245,6 +364,130 @@ impl Obligation {
         }
     }
 
+    pub fn is_unhealthy(&self) -> bool {
+        let borrowed_value = I80F48::from(self.borrowed_value);
+
+        borrowed_value > I80F48::ZERO
+            && borrowed_value >= I80F48::from(self.weighted_unhealthy_borrow_value)
+    }
+
+    /// Returns the maximum value when nothing is borrowed.
+    pub fn calculate_health_factor(&self) -> Result<I80F48> {
+        let borrowed_value = I80F48::from(self.borrowed_value);
+
+        if borrowed_value == I80F48::ZERO {
+            Ok(I80F48::MAX)
+        } else {
+            I80F48::from(self.weighted_unhealthy_borrow_value).safe_div(borrowed_value)
+        }
+    }
+
+    pub fn is_auto_deleverageable(&self) -> Result<bool> {
+        if self.auto_deleverage_threshold_bps == 0 {
+            return Ok(false);
+        }
+
+        let borrowed_value = I80F48::from(self.borrowed_value);
+        let auto_deleverage_borrow_value = I80F48::from(self.weighted_unhealthy_borrow_value)
+            .safe_mul(bps_to_i80f48(self.auto_deleverage_threshold_bps)?)?;
+
+        Ok(borrowed_value > I80F48::ZERO && borrowed_value >= auto_deleverage_borrow_value)
+    }
+
+    /// Repays liquidity and withdraws collateral, deducting their value from the last refreshed values so health can be rechecked without a refresh.
+    pub fn liquidate(
+        &mut self,
+        repay_amount: u64,
+        liquidity_index: usize,
+        withdraw_amount: u64,
+        collateral_index: usize,
+        loan_to_value: I80F48,
+        liquidation_threshold: I80F48,
+    ) -> Result<()> {
+        let obligation_liquidity = &mut self.borrows[liquidity_index];
+        let repay_value = I80F48::from(repay_amount)
+            .safe_mul(obligation_liquidity.market_value.into())?
+            .safe_div(obligation_liquidity.borrowed_amount.into())?;
+        obligation_liquidity.market_value = I80F48::from(obligation_liquidity.market_value)
+            .safe_sub(repay_value)?
+            .into();
+
+        let obligation_collateral = &mut self.deposits[collateral_index];
+        let withdraw_value = I80F48::from(withdraw_amount)
+            .safe_mul(obligation_collateral.market_value.into())?
+            .safe_div(obligation_collateral.deposited_amount.into())?;
+        obligation_collateral.market_value = I80F48::from(obligation_collateral.market_value)
+            .safe_sub(withdraw_value)?
+            .into();
+
+        self.borrowed_value = I80F48::from(self.borrowed_value)
+            .safe_sub(repay_value)?
+            .into();
+        self.deposited_value = I80F48::from(self.deposited_value)
+            .safe_sub(withdraw_value)?
+            .into();
+        self.weighted_allowed_borrow_value = I80F48::from(self.weighted_allowed_borrow_value)
+            .safe_sub(withdraw_value.safe_mul(loan_to_value)?)?
+            .into();
+        self.weighted_unhealthy_borrow_value = I80F48::from(self.weighted_unhealthy_borrow_value)
+            .safe_sub(withdraw_value.safe_mul(liquidation_threshold)?)?
+            .into();
+        self.health_factor = self.calculate_health_factor()?.into();
+
+        self.repay(repay_amount, liquidity_index)?;
+        self.withdraw(withdraw_amount, collateral_index)
+    }
+
+    /// Deposits collateral and adds its value to the last refreshed values so health can be checked without a refresh.
+    pub fn deposit_refreshed(
+        &mut self,
+        deposit_reserve: Pubkey,
+        deposit_amount: u64,
+        deposit_value: I80F48,
+        loan_to_value: I80F48,
+        liquidation_threshold: I80F48,
+    ) -> Result<()> {
+        let obligation_collateral = self.find_or_add_collateral_to_deposits(deposit_reserve)?;
+        obligation_collateral.deposit(deposit_amount)?;
+        obligation_collateral.market_value = I80F48::from(obligation_collateral.market_value)
+            .safe_add(deposit_value)?
+            .into();
+
+        self.deposited_value = I80F48::from(self.deposited_value)
+            .safe_add(deposit_value)?
+            .into();
+        self.weighted_allowed_borrow_value = I80F48::from(self.weighted_allowed_borrow_value)
+            .safe_add(deposit_value.safe_mul(loan_to_value)?)?
+            .into();
+        self.weighted_unhealthy_borrow_value = I80F48::from(self.weighted_unhealthy_borrow_value)
+            .safe_add(deposit_value.safe_mul(liquidation_threshold)?)?
+            .into();
+        self.health_factor = self.calculate_health_factor()?.into();
+
+        Ok(())
+    }
+
+    /// Borrows liquidity and adds its value to the last refreshed values so health can be checked without a refresh.
+    pub fn borrow_refreshed(
+        &mut self,
+        borrow_reserve: Pubkey,
+        borrow_amount: u64,
+        borrow_value: I80F48,
+    ) -> Result<()> {
+        let obligation_liquidity = self.find_or_add_liquidity_to_borrows(borrow_reserve)?;
+        obligation_liquidity.borrow(borrow_amount)?;
+        obligation_liquidity.market_value = I80F48::from(obligation_liquidity.market_value)
+            .safe_add(borrow_value)?
+            .into();
+
+        self.borrowed_value = I80F48::from(self.borrowed_value)
+            .safe_add(borrow_value)?
+            .into();
+        self.health_factor = self.calculate_health_factor()?.into();
+
+        Ok(())
+    }
+
     pub fn max_liquidation_amount(
         &self,
         obligation_liquidity: &ObligationLiquidity,
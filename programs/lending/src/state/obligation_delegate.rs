@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+
+/// Address allowed by an obligation's authority to act on the obligation within a set of permissions.
+#[account]
+#[derive(InitSpace)]
+pub struct ObligationDelegate {
+    /// Address of obligation.
+    pub obligation: Pubkey,
+    /// Obligation authority which granted the delegation, which lapses once the obligation is transferred.
+    pub authority: Pubkey,
+    /// Address of delegate.
+    pub delegate: Pubkey,
+    /// Bitmask of actions the delegate may perform on the obligation.
+    pub permissions: u8,
+    /// Unix timestamp after which the delegate can no longer act, 0 if it never expires.
+    pub expires_at: i64,
+    /// Bump used for deriving signer seeds.
+    pub bump: u8,
+}
+
+impl ObligationDelegate {
+    pub fn is_expired(&self, unix_timestamp: i64) -> bool {
+        self.expires_at != 0 && unix_timestamp >= self.expires_at
+    }
+}
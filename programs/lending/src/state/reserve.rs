@@ -4,9 +4,9 @@ use anchor_lang::prelude::*;
 use fixed::types::I80F48;
 
 use crate::{
-    bps_to_i80f48, error::LendingError, i80f48_pow, LastUpdate, Obligation, ObligationCollateral,
-    ObligationLiquidity, SafeConvert, SafeMath, SafeMathAssign, SafePow, WrappedI80F48,
-    MAX_BASIS_POINTS, SLOTS_PER_YEAR,
+    bps_to_i80f48, error::LendingError, i80f48_pow, FlashLoanFeeExemption, LastUpdate, Obligation,
+    ObligationCollateral, ObligationLiquidity, SafeConvert, SafeMath, SafeMathAssign, SafePow,
+    WrappedI80F48, MAX_BASIS_POINTS, SLOTS_PER_YEAR,
 };
 
 #[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone)]
@@ -15,11 +15,17 @@ pub struct ReserveFees {
     pub flash_loan_fee_bps: u16,
     /// Portion of borrow interest and flash loan fee that goes to the market authority before distributed to lenders, in basis points.
     pub platform_fee_bps: u16,
+    /// Portion of platform fees set aside in the insurance vault, in basis points.
+    pub insurance_fee_bps: u16,
+    /// Portion of the liquidation bonus on seized collateral set aside in the insurance vault, in basis points.
+    pub liquidation_insurance_fee_bps: u16,
 }
 
 pub struct NewReserveFeesArgs {
     pub flash_loan_fee_bps: u16,
     pub platform_fee_bps: u16,
+    pub insurance_fee_bps: u16,
+    pub liquidation_insurance_fee_bps: u16,
 }
 
 impl ReserveFees {
@@ -27,9 +33,18 @@ impl ReserveFees {
         Self {
             flash_loan_fee_bps: args.flash_loan_fee_bps,
             platform_fee_bps: args.platform_fee_bps,
+            insurance_fee_bps: args.insurance_fee_bps,
+            liquidation_insurance_fee_bps: args.liquidation_insurance_fee_bps,
         }
     }
 
+    pub fn calculate_insurance_fee(&self, platform_fee: u64) -> Result<u64> {
+        I80F48::from(platform_fee)
+            .safe_mul(bps_to_i80f48(self.insurance_fee_bps)?)?
+            .floor()
+            .safe_to_u64()
+    }
+
     pub fn calculate_flash_loan_fee(&self, amount: u64) -> Result<u64> {
         Ok(I80F48::from(amount)
             .safe_mul(bps_to_i80f48(
@@ -38,6 +53,14 @@ impl ReserveFees {
             .ceil()
             .safe_to_u64()?)
     }
+
+    /// Portion of the flash loan fee owed to the platform, the remainder goes to suppliers.
+    pub fn calculate_flash_loan_platform_fee(&self, amount: u64) -> Result<u64> {
+        I80F48::from(amount)
+            .safe_mul(bps_to_i80f48(self.platform_fee_bps)?)?
+            .floor()
+            .safe_to_u64()
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone)]
@@ -58,6 +81,10 @@ pub struct ReserveConfig {
     pub optimal_borrow_rate_bps: u16,
     /// Max borrow APY, in basis points.
     pub max_borrow_rate_bps: u16,
+    /// Whether liquidity can be flash loaned from the reserve.
+    pub flash_loans_enabled: bool,
+    /// Max amount of liquidity that can be flash loaned at once.
+    pub max_flash_loan_amount: u64,
     /// Program owner fees assessed, separate from gains due to interest accrual.
     pub fees: ReserveFees,
 }
@@ -71,6 +98,8 @@ pub struct NewReserveConfigArgs {
     pub min_borrow_rate_bps: u16,
     pub optimal_borrow_rate_bps: u16,
     pub max_borrow_rate_bps: u16,
+    pub flash_loans_enabled: bool,
+    pub max_flash_loan_amount: u64,
     pub fees: ReserveFees,
 }
 
@@ -85,6 +114,8 @@ impl ReserveConfig {
             min_borrow_rate_bps: args.min_borrow_rate_bps,
             optimal_borrow_rate_bps: args.optimal_borrow_rate_bps,
             max_borrow_rate_bps: args.max_borrow_rate_bps,
+            flash_loans_enabled: args.flash_loans_enabled,
+            max_flash_loan_amount: args.max_flash_loan_amount,
             fees: args.fees,
         }
     }
@@ -108,6 +139,10 @@ pub struct ReserveLiquidity {
     pub cumulative_borrow_index: WrappedI80F48,
     /// Total claimable fees accumulated to the platform.
     pub accumulated_platform_fees: u64,
+    /// Total fees accumulated to the insurance vault, pending transfer out of the reserve.
+    pub accumulated_insurance_fees: u64,
+    /// Total liquidity held in the insurance vault to cover bad debt.
+    pub insurance_amount: u64,
     /// Last refreshed price of reserve mint.
     pub market_price: WrappedI80F48,
 }
@@ -126,6 +161,8 @@ impl ReserveLiquidity {
             borrowed_amount: 0,
             cumulative_borrow_index: I80F48::ONE.into(),
             accumulated_platform_fees: 0,
+            accumulated_insurance_fees: 0,
+            insurance_amount: 0,
             market_price: I80F48::ZERO.into(),
         }
     }
@@ -198,7 +235,7 @@ impl ReserveLiquidity {
         let liquidity_amount = self.receipt_to_liquidity(receipt_mint_amount, exchange_rate)?;
 
         require!(
-            liquidity_amount <= self.available_amount - self.borrowed_amount,
+            liquidity_amount <= self.available_amount,
             LendingError::InsufficientLiquidity,
         );
 
@@ -227,6 +264,26 @@ impl ReserveLiquidity {
         Ok(())
     }
 
+    pub fn accumulate_fees(&mut self, platform_fee: u64, insurance_fee: u64) -> Result<()> {
+        self.accumulated_platform_fees
+            .safe_add_assign(platform_fee.safe_sub(insurance_fee)?)?;
+        self.accumulated_insurance_fees
+            .safe_add_assign(insurance_fee)?;
+
+        Ok(())
+    }
+
+    pub fn collect_flash_loan_fee(
+        &mut self,
+        flash_loan_fee: u64,
+        platform_fee: u64,
+        insurance_fee: u64,
+    ) -> Result<()> {
+        // supplier share stays in available amount, raising the exchange rate
+        self.available_amount.safe_add_assign(flash_loan_fee)?;
+        self.accumulate_fees(platform_fee, insurance_fee)
+    }
+
     pub fn redeem_fees(&mut self) -> Result<u64> {
         let redeemable_fees = self.accumulated_platform_fees.min(self.available_amount);
 
@@ -237,9 +294,68 @@ impl ReserveLiquidity {
 
         Ok(redeemable_fees)
     }
+
+    pub fn sweep_insurance_fees(&mut self) -> Result<u64> {
+        let sweepable_fees = self.accumulated_insurance_fees.min(self.available_amount);
+
+        // net available amount decreases when fees are moved to the insurance vault, which is
+        // credited with the amount it actually receives
+        self.available_amount.safe_sub_assign(sweepable_fees)?;
+        self.accumulated_insurance_fees
+            .safe_sub_assign(sweepable_fees)?;
+
+        Ok(sweepable_fees)
+    }
+
+    pub fn deposit_insurance(&mut self, amount: u64) -> Result<()> {
+        self.insurance_amount.safe_add_assign(amount)
+    }
+
+    pub fn withdraw_insurance(&mut self, amount: u64) -> Result<()> {
+        require!(
+            amount <= self.insurance_amount,
+            LendingError::InsufficientInsurance
+        );
+
+        self.insurance_amount.safe_sub_assign(amount)
+    }
+
+    /// Withdraws `amount` from insurance and repays the `received_amount` that reached the reserve after any transfer fee.
+    pub fn cover_bad_debt(&mut self, amount: u64, received_amount: u64) -> Result<()> {
+        self.withdraw_insurance(amount)?;
+
+        // rounding in interest accrual can leave the obligation owing more than the reserve tracks as borrowed, the
+        // excess received still joins the available liquidity
+        let repay_amount = received_amount.min(self.borrowed_amount);
+
+        self.repay_liquidity(repay_amount)?;
+        self.available_amount
+            .safe_add_assign(received_amount.safe_sub(repay_amount)?)?;
+
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Default)]
+pub struct InsuranceWithdrawal {
+    /// Amount of liquidity requested to be withdrawn from the insurance vault.
+    pub amount: u64,
+    /// Unix timestamp after which the requested amount can be withdrawn.
+    pub unlock_timestamp: i64,
+}
+
+impl InsuranceWithdrawal {
+    pub fn is_requested(&self) -> bool {
+        self.amount > 0
+    }
+
+    pub fn is_unlocked(&self, timestamp: i64) -> bool {
+        timestamp >= self.unlock_timestamp
+    }
 }
 
 /// Reserves represent a mint of liquidity that can be supplied and borrowed that is unique to a market.
+/// Reserves created with the original layout cannot be read until `realloc_reserve` migrates them.
 #[account]
 #[derive(InitSpace)]
 pub struct Reserve {
@@ -247,10 +363,50 @@ pub struct Reserve {
     pub last_update: LastUpdate,
     pub liquidity: ReserveLiquidity,
     pub config: ReserveConfig,
+    /// Pending withdrawal from the insurance vault by the market authority.
+    pub insurance_withdrawal: InsuranceWithdrawal,
     /// Stored to avoid passing liquidity mint accounts on refresh obligation
     pub liquidity_mint_decimals: u8,
     pub bump: u8,
     pub receipt_mint_bump: u8,
+    pub insurance_vault_bump: u8,
+}
+
+/// Layout reserves were created with before the insurance vault and flash loan limits were added.
+#[derive(AnchorDeserialize)]
+struct OriginalReserve {
+    market: Pubkey,
+    last_update: LastUpdate,
+    liquidity: OriginalReserveLiquidity,
+    config: OriginalReserveConfig,
+    liquidity_mint_decimals: u8,
+    bump: u8,
+    receipt_mint_bump: u8,
+}
+
+#[derive(AnchorDeserialize)]
+struct OriginalReserveLiquidity {
+    mint: Pubkey,
+    price_update_v2: Pubkey,
+    available_amount: u64,
+    borrowed_amount: u64,
+    cumulative_borrow_index: WrappedI80F48,
+    accumulated_platform_fees: u64,
+    market_price: WrappedI80F48,
+}
+
+#[derive(AnchorDeserialize)]
+struct OriginalReserveConfig {
+    optimal_utilization_rate_bps: u16,
+    loan_to_value_bps: u16,
+    liquidation_bonus_bps: u16,
+    liquidation_threshold_bps: u16,
+    liquidation_close_factor_bps: u16,
+    min_borrow_rate_bps: u16,
+    optimal_borrow_rate_bps: u16,
+    max_borrow_rate_bps: u16,
+    flash_loan_fee_bps: u16,
+    platform_fee_bps: u16,
 }
 
 pub struct NewReserveArgs {
@@ -260,6 +416,7 @@ pub struct NewReserveArgs {
     pub config: ReserveConfig,
     pub bump: u8,
     pub receipt_mint_bump: u8,
+    pub insurance_vault_bump: u8,
     pub slot: u64,
 }
 
@@ -332,10 +489,10 @@ impl Reserve {
                 .safe_mul(bps_to_i80f48(self.config.fees.platform_fee_bps)?)?
                 .ceil()
                 .safe_to_u64()?;
+            let insurance_fee = self.config.fees.calculate_insurance_fee(platform_fee)?;
 
             self.liquidity
-                .accumulated_platform_fees
-                .safe_add_assign(platform_fee)?;
+                .accumulate_fees(platform_fee, insurance_fee)?;
         }
 
         Ok(())
@@ -369,6 +526,21 @@ impl Reserve {
         }
     }
 
+    pub fn market_value(&self, amount: u64) -> Result<I80F48> {
+        I80F48::from(amount)
+            .safe_mul(self.liquidity.market_price.into())?
+            .safe_div(10_u64.safe_pow(self.liquidity_mint_decimals.into())?.into())
+    }
+
+    /// Amount of liquidity worth the given value, rounded down.
+    pub fn market_amount(&self, value: I80F48) -> Result<u64> {
+        value
+            .safe_mul(10_u64.safe_pow(self.liquidity_mint_decimals.into())?.into())?
+            .safe_div(self.liquidity.market_price.into())?
+            .floor()
+            .safe_to_u64()
+    }
+
     pub fn calculate_repay(&self, amount_to_repay: u64, borrowed_amount: u64) -> Result<u64> {
         let repay_amount = if amount_to_repay == u64::MAX {
             borrowed_amount
@@ -379,23 +551,91 @@ impl Reserve {
         Ok(repay_amount)
     }
 
+    /// Receipt amount of the obligation's collateral in this reserve that can be withdrawn while keeping its borrows
+    /// within the loan to value, `u64::MAX` withdrawing as much as allowed.
+    pub fn calculate_withdraw(
+        &self,
+        amount_to_withdraw: u64,
+        obligation: &Obligation,
+        obligation_collateral: &ObligationCollateral,
+    ) -> Result<u64> {
+        if obligation.borrows.is_empty() {
+            return Ok(amount_to_withdraw.min(obligation_collateral.deposited_amount));
+        }
+
+        require!(
+            I80F48::from(obligation.deposited_value) > I80F48::ZERO,
+            LendingError::ObligationDepositsValueZero
+        );
+
+        let max_withdraw_value =
+            obligation.max_withdraw_value(bps_to_i80f48(self.config.loan_to_value_bps)?)?;
+
+        require!(
+            max_withdraw_value > I80F48::ZERO,
+            LendingError::MaxWithdrawValueZero
+        );
+
+        if amount_to_withdraw == u64::MAX {
+            let withdraw_value = max_withdraw_value.min(obligation_collateral.market_value.into());
+            let withdraw_pct =
+                withdraw_value.safe_div(obligation_collateral.market_value.into())?;
+
+            Ok(withdraw_pct
+                .safe_mul(obligation_collateral.deposited_amount.into())?
+                .floor()
+                .safe_to_u64()?
+                .min(obligation_collateral.deposited_amount))
+        } else {
+            let withdraw_amount = amount_to_withdraw.min(obligation_collateral.deposited_amount);
+            let withdraw_pct = I80F48::from(withdraw_amount)
+                .safe_div(obligation_collateral.deposited_amount.into())?;
+            let withdraw_value =
+                I80F48::from(obligation_collateral.market_value).safe_mul(withdraw_pct)?;
+
+            require!(
+                withdraw_value <= max_withdraw_value,
+                LendingError::WithdrawTooLarge
+            );
+
+            Ok(withdraw_amount)
+        }
+    }
+
     pub fn calculate_liquidation(
         &self,
         obligation: &Obligation,
         obligation_liquidity: &ObligationLiquidity,
         obligation_collateral: &ObligationCollateral,
+        dust_threshold_value: I80F48,
     ) -> Result<(u64, u64)> {
         let bonus_rate = I80F48::ONE.safe_add(bps_to_i80f48(self.config.liquidation_bonus_bps)?)?;
 
         let repay_amount: u64;
         let withdraw_amount: u64;
 
-        let liquidation_amount = obligation
-            .max_liquidation_amount(
-                obligation_liquidity,
-                bps_to_i80f48(self.config.liquidation_close_factor_bps)?,
-            )?
+        // obligations below the dust threshold are liquidated in full
+        let liquidation_close_factor =
+            if I80F48::from(obligation.borrowed_value) < dust_threshold_value {
+                I80F48::ONE
+            } else {
+                bps_to_i80f48(self.config.liquidation_close_factor_bps)?
+            };
+
+        let mut liquidation_amount = obligation
+            .max_liquidation_amount(obligation_liquidity, liquidation_close_factor)?
             .min(obligation_liquidity.borrowed_amount.into());
+
+        // clear leftover borrow that would be too small to be worth liquidating
+        let remaining_borrow_value = I80F48::from(obligation_liquidity.borrowed_amount)
+            .safe_sub(liquidation_amount)?
+            .safe_div(obligation_liquidity.borrowed_amount.into())?
+            .safe_mul(obligation_liquidity.market_value.into())?;
+
+        if remaining_borrow_value < dust_threshold_value {
+            liquidation_amount = obligation_liquidity.borrowed_amount.into();
+        }
+
         let liquidation_ratio =
             liquidation_amount.safe_div(obligation_liquidity.borrowed_amount.into())?;
         let liquidation_value = liquidation_ratio
@@ -427,6 +667,262 @@ impl Reserve {
             }
         }
 
+        // clear leftover collateral that would be too small to be worth liquidating, only once the borrow is repaid
+        // in full so the liquidator is not handed extra collateral for the same repayment while debt remains
+        let remaining_collateral_value = I80F48::from(obligation_collateral.deposited_amount)
+            .safe_sub(withdraw_amount.into())?
+            .safe_div(obligation_collateral.deposited_amount.into())?
+            .safe_mul(obligation_collateral.market_value.into())?;
+
+        let withdraw_amount = if remaining_collateral_value < dust_threshold_value
+            && repay_amount >= obligation_liquidity.borrowed_amount
+        {
+            obligation_collateral.deposited_amount
+        } else {
+            withdraw_amount
+        };
+
         Ok((repay_amount, withdraw_amount))
     }
+
+    /// Collateral withdrawn and debt repaid are in the same asset, so both amounts are equal and no bonus is paid.
+    pub fn calculate_auto_deleverage(
+        &self,
+        obligation: &Obligation,
+        obligation_liquidity: &ObligationLiquidity,
+        obligation_collateral: &ObligationCollateral,
+        exchange_rate: I80F48,
+    ) -> Result<u64> {
+        let deposited_amount = self
+            .liquidity
+            .receipt_to_liquidity(obligation_collateral.deposited_amount, exchange_rate)?;
+        let deleverage_amount = obligation
+            .max_liquidation_amount(
+                obligation_liquidity,
+                bps_to_i80f48(self.config.liquidation_close_factor_bps)?,
+            )?
+            .floor()
+            .safe_to_u64()?;
+
+        Ok(deleverage_amount
+            .min(obligation_liquidity.borrowed_amount)
+            .min(deposited_amount))
+    }
+
+    /// Returns the flash loan fee along with its platform and insurance portions, after any fee exemption.
+    pub fn calculate_flash_loan_fees(
+        &self,
+        amount: u64,
+        fee_exemption: Option<&FlashLoanFeeExemption>,
+    ) -> Result<(u64, u64, u64)> {
+        let mut flash_loan_fee = self.config.fees.calculate_flash_loan_fee(amount)?;
+        let mut platform_fee = self.config.fees.calculate_flash_loan_platform_fee(amount)?;
+
+        if let Some(fee_exemption) = fee_exemption {
+            flash_loan_fee = fee_exemption.apply_waiver(flash_loan_fee)?;
+            platform_fee = fee_exemption.apply_waiver(platform_fee)?;
+        }
+
+        let insurance_fee = self.config.fees.calculate_insurance_fee(platform_fee)?;
+
+        Ok((flash_loan_fee, platform_fee, insurance_fee))
+    }
+
+    pub fn calculate_liquidation_insurance_fee(&self, withdraw_amount: u64) -> Result<u64> {
+        let bonus_rate = bps_to_i80f48(self.config.liquidation_bonus_bps)?;
+        let bonus_amount = I80F48::from(withdraw_amount)
+            .safe_mul(bonus_rate)?
+            .safe_div(I80F48::ONE.safe_add(bonus_rate)?)?;
+
+        bonus_amount
+            .safe_mul(bps_to_i80f48(
+                self.config.fees.liquidation_insurance_fee_bps,
+            )?)?
+            .floor()
+            .safe_to_u64()
+    }
+    /// Deserializes a reserve, reading reserves too small for the added fields with the original layout. Those keep
+    /// flash loans enabled without a limit as before, and start without insurance fees until the market authority
+    /// configures them.
+    pub fn try_deserialize_migrated(data: &[u8]) -> Result<Self> {
+        if let Ok(reserve) = Reserve::try_deserialize(&mut &data[..]) {
+            return Ok(reserve);
+        }
+
+        require!(
+            data.starts_with(Reserve::DISCRIMINATOR),
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+
+        let original = OriginalReserve::deserialize(&mut &data[Reserve::DISCRIMINATOR.len()..])
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+
+        Ok(Self {
+            bump: original.bump,
+            receipt_mint_bump: original.receipt_mint_bump,
+            insurance_vault_bump: 0,
+            config: ReserveConfig::new(NewReserveConfigArgs {
+                fees: ReserveFees::new(NewReserveFeesArgs {
+                    flash_loan_fee_bps: original.config.flash_loan_fee_bps,
+                    platform_fee_bps: original.config.platform_fee_bps,
+                    insurance_fee_bps: 0,
+                    liquidation_insurance_fee_bps: 0,
+                }),
+                flash_loans_enabled: true,
+                liquidation_bonus_bps: original.config.liquidation_bonus_bps,
+                liquidation_threshold_bps: original.config.liquidation_threshold_bps,
+                liquidation_close_factor_bps: original.config.liquidation_close_factor_bps,
+                loan_to_value_bps: original.config.loan_to_value_bps,
+                max_borrow_rate_bps: original.config.max_borrow_rate_bps,
+                max_flash_loan_amount: u64::MAX,
+                optimal_borrow_rate_bps: original.config.optimal_borrow_rate_bps,
+                min_borrow_rate_bps: original.config.min_borrow_rate_bps,
+                optimal_utilization_rate_bps: original.config.optimal_utilization_rate_bps,
+            }),
+            last_update: original.last_update,
+            insurance_withdrawal: InsuranceWithdrawal::default(),
+            liquidity: ReserveLiquidity {
+                mint: original.liquidity.mint,
+                price_update_v2: original.liquidity.price_update_v2,
+                available_amount: original.liquidity.available_amount,
+                borrowed_amount: original.liquidity.borrowed_amount,
+                cumulative_borrow_index: original.liquidity.cumulative_borrow_index,
+                accumulated_platform_fees: original.liquidity.accumulated_platform_fees,
+                accumulated_insurance_fees: 0,
+                insurance_amount: 0,
+                market_price: original.liquidity.market_price,
+            },
+            market: original.market,
+            liquidity_mint_decimals: original.liquidity_mint_decimals,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NewLastUpdateArgs, NewObligationArgs};
+
+    fn reserve(liquidation_close_factor_bps: u16) -> Reserve {
+        Reserve {
+            market: Pubkey::default(),
+            last_update: LastUpdate::new(NewLastUpdateArgs { slot: 0 }),
+            liquidity: ReserveLiquidity::new(NewReserveLiquidityArgs {
+                mint: Pubkey::default(),
+                price_update_v2: Pubkey::default(),
+            }),
+            config: ReserveConfig::new(NewReserveConfigArgs {
+                optimal_utilization_rate_bps: 0,
+                loan_to_value_bps: 0,
+                liquidation_bonus_bps: 0,
+                liquidation_threshold_bps: 0,
+                liquidation_close_factor_bps,
+                min_borrow_rate_bps: 0,
+                optimal_borrow_rate_bps: 0,
+                max_borrow_rate_bps: 0,
+                flash_loans_enabled: false,
+                max_flash_loan_amount: 0,
+                fees: ReserveFees::new(NewReserveFeesArgs {
+                    flash_loan_fee_bps: 0,
+                    platform_fee_bps: 0,
+                    insurance_fee_bps: 0,
+                    liquidation_insurance_fee_bps: 0,
+                }),
+            }),
+            insurance_withdrawal: InsuranceWithdrawal::default(),
+            liquidity_mint_decimals: 0,
+            bump: 0,
+            receipt_mint_bump: 0,
+            insurance_vault_bump: 0,
+        }
+    }
+
+    /// Liquidates a borrow of 1000 against `deposited_amount` collateral, both valued one-to-one.
+    fn calculate_liquidation(
+        liquidation_close_factor_bps: u16,
+        deposited_amount: u64,
+    ) -> (u64, u64) {
+        let mut obligation = Obligation::new(NewObligationArgs {
+            last_update: LastUpdate::new(NewLastUpdateArgs { slot: 0 }),
+            market: Pubkey::default(),
+            authority: Pubkey::default(),
+            index: 0,
+            max_deposits: 1,
+            max_borrows: 1,
+            bump: 0,
+        });
+        obligation.borrowed_value = I80F48::from_num(1000).into();
+        obligation.deposited_value = I80F48::from_num(deposited_amount).into();
+
+        let obligation_liquidity = ObligationLiquidity {
+            borrowed_amount: 1000,
+            market_value: I80F48::from_num(1000).into(),
+            ..Default::default()
+        };
+        let obligation_collateral = ObligationCollateral {
+            deposited_amount,
+            market_value: I80F48::from_num(deposited_amount).into(),
+            ..Default::default()
+        };
+
+        reserve(liquidation_close_factor_bps)
+            .calculate_liquidation(
+                &obligation,
+                &obligation_liquidity,
+                &obligation_collateral,
+                I80F48::from_num(100),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn adds_bad_debt_cover_above_borrowed_amount_to_available() {
+        let mut liquidity = reserve(0).liquidity;
+        liquidity.insurance_amount = 100;
+        liquidity.borrowed_amount = 40;
+
+        liquidity.cover_bad_debt(50, 50).unwrap();
+
+        assert_eq!(liquidity.insurance_amount, 50);
+        assert_eq!(liquidity.borrowed_amount, 0);
+        assert_eq!(liquidity.available_amount, 50);
+    }
+
+    #[test]
+    fn rejects_withdraw_without_deposited_value() {
+        let mut obligation = Obligation::new(NewObligationArgs {
+            last_update: LastUpdate::new(NewLastUpdateArgs { slot: 0 }),
+            market: Pubkey::default(),
+            authority: Pubkey::default(),
+            index: 0,
+            max_deposits: 1,
+            max_borrows: 1,
+            bump: 0,
+        });
+        obligation.borrows.push(ObligationLiquidity::default());
+        obligation.weighted_allowed_borrow_value = I80F48::from_num(1000).into();
+
+        let obligation_collateral = ObligationCollateral {
+            deposited_amount: 1000,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            reserve(0)
+                .calculate_withdraw(u64::MAX, &obligation, &obligation_collateral)
+                .unwrap_err(),
+            LendingError::ObligationDepositsValueZero.into()
+        );
+    }
+
+    #[test]
+    fn leaves_dust_collateral_while_borrow_remains() {
+        // half of the borrow is repaid, leaving 12 of collateral below the dust threshold
+        assert_eq!(calculate_liquidation(5000, 512), (500, 500));
+    }
+
+    #[test]
+    fn clears_dust_collateral_once_borrow_is_repaid() {
+        assert_eq!(calculate_liquidation(MAX_BASIS_POINTS, 1050), (1000, 1050));
+    }
 }
@@ -7,6 +7,9 @@ pub use safe_math::*;
 pub mod signer_seeds;
 pub use signer_seeds::*;
 
+pub mod token_transfer;
+pub use token_transfer::*;
+
 pub mod validate;
 pub use validate::*;
 
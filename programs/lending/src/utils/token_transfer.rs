@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::accessor,
+    token_2022::{
+        self,
+        spl_token_2022::{
+            extension::{
+                transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+            },
+            state::Mint,
+        },
+        transfer_checked, TransferChecked,
+    },
+};
+
+use crate::{error::LendingError, SafeMath};
+
+/// Transfers tokens and returns the amount that reached the destination, which is less than `amount` for transfer-fee mints.
+pub fn transfer_checked_received<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<u64> {
+    let destination = ctx.accounts.to.clone();
+    let pre_balance = accessor::amount(&destination)?;
+
+    transfer_checked(ctx, amount, decimals)?;
+
+    accessor::amount(&destination)?.safe_sub(pre_balance)
+}
+
+/// Returns the amount to send so that `received_amount` reaches the destination after the mint's transfer fee.
+pub fn calculate_pre_transfer_fee_amount(
+    mint_info: &AccountInfo,
+    received_amount: u64,
+) -> Result<u64> {
+    if *mint_info.owner != token_2022::ID {
+        return Ok(received_amount);
+    }
+
+    let data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => {
+            let transfer_fee = transfer_fee_config
+                .calculate_inverse_epoch_fee(Clock::get()?.epoch, received_amount)
+                .ok_or(LendingError::MathOverflow)?;
+
+            received_amount.safe_add(transfer_fee)
+        }
+        Err(_) => Ok(received_amount),
+    }
+}
@@ -1,6 +1,21 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_2022::{
+    self,
+    spl_token_2022::{
+        extension::{
+            default_account_state::DefaultAccountState, BaseStateWithExtensions, ExtensionType,
+            StateWithExtensions,
+        },
+        state::{AccountState, Mint},
+    },
+};
 
-use crate::{error::LendingError, MAX_BASIS_POINTS};
+use crate::{
+    error::LendingError, AllowlistEntry, Market, Obligation, ObligationDelegate, MAX_BASIS_POINTS,
+    MAX_OBLIGATION_RESERVES, MINT_EXTENSION_CONFIDENTIAL_TRANSFER, MINT_EXTENSION_DEFAULT_FROZEN,
+    MINT_EXTENSION_MINT_CLOSE_AUTHORITY, MINT_EXTENSION_NON_TRANSFERABLE, MINT_EXTENSION_PAUSABLE,
+    MINT_EXTENSION_PERMANENT_DELEGATE, MINT_EXTENSION_TRANSFER_FEE,
+};
 
 pub fn validate_bps(bps: u16) -> Result<()> {
     require_gte!(MAX_BASIS_POINTS, bps, LendingError::InvalidBasisPoints);
@@ -8,6 +23,15 @@ pub fn validate_bps(bps: u16) -> Result<()> {
     Ok(())
 }
 
+pub fn validate_obligation_reserve_limit(limit: u8) -> Result<()> {
+    require!(
+        (1..=MAX_OBLIGATION_RESERVES).contains(&limit),
+        LendingError::InvalidObligationReserveLimit
+    );
+
+    Ok(())
+}
+
 pub fn validate_reserve_refreshed(is_stale: bool) -> Result<()> {
     require!(!is_stale, LendingError::ReserveStale);
 
@@ -19,3 +43,107 @@ pub fn validate_obligation_refreshed(is_stale: bool) -> Result<()> {
 
     Ok(())
 }
+
+/// Requires an allowlist entry for the wallet when the market only admits allowlisted obligation owners.
+pub fn validate_allowlisted(
+    market: &Market,
+    allowlist_entry: Option<&AllowlistEntry>,
+    wallet: Pubkey,
+) -> Result<()> {
+    if !market.permissioned_obligations {
+        return Ok(());
+    }
+
+    let allowlist_entry = allowlist_entry.ok_or(LendingError::WalletNotAllowlisted)?;
+
+    require_keys_eq!(
+        allowlist_entry.wallet,
+        wallet,
+        LendingError::WalletNotAllowlisted
+    );
+
+    Ok(())
+}
+
+/// Accepts the obligation's authority, or a delegate it granted the permission to that has not expired.
+pub fn validate_obligation_authority(
+    obligation: &Obligation,
+    signer: Pubkey,
+    obligation_delegate: Option<&ObligationDelegate>,
+    permission: u8,
+) -> Result<()> {
+    if signer == obligation.authority {
+        return Ok(());
+    }
+
+    let obligation_delegate =
+        obligation_delegate.ok_or(LendingError::InvalidObligationAuthority)?;
+
+    require!(
+        obligation_delegate.delegate == signer
+            && obligation_delegate.authority == obligation.authority,
+        LendingError::InvalidObligationAuthority
+    );
+    require!(
+        obligation_delegate.permissions & permission != 0,
+        LendingError::DelegatePermissionDenied
+    );
+    require!(
+        !obligation_delegate.is_expired(Clock::get()?.unix_timestamp),
+        LendingError::DelegateExpired
+    );
+
+    Ok(())
+}
+
+/// Rejects Token-2022 mint extensions that can break reserve accounting unless the market allows them.
+/// Transfer hooks and extensions not known to be harmless are always rejected.
+pub fn validate_mint_extensions(mint_info: &AccountInfo, allowed_extensions: u16) -> Result<()> {
+    if *mint_info.owner != token_2022::ID {
+        return Ok(());
+    }
+
+    let data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    for extension_type in mint.get_extension_types()? {
+        let extension = match extension_type {
+            ExtensionType::TransferFeeConfig => MINT_EXTENSION_TRANSFER_FEE,
+            // transfers never forward the hook's extra accounts, so hooked mints can't move
+            ExtensionType::TransferHook => return err!(LendingError::TransferHookNotSupported),
+            ExtensionType::PermanentDelegate => MINT_EXTENSION_PERMANENT_DELEGATE,
+            ExtensionType::DefaultAccountState => {
+                let default_state = mint.get_extension::<DefaultAccountState>()?.state;
+
+                if default_state == AccountState::Frozen as u8 {
+                    MINT_EXTENSION_DEFAULT_FROZEN
+                } else {
+                    continue;
+                }
+            }
+            ExtensionType::ConfidentialTransferMint
+            | ExtensionType::ConfidentialTransferFeeConfig
+            | ExtensionType::ConfidentialMintBurn => MINT_EXTENSION_CONFIDENTIAL_TRANSFER,
+            ExtensionType::NonTransferable => MINT_EXTENSION_NON_TRANSFERABLE,
+            ExtensionType::Pausable => MINT_EXTENSION_PAUSABLE,
+            ExtensionType::MintCloseAuthority => MINT_EXTENSION_MINT_CLOSE_AUTHORITY,
+            // only change how amounts and metadata are displayed
+            ExtensionType::MetadataPointer
+            | ExtensionType::TokenMetadata
+            | ExtensionType::GroupPointer
+            | ExtensionType::TokenGroup
+            | ExtensionType::GroupMemberPointer
+            | ExtensionType::TokenGroupMember
+            | ExtensionType::InterestBearingConfig
+            | ExtensionType::ScaledUiAmount => continue,
+            _ => return err!(LendingError::MintExtensionNotAllowed),
+        };
+
+        require!(
+            allowed_extensions & extension != 0,
+            LendingError::MintExtensionNotAllowed
+        );
+    }
+
+    Ok(())
+}
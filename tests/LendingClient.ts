@@ -5,11 +5,47 @@ import idl from "../target/idl/lending.json";
 import { LENDING_PROGRAM_ID } from "./constants";
 import { AnchorProvider } from "@coral-xyz/anchor";
 
+// discriminator and last update precede the obligation's market and authority
+const OBLIGATION_MARKET_OFFSET = 17;
+const OBLIGATION_AUTHORITY_OFFSET = 49;
+
 export class LendingClient extends ProgramClient<Lending> {
   constructor(provider: AnchorProvider) {
     super(provider, idl);
   }
 
+  async getObligationsByAuthority(authority: PublicKey, market: PublicKey) {
+    // transferred obligations keep the address of their initializing authority
+    return await this.program.account.obligation.all([
+      {
+        memcmp: { offset: OBLIGATION_MARKET_OFFSET, bytes: market.toBase58() },
+      },
+      {
+        memcmp: {
+          offset: OBLIGATION_AUTHORITY_OFFSET,
+          bytes: authority.toBase58(),
+        },
+      },
+    ]);
+  }
+
+  // obligations created before fields were appended read zero reserve limits, and are rejected when refreshed or
+  // given a new position until reallocObligation migrates them; full ones cannot be decoded until then
+  async requiresRealloc(obligation: PublicKey) {
+    const accountInfo = await this.connection.getAccountInfo(obligation);
+
+    try {
+      const obligationAcc = this.program.coder.accounts.decode(
+        "obligation",
+        accountInfo!.data,
+      );
+
+      return obligationAcc.maxDeposits === 0;
+    } catch {
+      return true;
+    }
+  }
+
   static getMarketPda(name: string) {
     return PublicKey.findProgramAddressSync(
       [Buffer.from("market"), Buffer.from(name)],
@@ -24,9 +60,20 @@ export class LendingClient extends ProgramClient<Lending> {
     )[0];
   }
 
-  static getObligationPda(authority: PublicKey, market: PublicKey) {
+  static getObligationPda(authority: PublicKey, market: PublicKey, index = 0) {
+    // the first obligation of an authority in a market is derived without an index seed
+    const indexSeed = Buffer.alloc(index === 0 ? 0 : 2);
+    if (index !== 0) {
+      indexSeed.writeUInt16LE(index);
+    }
+
     return PublicKey.findProgramAddressSync(
-      [Buffer.from("obligation"), authority.toBuffer(), market.toBuffer()],
+      [
+        Buffer.from("obligation"),
+        authority.toBuffer(),
+        market.toBuffer(),
+        indexSeed,
+      ],
       LENDING_PROGRAM_ID,
     )[0];
   }
@@ -37,4 +84,58 @@ export class LendingClient extends ProgramClient<Lending> {
       LENDING_PROGRAM_ID,
     )[0];
   }
+
+  static getInsuranceVaultPda(reservePda: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("insurance_vault"), reservePda.toBuffer()],
+      LENDING_PROGRAM_ID,
+    )[0];
+  }
+
+  static getFlashLoanFeeExemptionPda(market: PublicKey, address: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("flash_loan_fee_exemption"),
+        market.toBuffer(),
+        address.toBuffer(),
+      ],
+      LENDING_PROGRAM_ID,
+    )[0];
+  }
+
+  static getCollateralEscrowPda(obligation: PublicKey, reserve: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("collateral_escrow"),
+        obligation.toBuffer(),
+        reserve.toBuffer(),
+      ],
+      LENDING_PROGRAM_ID,
+    )[0];
+  }
+
+  static getListingApprovalPda(market: PublicKey, mint: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("listing_approval"), market.toBuffer(), mint.toBuffer()],
+      LENDING_PROGRAM_ID,
+    )[0];
+  }
+
+  static getAllowlistEntryPda(market: PublicKey, wallet: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("allowlist_entry"), market.toBuffer(), wallet.toBuffer()],
+      LENDING_PROGRAM_ID,
+    )[0];
+  }
+
+  static getObligationDelegatePda(obligation: PublicKey, delegate: PublicKey) {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("obligation_delegate"),
+        obligation.toBuffer(),
+        delegate.toBuffer(),
+      ],
+      LENDING_PROGRAM_ID,
+    )[0];
+  }
 }
@@ -15,3 +15,9 @@ export const WBTC_USD_PRICE_UPDATE_V2 = new PublicKey(
 // export const WBTC_MINT = new PublicKey("3NZ9JMVBmGAqocybic2c7LQCJScmgsAZ6vQqTDzcqmJh");
 export const USDC_MINT_DECIMALS = 6;
 export const WBTC_MINT_DECIMALS = 8;
+// mirrors the program's MINT_EXTENSION_* bit flags
+export const MINT_EXTENSION_TRANSFER_FEE = 1 << 0;
+// mirrors the program's DELEGATE_PERMISSION_* bit flags
+export const DELEGATE_PERMISSION_DEPOSIT = 1 << 0;
+export const DELEGATE_PERMISSION_BORROW = 1 << 1;
+export const DELEGATE_PERMISSION_WITHDRAW = 1 << 2;
@@ -0,0 +1,376 @@
+import { afterEach, beforeEach, describe, expect, test } from "bun:test";
+import { LendingClient } from "../LendingClient";
+import { Connection, Keypair, PublicKey } from "@solana/web3.js";
+import {
+  buildAndSendv0Tx,
+  expireBlockhash,
+  getSetup,
+  resetAccounts,
+} from "../setup";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Lending } from "../../target/types/lending";
+import { Surfpool } from "../surfpool";
+import {
+  getAccount,
+  getAssociatedTokenAddressSync,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import { USDC_MINT_DECIMALS, USDC_USD_PRICE_UPDATE_V2 } from "../constants";
+
+describe("autoDeleverageObligation", () => {
+  let client: LendingClient;
+  let program: Program<Lending>;
+  let connection: Connection;
+
+  let marketAuthority: Keypair;
+  let obligationAuthority: Keypair;
+  let keeper: Keypair;
+  let marketPda: PublicKey;
+  let reservePda: PublicKey;
+  let obligationPda: PublicKey;
+  let receiptMint: PublicKey;
+  let reserveAta: PublicKey;
+
+  let liquidityMint: PublicKey;
+  const liquidityMintPriceUpdateV2 = USDC_USD_PRICE_UPDATE_V2;
+
+  const depositAmount = 1000 * Math.pow(10, USDC_MINT_DECIMALS);
+  const borrowAmount = depositAmount / 2;
+
+  async function refreshInstructions() {
+    return [
+      await program.methods
+        .refreshReserve()
+        .accounts({
+          priceUpdateV2: liquidityMintPriceUpdateV2,
+          reserve: reservePda,
+        })
+        .instruction(),
+      await program.methods
+        .refreshObligation()
+        .accounts({
+          obligation: obligationPda,
+        })
+        .remainingAccounts([
+          {
+            isSigner: false,
+            isWritable: false,
+            pubkey: reservePda,
+          },
+          {
+            isSigner: false,
+            isWritable: false,
+            pubkey: receiptMint,
+          },
+          {
+            isSigner: false,
+            isWritable: false,
+            pubkey: reservePda,
+          },
+        ])
+        .instruction(),
+    ];
+  }
+
+  beforeEach(async () => {
+    [marketAuthority, obligationAuthority, keeper] = Array.from(
+      { length: 3 },
+      () => Keypair.generate(),
+    );
+
+    ({ client } = await getSetup([
+      {
+        publicKey: marketAuthority.publicKey,
+      },
+      {
+        publicKey: obligationAuthority.publicKey,
+      },
+      {
+        publicKey: keeper.publicKey,
+      },
+    ]));
+
+    program = client.program;
+    connection = client.connection;
+
+    liquidityMint = await Surfpool.initMint({
+      decimals: USDC_MINT_DECIMALS,
+    });
+
+    // initializes a market
+    const name = "Test Market";
+
+    await program.methods
+      .initializeMarket(name)
+      .accounts({
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    marketPda = LendingClient.getMarketPda(name);
+
+    // initializes a reserve
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 8000; // 80%
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: null,
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2: liquidityMintPriceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    reservePda = LendingClient.getReservePda(marketPda, liquidityMint);
+    receiptMint = LendingClient.getReceiptMint(reservePda);
+    reserveAta = getAssociatedTokenAddressSync(
+      liquidityMint,
+      reservePda,
+      !PublicKey.isOnCurve(reservePda),
+    );
+
+    // initializes an obligation
+    await program.methods
+      .initializeObligation(0)
+      .accountsPartial({
+        authority: obligationAuthority.publicKey,
+        reserve: reservePda,
+        obligation: LendingClient.getObligationPda(
+          obligationAuthority.publicKey,
+          marketPda,
+        ),
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    obligationPda = LendingClient.getObligationPda(
+      obligationAuthority.publicKey,
+      marketPda,
+    );
+
+    await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: obligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: depositAmount,
+      },
+    });
+
+    await buildAndSendv0Tx(
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: reservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: obligationPda,
+          })
+          .instruction(),
+        // deposit collateral into reserve
+        await program.methods
+          .depositReserveLiquidityAndObligationCollateral(new BN(depositAmount))
+          .accountsPartial({
+            authority: obligationAuthority.publicKey,
+            collateralMint: liquidityMint,
+            collateralTokenProgram: TOKEN_PROGRAM_ID,
+            obligation: obligationPda,
+            reserve: reservePda,
+          })
+          .instruction(),
+      ],
+      [obligationAuthority],
+    );
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: reservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: obligationPda,
+          })
+          .remainingAccounts([
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: reservePda,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: receiptMint,
+            },
+          ])
+          .instruction(),
+        // borrow liquidity from the same reserve
+        await program.methods
+          .borrowObligationLiquidity(new BN(borrowAmount))
+          .accountsPartial({
+            authority: obligationAuthority.publicKey,
+            obligationAuthority: obligationAuthority.publicKey,
+            liquidityMint,
+            obligation: obligationPda,
+            reserveTokenAccount: reserveAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            reserve: reservePda,
+          })
+          .instruction(),
+      ],
+      [obligationAuthority],
+    );
+  });
+
+  test("set auto-deleverage threshold", async () => {
+    const thresholdBps = 9000; // 90%
+
+    await program.methods
+      .setAutoDeleverage(thresholdBps)
+      .accountsPartial({
+        authority: obligationAuthority.publicKey,
+        obligation: obligationPda,
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    const obligationAcc = await client.fetchProgramAccount(
+      obligationPda,
+      "obligation",
+    );
+
+    expect(obligationAcc.autoDeleverageThresholdBps).toBe(thresholdBps);
+  });
+
+  test("auto-deleverage obligation", async () => {
+    // any borrow reaches a 0.01% threshold
+    await program.methods
+      .setAutoDeleverage(1)
+      .accountsPartial({
+        authority: obligationAuthority.publicKey,
+        obligation: obligationPda,
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    const preReserveAcc = await client.fetchProgramAccount(
+      reservePda,
+      "reserve",
+    );
+    const preObligationAcc = await client.fetchProgramAccount(
+      obligationPda,
+      "obligation",
+    );
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      [
+        ...(await refreshInstructions()),
+        await program.methods
+          .autoDeleverageObligation()
+          .accountsPartial({
+            keeper: keeper.publicKey,
+            obligation: obligationPda,
+            reserve: reservePda,
+            receiptTokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .instruction(),
+      ],
+      [keeper],
+    );
+
+    const postReserveAcc = await client.fetchProgramAccount(
+      reservePda,
+      "reserve",
+    );
+
+    expect(
+      preReserveAcc.liquidity.borrowedAmount.gt(
+        postReserveAcc.liquidity.borrowedAmount,
+      ),
+    ).toBeTrue();
+    // liquidity backing the burned receipts never leaves the reserve
+    expect(
+      preReserveAcc.liquidity.availableAmount.eq(
+        postReserveAcc.liquidity.availableAmount,
+      ),
+    ).toBeTrue();
+
+    const postObligationAcc = await client.fetchProgramAccount(
+      obligationPda,
+      "obligation",
+    );
+
+    expect(postObligationAcc.lastUpdate.isStale).toBeTrue();
+    expect(
+      preObligationAcc.borrows[0].borrowedAmount.gt(
+        postObligationAcc.borrows[0].borrowedAmount,
+      ),
+    ).toBeTrue();
+    expect(
+      preObligationAcc.deposits[0].depositedAmount.gt(
+        postObligationAcc.deposits[0].depositedAmount,
+      ),
+    ).toBeTrue();
+
+    const collateralEscrowAcc = await getAccount(
+      connection,
+      LendingClient.getCollateralEscrowPda(obligationPda, reservePda),
+    );
+
+    expect(collateralEscrowAcc.amount).toBe(
+      BigInt(postObligationAcc.deposits[0].depositedAmount.toString()),
+    );
+  });
+
+  afterEach(async () => {
+    await resetAccounts([marketPda]);
+  });
+});
@@ -0,0 +1,1046 @@
+import { afterEach, beforeEach, describe, expect, test } from "bun:test";
+import { LendingClient } from "../LendingClient";
+import { Connection, Keypair, PublicKey } from "@solana/web3.js";
+import {
+  buildAndSendv0Tx,
+  expireBlockhash,
+  getSetup,
+  resetAccounts,
+} from "../setup";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Lending } from "../../target/types/lending";
+import { Surfpool } from "../surfpool";
+import {
+  getAccount,
+  getAssociatedTokenAddressSync,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import {
+  USDC_MINT_DECIMALS,
+  USDC_USD_PRICE_UPDATE_V2,
+  WBTC_MINT_DECIMALS,
+  WBTC_USD_PRICE_UPDATE_V2,
+} from "../constants";
+
+describe("batchLiquidateObligation", () => {
+  let client: LendingClient;
+  let program: Program<Lending>;
+  let connection: Connection;
+
+  let marketAuthority: Keypair;
+  let borrowerObligationAuthority: Keypair;
+  let lenderObligationAuthority: Keypair;
+  let liquidator: Keypair;
+  let marketPda: PublicKey;
+  let collateralReservePda: PublicKey;
+  let liquidityReservePda: PublicKey;
+  let borrowerObligationPda: PublicKey;
+  let lenderObligationPda: PublicKey;
+  let collateralReceiptMint: PublicKey;
+  let liquidityReceiptMint: PublicKey;
+  let liquidityReserveAta: PublicKey;
+
+  let collateralMint: PublicKey;
+  const collateralMintPriceUpdateV2 = WBTC_USD_PRICE_UPDATE_V2;
+  let liquidityMint: PublicKey;
+  const liquidityMintPriceUpdateV2 = USDC_USD_PRICE_UPDATE_V2;
+
+  const collateralDepositAmount = 1 * Math.pow(10, WBTC_MINT_DECIMALS);
+  const liquidityDepositAmount = 1000 * Math.pow(10, USDC_MINT_DECIMALS);
+  const borrowAmount = liquidityDepositAmount / 2;
+
+  beforeEach(async () => {
+    [
+      marketAuthority,
+      borrowerObligationAuthority,
+      lenderObligationAuthority,
+      liquidator,
+    ] = Array.from({ length: 4 }, () => Keypair.generate());
+
+    ({ client } = await getSetup([
+      {
+        publicKey: marketAuthority.publicKey,
+      },
+      {
+        publicKey: borrowerObligationAuthority.publicKey,
+      },
+      {
+        publicKey: lenderObligationAuthority.publicKey,
+      },
+      {
+        publicKey: liquidator.publicKey,
+      },
+    ]));
+
+    program = client.program;
+    connection = client.connection;
+
+    collateralMint = await Surfpool.initMint({
+      decimals: WBTC_MINT_DECIMALS,
+    });
+
+    liquidityMint = await Surfpool.initMint({
+      decimals: USDC_MINT_DECIMALS,
+    });
+
+    // initializes a market
+    const name = "Test Market";
+
+    await program.methods
+      .initializeMarket(name)
+      .accounts({
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    marketPda = LendingClient.getMarketPda(name);
+
+    // initializes collateral reserve
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 8000; // 80%
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+    const priceUpdateV2 = collateralMintPriceUpdateV2;
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: null,
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint: collateralMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    collateralReservePda = LendingClient.getReservePda(
+      marketPda,
+      collateralMint,
+    );
+
+    // initializes an obligation
+    await program.methods
+      .initializeObligation(0)
+      .accountsPartial({
+        authority: borrowerObligationAuthority.publicKey,
+        reserve: collateralReservePda,
+        obligation: LendingClient.getObligationPda(
+          borrowerObligationAuthority.publicKey,
+          marketPda,
+        ),
+      })
+      .signers([borrowerObligationAuthority])
+      .rpc();
+
+    borrowerObligationPda = LendingClient.getObligationPda(
+      borrowerObligationAuthority.publicKey,
+      marketPda,
+    );
+
+    await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: borrowerObligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: collateralDepositAmount,
+      },
+    });
+
+    await buildAndSendv0Tx(
+      // refresh reserves and obligations
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: collateralMintPriceUpdateV2,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: borrowerObligationPda,
+          })
+          .instruction(),
+        // deposit collateral into reserve
+        await program.methods
+          .depositReserveLiquidityAndObligationCollateral(
+            new BN(collateralDepositAmount),
+          )
+          .accountsPartial({
+            authority: borrowerObligationAuthority.publicKey,
+            collateralMint,
+            collateralTokenProgram: TOKEN_PROGRAM_ID,
+            obligation: borrowerObligationPda,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+      ],
+      [borrowerObligationAuthority],
+    );
+
+    collateralReceiptMint = LendingClient.getReceiptMint(collateralReservePda);
+
+    // initialize liquidity reserve
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: null,
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2: liquidityMintPriceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    liquidityReservePda = LendingClient.getReservePda(marketPda, liquidityMint);
+    lenderObligationPda = LendingClient.getObligationPda(
+      lenderObligationAuthority.publicKey,
+      marketPda,
+    );
+
+    // initializes an obligation
+    await program.methods
+      .initializeObligation(0)
+      .accountsPartial({
+        authority: lenderObligationAuthority.publicKey,
+        reserve: liquidityReservePda,
+        obligation: lenderObligationPda,
+      })
+      .signers([lenderObligationAuthority])
+      .rpc();
+
+    await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: lenderObligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: liquidityDepositAmount,
+      },
+    });
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      // refresh reserves and obligations
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: lenderObligationPda,
+          })
+          .instruction(),
+        // deposit liquidity into reserve
+        await program.methods
+          .depositReserveLiquidityAndObligationCollateral(
+            new BN(liquidityDepositAmount),
+          )
+          .accountsPartial({
+            authority: lenderObligationAuthority.publicKey,
+            collateralMint: liquidityMint,
+            collateralTokenProgram: TOKEN_PROGRAM_ID,
+            obligation: lenderObligationPda,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+      ],
+      [lenderObligationAuthority],
+    );
+
+    liquidityReceiptMint = LendingClient.getReceiptMint(liquidityReservePda);
+
+    liquidityReserveAta = getAssociatedTokenAddressSync(
+      liquidityMint,
+      liquidityReservePda,
+      !PublicKey.isOnCurve(liquidityReservePda),
+    );
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      // refresh reserves and obligations
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: collateralMintPriceUpdateV2,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: borrowerObligationPda,
+          })
+          .remainingAccounts([
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReservePda,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReceiptMint,
+            },
+          ])
+          .instruction(),
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: lenderObligationPda,
+          })
+          .remainingAccounts([
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: liquidityReservePda,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: liquidityReceiptMint,
+            },
+          ])
+          .instruction(),
+        // borrow liquidity from reserve
+        await program.methods
+          .borrowObligationLiquidity(new BN(borrowAmount))
+          .accountsPartial({
+            authority: borrowerObligationAuthority.publicKey,
+            obligationAuthority: borrowerObligationAuthority.publicKey,
+            liquidityMint,
+            obligation: borrowerObligationPda,
+            reserveTokenAccount: liquidityReserveAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+      ],
+      [borrowerObligationAuthority],
+    );
+  });
+
+  // lowers the liquidation threshold so the borrower's obligation can be liquidated in full against the reserve
+  async function enableLiquidation(reservePda: PublicKey) {
+    await program.methods
+      .updateReserve({
+        flashLoanFeeBps: null,
+        flashLoansEnabled: null,
+        insuranceFeeBps: null,
+        liquidationBonusBps: null,
+        liquidationCloseFactorBps: 10000, // 100%
+        liquidationInsuranceFeeBps: null,
+        liquidationThresholdBps: 1, // 0.01%
+        loanToValueBps: null,
+        maxBorrowRateBps: null,
+        maxFlashLoanAmount: null,
+        minBorrowRateBps: null,
+        optimalBorrowRateBps: null,
+        optimalUtilizationRateBps: null,
+        platformFeeBps: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        reserve: reservePda,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+  }
+
+  // initializes another collateral reserve and deposits into it from the borrower's obligation
+  async function depositSecondCollateral(depositAmount: number) {
+    const mint = await Surfpool.initMint({
+      decimals: WBTC_MINT_DECIMALS,
+    });
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps: 500, // 5%
+        flashLoansEnabled: true,
+        insuranceFeeBps: 1000, // 10%
+        liquidationBonusBps: 200, // 2%
+        liquidationCloseFactorBps: 1000, // 10%
+        liquidationInsuranceFeeBps: 1000, // 10%
+        liquidationThresholdBps: 8500, // 85%
+        liquiditySymbol: null,
+        loanToValueBps: 8000, // 80%
+        maxBorrowRateBps: 8000, // 80%
+        maxFlashLoanAmount: new BN(Number.MAX_SAFE_INTEGER),
+        minBorrowRateBps: 200, // 2%
+        optimalBorrowRateBps: 2000, // 20%
+        optimalUtilizationRateBps: 7500, // 75%
+        platformFeeBps: 250, // 2.5%
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint: mint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2: collateralMintPriceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const reservePda = LendingClient.getReservePda(marketPda, mint);
+
+    await Surfpool.setTokenAccount({
+      mint: mint.toBase58(),
+      owner: borrowerObligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: depositAmount,
+      },
+    });
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: collateralMintPriceUpdateV2,
+            reserve: reservePda,
+          })
+          .instruction(),
+        await program.methods
+          .depositReserveLiquidityAndObligationCollateral(
+            new BN(depositAmount),
+          )
+          .accountsPartial({
+            authority: borrowerObligationAuthority.publicKey,
+            collateralMint: mint,
+            collateralTokenProgram: TOKEN_PROGRAM_ID,
+            obligation: borrowerObligationPda,
+            reserve: reservePda,
+          })
+          .instruction(),
+      ],
+      [borrowerObligationAuthority],
+    );
+
+    return {
+      mint,
+      reservePda,
+      receiptMint: LendingClient.getReceiptMint(reservePda),
+    };
+  }
+
+  // remaining accounts for one step repaying the liquidity reserve and withdrawing from a collateral reserve
+  function liquidationStepAccounts(
+    withdrawReservePda: PublicKey,
+    withdrawMint: PublicKey,
+    liquidatorLiquidityTokenAccount: PublicKey,
+    liquidatorCollateralTokenAccount: PublicKey,
+  ) {
+    return [
+      liquidityReservePda,
+      withdrawReservePda,
+      liquidityMint,
+      withdrawMint,
+      liquidityReserveAta,
+      getAssociatedTokenAddressSync(
+        withdrawMint,
+        withdrawReservePda,
+        !PublicKey.isOnCurve(withdrawReservePda),
+      ),
+      LendingClient.getInsuranceVaultPda(withdrawReservePda),
+      liquidatorLiquidityTokenAccount,
+      liquidatorCollateralTokenAccount,
+      LendingClient.getReceiptMint(withdrawReservePda),
+      LendingClient.getCollateralEscrowPda(
+        borrowerObligationPda,
+        withdrawReservePda,
+      ),
+      TOKEN_PROGRAM_ID,
+      TOKEN_PROGRAM_ID,
+      TOKEN_PROGRAM_ID,
+    ].map((pubkey, i) => ({
+      isSigner: false,
+      isWritable: [0, 1, 4, 5, 6, 7, 8, 9, 10].includes(i),
+      pubkey,
+    }));
+  }
+
+  // refreshes both collateral reserves, the liquidity reserve and the borrower's obligation
+  async function refreshInstructions(
+    secondReservePda: PublicKey,
+    secondReceiptMint: PublicKey,
+  ) {
+    return [
+      await program.methods
+        .refreshReserve()
+        .accounts({
+          priceUpdateV2: collateralMintPriceUpdateV2,
+          reserve: collateralReservePda,
+        })
+        .instruction(),
+      await program.methods
+        .refreshReserve()
+        .accounts({
+          priceUpdateV2: collateralMintPriceUpdateV2,
+          reserve: secondReservePda,
+        })
+        .instruction(),
+      await program.methods
+        .refreshReserve()
+        .accounts({
+          priceUpdateV2: liquidityMintPriceUpdateV2,
+          reserve: liquidityReservePda,
+        })
+        .instruction(),
+      await program.methods
+        .refreshObligation()
+        .accounts({
+          obligation: borrowerObligationPda,
+        })
+        .remainingAccounts(
+          [
+            collateralReservePda,
+            collateralReceiptMint,
+            secondReservePda,
+            secondReceiptMint,
+            liquidityReservePda,
+          ].map((pubkey) => ({
+            isSigner: false,
+            isWritable: false,
+            pubkey,
+          })),
+        )
+        .instruction(),
+    ];
+  }
+
+  test("batch liquidate obligation", async () => {
+    // update reserve to meet liquidation requirements
+    await program.methods
+      .updateReserve({
+        flashLoanFeeBps: null,
+        flashLoansEnabled: null,
+        insuranceFeeBps: null,
+        liquidationBonusBps: null,
+        liquidationCloseFactorBps: 10000, // 100%
+        liquidationInsuranceFeeBps: null,
+        liquidationThresholdBps: 1, // 0.01%
+        loanToValueBps: null,
+        maxBorrowRateBps: null,
+        maxFlashLoanAmount: null,
+        minBorrowRateBps: null,
+        optimalBorrowRateBps: null,
+        optimalUtilizationRateBps: null,
+        platformFeeBps: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        reserve: collateralReservePda,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    // set liquidator liquidity token account
+    const liquidatorLiquidityTokenAccount = await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: liquidator.publicKey.toBase58(),
+      update: {
+        amount: borrowAmount,
+      },
+    });
+
+    // set liquidator collateral token account
+    const liquidatorCollateralTokenAccount = await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: liquidator.publicKey.toBase58(),
+      update: {
+        amount: 0,
+      },
+    });
+
+    const collateralReserveAta = getAssociatedTokenAddressSync(
+      collateralMint,
+      collateralReservePda,
+      !PublicKey.isOnCurve(collateralReservePda),
+    );
+
+    const preLiquidityReserveAcc = await client.fetchProgramAccount(
+      liquidityReservePda,
+      "reserve",
+    );
+    const preBorrowerObligationAcc = await client.fetchProgramAccount(
+      borrowerObligationPda,
+      "obligation",
+    );
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      [
+        // refresh reserves and obligations
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: collateralMintPriceUpdateV2,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: borrowerObligationPda,
+          })
+          .remainingAccounts([
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReservePda,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReceiptMint,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: liquidityReservePda,
+            },
+          ])
+          .instruction(),
+        // liquidate borrower's obligation
+        await program.methods
+          .batchLiquidateObligation()
+          .accountsPartial({
+            liquidator: liquidator.publicKey,
+            market: marketPda,
+            obligation: borrowerObligationPda,
+          })
+          .remainingAccounts(
+            [
+              liquidityReservePda,
+              collateralReservePda,
+              liquidityMint,
+              collateralMint,
+              liquidityReserveAta,
+              collateralReserveAta,
+              LendingClient.getInsuranceVaultPda(collateralReservePda),
+              liquidatorLiquidityTokenAccount,
+              liquidatorCollateralTokenAccount,
+              collateralReceiptMint,
+              LendingClient.getCollateralEscrowPda(
+                borrowerObligationPda,
+                collateralReservePda,
+              ),
+              TOKEN_PROGRAM_ID,
+              TOKEN_PROGRAM_ID,
+              TOKEN_PROGRAM_ID,
+            ].map((pubkey, i) => ({
+              isSigner: false,
+              isWritable: [0, 1, 4, 5, 6, 7, 8, 9, 10].includes(i),
+              pubkey,
+            })),
+          )
+          .instruction(),
+      ],
+      [liquidator],
+    );
+
+    const postLiquidityReserveAcc = await client.fetchProgramAccount(
+      liquidityReservePda,
+      "reserve",
+    );
+
+    expect(preLiquidityReserveAcc.lastUpdate.isStale).toBeTrue();
+    expect(
+      preLiquidityReserveAcc.liquidity.availableAmount.lt(
+        postLiquidityReserveAcc.liquidity.availableAmount,
+      ),
+    ).toBeTrue();
+    expect(
+      preLiquidityReserveAcc.liquidity.borrowedAmount.gt(
+        postLiquidityReserveAcc.liquidity.borrowedAmount,
+      ),
+    ).toBeTrue();
+
+    const postBorrowerObligationAcc = await client.fetchProgramAccount(
+      borrowerObligationPda,
+      "obligation",
+    );
+
+    expect(postBorrowerObligationAcc.lastUpdate.isStale).toBeTrue();
+    expect(
+      preBorrowerObligationAcc.borrows[0].borrowedAmount.gt(
+        postBorrowerObligationAcc.borrows[0].borrowedAmount,
+      ),
+    ).toBeTrue();
+    expect(
+      preBorrowerObligationAcc.deposits[0].depositedAmount.gt(
+        postBorrowerObligationAcc.deposits[0].depositedAmount,
+      ),
+    ).toBeTrue();
+  });
+
+  test("batch liquidate two collateral reserves against the same repay reserve", async () => {
+    // small enough that liquidating it alone leaves the obligation unhealthy
+    const second = await depositSecondCollateral(
+      collateralDepositAmount / 1000,
+    );
+
+    await enableLiquidation(collateralReservePda);
+    await enableLiquidation(second.reservePda);
+
+    const liquidatorLiquidityTokenAccount = await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: liquidator.publicKey.toBase58(),
+      update: {
+        amount: borrowAmount,
+      },
+    });
+    const liquidatorCollateralTokenAccount = await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: liquidator.publicKey.toBase58(),
+      update: {
+        amount: 0,
+      },
+    });
+    const liquidatorSecondCollateralTokenAccount =
+      await Surfpool.setTokenAccount({
+        mint: second.mint.toBase58(),
+        owner: liquidator.publicKey.toBase58(),
+        update: {
+          amount: 0,
+        },
+      });
+
+    const preBorrowerObligationAcc = await client.fetchProgramAccount(
+      borrowerObligationPda,
+      "obligation",
+    );
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      [
+        ...(await refreshInstructions(second.reservePda, second.receiptMint)),
+        await program.methods
+          .batchLiquidateObligation()
+          .accountsPartial({
+            liquidator: liquidator.publicKey,
+            market: marketPda,
+            obligation: borrowerObligationPda,
+          })
+          .remainingAccounts([
+            ...liquidationStepAccounts(
+              second.reservePda,
+              second.mint,
+              liquidatorLiquidityTokenAccount,
+              liquidatorSecondCollateralTokenAccount,
+            ),
+            ...liquidationStepAccounts(
+              collateralReservePda,
+              collateralMint,
+              liquidatorLiquidityTokenAccount,
+              liquidatorCollateralTokenAccount,
+            ),
+          ])
+          .instruction(),
+      ],
+      [liquidator],
+    );
+
+    const liquidatorSecondCollateralAcc = await getAccount(
+      connection,
+      liquidatorSecondCollateralTokenAccount,
+    );
+    const liquidatorCollateralAcc = await getAccount(
+      connection,
+      liquidatorCollateralTokenAccount,
+    );
+
+    expect(liquidatorSecondCollateralAcc.amount).toBeGreaterThan(0);
+    expect(liquidatorCollateralAcc.amount).toBeGreaterThan(0);
+
+    const postBorrowerObligationAcc = await client.fetchProgramAccount(
+      borrowerObligationPda,
+      "obligation",
+    );
+
+    // the second reserve is withdrawn in full, leaving only the first deposit
+    expect(postBorrowerObligationAcc.deposits.length).toBe(1);
+    expect(
+      postBorrowerObligationAcc.deposits[0].reserve.equals(
+        collateralReservePda,
+      ),
+    ).toBeTrue();
+    expect(
+      preBorrowerObligationAcc.deposits[0].depositedAmount.gt(
+        postBorrowerObligationAcc.deposits[0].depositedAmount,
+      ),
+    ).toBeTrue();
+
+    const postLiquidityReserveAcc = await client.fetchProgramAccount(
+      liquidityReservePda,
+      "reserve",
+    );
+    const postSecondReserveAcc = await client.fetchProgramAccount(
+      second.reservePda,
+      "reserve",
+    );
+
+    expect(postLiquidityReserveAcc.lastUpdate.isStale).toBeTrue();
+    expect(postSecondReserveAcc.lastUpdate.isStale).toBeTrue();
+  });
+
+  test("stop batch liquidating once the obligation is healthy", async () => {
+    const second = await depositSecondCollateral(
+      collateralDepositAmount / 1000,
+    );
+
+    await enableLiquidation(collateralReservePda);
+    await enableLiquidation(second.reservePda);
+
+    const liquidatorLiquidityTokenAccount = await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: liquidator.publicKey.toBase58(),
+      update: {
+        amount: borrowAmount * 2,
+      },
+    });
+    const liquidatorCollateralTokenAccount = await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: liquidator.publicKey.toBase58(),
+      update: {
+        amount: 0,
+      },
+    });
+    const liquidatorSecondCollateralTokenAccount =
+      await Surfpool.setTokenAccount({
+        mint: second.mint.toBase58(),
+        owner: liquidator.publicKey.toBase58(),
+        update: {
+          amount: 0,
+        },
+      });
+
+    const preSecondReserveAcc = await client.fetchProgramAccount(
+      second.reservePda,
+      "reserve",
+    );
+
+    await expireBlockhash();
+
+    // the first collateral reserve covers the whole borrow, so the second step is skipped
+    await buildAndSendv0Tx(
+      [
+        ...(await refreshInstructions(second.reservePda, second.receiptMint)),
+        await program.methods
+          .batchLiquidateObligation()
+          .accountsPartial({
+            liquidator: liquidator.publicKey,
+            market: marketPda,
+            obligation: borrowerObligationPda,
+          })
+          .remainingAccounts([
+            ...liquidationStepAccounts(
+              collateralReservePda,
+              collateralMint,
+              liquidatorLiquidityTokenAccount,
+              liquidatorCollateralTokenAccount,
+            ),
+            ...liquidationStepAccounts(
+              second.reservePda,
+              second.mint,
+              liquidatorLiquidityTokenAccount,
+              liquidatorSecondCollateralTokenAccount,
+            ),
+          ])
+          .instruction(),
+      ],
+      [liquidator],
+    );
+
+    const liquidatorSecondCollateralAcc = await getAccount(
+      connection,
+      liquidatorSecondCollateralTokenAccount,
+    );
+
+    expect(liquidatorSecondCollateralAcc.amount).toBe(0n);
+
+    const postBorrowerObligationAcc = await client.fetchProgramAccount(
+      borrowerObligationPda,
+      "obligation",
+    );
+
+    expect(postBorrowerObligationAcc.borrows.length).toBe(0);
+    expect(postBorrowerObligationAcc.deposits.length).toBe(2);
+    expect(
+      postBorrowerObligationAcc.deposits[1].depositedAmount.eqn(
+        collateralDepositAmount / 1000,
+      ),
+    ).toBeTrue();
+
+    const postSecondReserveAcc = await client.fetchProgramAccount(
+      second.reservePda,
+      "reserve",
+    );
+
+    // reserves of skipped steps are left untouched
+    expect(
+      postSecondReserveAcc.liquidity.availableAmount.eq(
+        preSecondReserveAcc.liquidity.availableAmount,
+      ),
+    ).toBeTrue();
+    expect(postSecondReserveAcc.lastUpdate.isStale).toBeFalse();
+  });
+
+  test("reject remaining accounts that are not whole steps", async () => {
+    await enableLiquidation(collateralReservePda);
+
+    const liquidatorLiquidityTokenAccount = await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: liquidator.publicKey.toBase58(),
+      update: {
+        amount: borrowAmount,
+      },
+    });
+    const liquidatorCollateralTokenAccount = await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: liquidator.publicKey.toBase58(),
+      update: {
+        amount: 0,
+      },
+    });
+
+    await expireBlockhash();
+
+    await expect(
+      buildAndSendv0Tx(
+        [
+          await program.methods
+            .refreshReserve()
+            .accounts({
+              priceUpdateV2: collateralMintPriceUpdateV2,
+              reserve: collateralReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .refreshReserve()
+            .accounts({
+              priceUpdateV2: liquidityMintPriceUpdateV2,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .refreshObligation()
+            .accounts({
+              obligation: borrowerObligationPda,
+            })
+            .remainingAccounts(
+              [
+                collateralReservePda,
+                collateralReceiptMint,
+                liquidityReservePda,
+              ].map((pubkey) => ({
+                isSigner: false,
+                isWritable: false,
+                pubkey,
+              })),
+            )
+            .instruction(),
+          await program.methods
+            .batchLiquidateObligation()
+            .accountsPartial({
+              liquidator: liquidator.publicKey,
+              market: marketPda,
+              obligation: borrowerObligationPda,
+            })
+            .remainingAccounts(
+              // drops the trailing receipt token program
+              liquidationStepAccounts(
+                collateralReservePda,
+                collateralMint,
+                liquidatorLiquidityTokenAccount,
+                liquidatorCollateralTokenAccount,
+              ).slice(0, -1),
+            )
+            .instruction(),
+        ],
+        [liquidator],
+      ),
+    ).rejects.toThrow("InvalidLiquidationAccounts");
+  });
+
+  afterEach(async () => {
+    await resetAccounts([marketPda]);
+  });
+});
@@ -2,6 +2,7 @@ import { afterEach, beforeEach, describe, expect, test } from "bun:test";
 import { LendingClient } from "../LendingClient";
 import { Connection, Keypair, PublicKey } from "@solana/web3.js";
 import {
+  airdropAccount,
   buildAndSendv0Tx,
   expireBlockhash,
   getSetup,
@@ -11,10 +12,12 @@ import { BN, Program } from "@coral-xyz/anchor";
 import { Lending } from "../../target/types/lending";
 import { Surfpool } from "../surfpool";
 import {
+  getAccount,
   getAssociatedTokenAddressSync,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import {
+  DELEGATE_PERMISSION_BORROW,
   USDC_MINT_DECIMALS,
   USDC_USD_PRICE_UPDATE_V2,
   WBTC_MINT_DECIMALS,
@@ -96,24 +99,35 @@ describe("borrowObligationLiquidity", () => {
     const maxBorrowRateBps = 8000; // 80%
     const flashLoanFeeBps = 500; // 5%
     const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
     const priceUpdateV2 = collateralMintPriceUpdateV2;
 
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint: collateralMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -128,10 +142,14 @@ describe("borrowObligationLiquidity", () => {
 
     // initializes an obligation
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: borrowerObligationAuthority.publicKey,
         reserve: collateralReservePda,
+        obligation: LendingClient.getObligationPda(
+          borrowerObligationAuthority.publicKey,
+          marketPda,
+        ),
       })
       .signers([borrowerObligationAuthority])
       .rpc();
@@ -188,19 +206,26 @@ describe("borrowObligationLiquidity", () => {
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2: liquidityMintPriceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -216,7 +241,7 @@ describe("borrowObligationLiquidity", () => {
 
     // initializes an obligation
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: lenderObligationAuthority.publicKey,
         reserve: liquidityReservePda,
@@ -345,6 +370,7 @@ describe("borrowObligationLiquidity", () => {
           .borrowObligationLiquidity(new BN(borrowAmount))
           .accountsPartial({
             authority: borrowerObligationAuthority.publicKey,
+            obligationAuthority: borrowerObligationAuthority.publicKey,
             liquidityMint,
             obligation: borrowerObligationPda,
             reserveTokenAccount: liquidityReserveAta,
@@ -386,6 +412,106 @@ describe("borrowObligationLiquidity", () => {
     ).toBeTrue();
   });
 
+  test("borrow by a delegate into the owner's token account", async () => {
+    const borrowAmount = liquidityDepositAmount / 4;
+    const manager = Keypair.generate();
+
+    await airdropAccount(manager.publicKey);
+
+    await program.methods
+      .setObligationDelegate(
+        manager.publicKey,
+        DELEGATE_PERMISSION_BORROW,
+        new BN(0),
+      )
+      .accountsPartial({
+        authority: borrowerObligationAuthority.publicKey,
+        obligation: borrowerObligationPda,
+      })
+      .signers([borrowerObligationAuthority])
+      .rpc();
+
+    const obligationDelegatePda = LendingClient.getObligationDelegatePda(
+      borrowerObligationPda,
+      manager.publicKey,
+    );
+
+    const liquidityReserveAta = getAssociatedTokenAddressSync(
+      liquidityMint,
+      liquidityReservePda,
+      !PublicKey.isOnCurve(liquidityReservePda),
+    );
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: collateralMintPriceUpdateV2,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: borrowerObligationPda,
+          })
+          .remainingAccounts([
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReservePda,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReceiptMint,
+            },
+          ])
+          .instruction(),
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        // the delegate signs, but the owner receives the borrowed liquidity
+        await program.methods
+          .borrowObligationLiquidity(new BN(borrowAmount))
+          .accountsPartial({
+            authority: manager.publicKey,
+            obligationAuthority: borrowerObligationAuthority.publicKey,
+            liquidityMint,
+            obligation: borrowerObligationPda,
+            obligationDelegate: obligationDelegatePda,
+            reserveTokenAccount: liquidityReserveAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+      ],
+      [manager],
+    );
+
+    const ownerLiquidityAtaAcc = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(
+        liquidityMint,
+        borrowerObligationAuthority.publicKey,
+      ),
+    );
+
+    expect(ownerLiquidityAtaAcc.amount).toBe(BigInt(borrowAmount));
+    expect(
+      await connection.getAccountInfo(
+        getAssociatedTokenAddressSync(liquidityMint, manager.publicKey),
+      ),
+    ).toBeNull();
+  });
+
   afterEach(async () => {
     await resetAccounts([marketPda]);
   });
@@ -0,0 +1,321 @@
+import { afterEach, beforeEach, describe, expect, test } from "bun:test";
+import { LendingClient } from "../LendingClient";
+import { Connection, Keypair, PublicKey } from "@solana/web3.js";
+import { getSetup, resetAccounts } from "../setup";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Lending } from "../../target/types/lending";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { USDC_MINT_DECIMALS, USDC_USD_PRICE_UPDATE_V2 } from "../constants";
+import { Surfpool } from "../surfpool";
+
+describe("closeObligation", () => {
+  let client: LendingClient;
+  let program: Program<Lending>;
+  let connection: Connection;
+
+  let marketAuthority: Keypair;
+  let depositor: Keypair;
+  let marketPda: PublicKey;
+  let reservePda: PublicKey;
+  let receiptMint: PublicKey;
+  let obligationPda: PublicKey;
+  let collateralEscrowPda: PublicKey;
+
+  let liquidityMint: PublicKey;
+  const liquidityMintPriceUpdateV2 = USDC_USD_PRICE_UPDATE_V2;
+
+  const depositAmount = 100 * Math.pow(10, USDC_MINT_DECIMALS);
+
+  beforeEach(async () => {
+    [marketAuthority, depositor] = Array.from({ length: 2 }, () =>
+      Keypair.generate(),
+    );
+
+    ({ client } = await getSetup([
+      {
+        publicKey: marketAuthority.publicKey,
+      },
+      {
+        publicKey: depositor.publicKey,
+      },
+    ]));
+
+    program = client.program;
+    connection = client.connection;
+
+    liquidityMint = await Surfpool.initMint({
+      decimals: USDC_MINT_DECIMALS,
+    });
+
+    // initializes a market
+    const name = "Test Market";
+
+    await program.methods
+      .initializeMarket(name)
+      .accounts({
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    marketPda = LendingClient.getMarketPda(name);
+
+    // initializes a reserve
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 8000; // 80%
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: null,
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2: liquidityMintPriceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    reservePda = LendingClient.getReservePda(marketPda, liquidityMint);
+    receiptMint = LendingClient.getReceiptMint(reservePda);
+
+    await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: depositor.publicKey.toBase58(),
+      update: {
+        amount: depositAmount,
+      },
+    });
+
+    await program.methods
+      .depositReserveLiquidity(new BN(depositAmount))
+      .accountsPartial({
+        authority: depositor.publicKey,
+        liquidityMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        reserve: reservePda,
+      })
+      .signers([depositor])
+      .preInstructions([
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: reservePda,
+          })
+          .instruction(),
+      ])
+      .rpc();
+
+    // initializes an obligation
+    await program.methods
+      .initializeObligation(0)
+      .accountsPartial({
+        authority: depositor.publicKey,
+        reserve: reservePda,
+        obligation: LendingClient.getObligationPda(
+          depositor.publicKey,
+          marketPda,
+        ),
+      })
+      .signers([depositor])
+      .rpc();
+
+    obligationPda = LendingClient.getObligationPda(
+      depositor.publicKey,
+      marketPda,
+    );
+    collateralEscrowPda = LendingClient.getCollateralEscrowPda(
+      obligationPda,
+      reservePda,
+    );
+  });
+
+  test("close an empty obligation", async () => {
+    const receiver = Keypair.generate().publicKey;
+    const obligationRent = (await connection.getAccountInfo(obligationPda))!
+      .lamports;
+
+    await program.methods
+      .closeObligation()
+      .accountsPartial({
+        authority: depositor.publicKey,
+        obligation: obligationPda,
+        receiver,
+      })
+      .signers([depositor])
+      .rpc();
+
+    expect(await connection.getAccountInfo(obligationPda)).toBeNull();
+    expect(await connection.getBalance(receiver)).toBe(obligationRent);
+  });
+
+  test("close an obligation with its emptied collateral escrow", async () => {
+    await program.methods
+      .depositObligationCollateral(new BN(depositAmount))
+      .accountsPartial({
+        authority: depositor.publicKey,
+        obligation: obligationPda,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        reserve: reservePda,
+      })
+      .signers([depositor])
+      .preInstructions([
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: reservePda,
+          })
+          .instruction(),
+      ])
+      .rpc();
+
+    await program.methods
+      .withdrawObligationCollateralReceipt(new BN(depositAmount))
+      .accountsPartial({
+        authority: depositor.publicKey,
+        obligationAuthority: depositor.publicKey,
+        obligation: obligationPda,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        reserve: reservePda,
+      })
+      .signers([depositor])
+      .preInstructions([
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: reservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: obligationPda,
+          })
+          .remainingAccounts([
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: reservePda,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: receiptMint,
+            },
+          ])
+          .instruction(),
+      ])
+      .rpc();
+
+    const receiver = Keypair.generate().publicKey;
+    const obligationRent = (await connection.getAccountInfo(obligationPda))!
+      .lamports;
+    const collateralEscrowRent = (await connection.getAccountInfo(
+      collateralEscrowPda,
+    ))!.lamports;
+
+    await program.methods
+      .closeObligation()
+      .accountsPartial({
+        authority: depositor.publicKey,
+        obligation: obligationPda,
+        receiver,
+      })
+      .remainingAccounts([
+        {
+          isSigner: false,
+          isWritable: false,
+          pubkey: reservePda,
+        },
+        {
+          isSigner: false,
+          isWritable: true,
+          pubkey: collateralEscrowPda,
+        },
+        {
+          isSigner: false,
+          isWritable: false,
+          pubkey: TOKEN_PROGRAM_ID,
+        },
+      ])
+      .signers([depositor])
+      .rpc();
+
+    expect(await connection.getAccountInfo(obligationPda)).toBeNull();
+    expect(await connection.getAccountInfo(collateralEscrowPda)).toBeNull();
+    expect(await connection.getBalance(receiver)).toBe(
+      obligationRent + collateralEscrowRent,
+    );
+  });
+
+  test("reject closing an obligation with deposits", async () => {
+    await program.methods
+      .depositObligationCollateral(new BN(depositAmount))
+      .accountsPartial({
+        authority: depositor.publicKey,
+        obligation: obligationPda,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        reserve: reservePda,
+      })
+      .signers([depositor])
+      .preInstructions([
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: reservePda,
+          })
+          .instruction(),
+      ])
+      .rpc();
+
+    await expect(
+      program.methods
+        .closeObligation()
+        .accountsPartial({
+          authority: depositor.publicKey,
+          obligation: obligationPda,
+          receiver: depositor.publicKey,
+        })
+        .signers([depositor])
+        .rpc(),
+    ).rejects.toThrow("ObligationDepositsNotEmpty");
+  });
+
+  afterEach(async () => {
+    await resetAccounts([marketPda]);
+  });
+});
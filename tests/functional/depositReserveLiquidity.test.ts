@@ -0,0 +1,439 @@
+import { afterEach, beforeEach, describe, expect, test } from "bun:test";
+import { LendingClient } from "../LendingClient";
+import {
+  Connection,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+} from "@solana/web3.js";
+import { buildAndSendv0Tx, getSetup, resetAccounts } from "../setup";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Lending } from "../../target/types/lending";
+import {
+  createAssociatedTokenAccountInstruction,
+  createInitializeMint2Instruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMintToInstruction,
+  ExtensionType,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMintLen,
+  TOKEN_2022_PROGRAM_ID,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import {
+  MINT_EXTENSION_TRANSFER_FEE,
+  USDC_MINT_DECIMALS,
+  USDC_USD_PRICE_UPDATE_V2,
+} from "../constants";
+import { Surfpool } from "../surfpool";
+
+describe("depositReserveLiquidity", () => {
+  let client: LendingClient;
+  let program: Program<Lending>;
+  let connection: Connection;
+
+  let marketAuthority: Keypair;
+  let depositor: Keypair;
+  let marketPda: PublicKey;
+  let reservePda: PublicKey;
+  let receiptMint: PublicKey;
+
+  let liquidityMint: PublicKey;
+  const liquidityMintPriceUpdateV2 = USDC_USD_PRICE_UPDATE_V2;
+
+  const depositAmount = 100 * Math.pow(10, USDC_MINT_DECIMALS);
+
+  beforeEach(async () => {
+    [marketAuthority, depositor] = Array.from({ length: 2 }, () =>
+      Keypair.generate(),
+    );
+
+    ({ client } = await getSetup([
+      {
+        publicKey: marketAuthority.publicKey,
+      },
+      {
+        publicKey: depositor.publicKey,
+      },
+    ]));
+
+    program = client.program;
+    connection = client.connection;
+
+    liquidityMint = await Surfpool.initMint({
+      decimals: USDC_MINT_DECIMALS,
+    });
+
+    // initializes a market
+    const name = "Test Market";
+
+    await program.methods
+      .initializeMarket(name)
+      .accounts({
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    marketPda = LendingClient.getMarketPda(name);
+
+    // initializes a reserve
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 0; // borrow-only
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: null,
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2: liquidityMintPriceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    reservePda = LendingClient.getReservePda(marketPda, liquidityMint);
+    receiptMint = LendingClient.getReceiptMint(reservePda);
+
+    await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: depositor.publicKey.toBase58(),
+      update: {
+        amount: depositAmount,
+      },
+    });
+
+    await program.methods
+      .depositReserveLiquidity(new BN(depositAmount))
+      .accountsPartial({
+        authority: depositor.publicKey,
+        liquidityMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        reserve: reservePda,
+      })
+      .signers([depositor])
+      .preInstructions([
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: reservePda,
+          })
+          .instruction(),
+      ])
+      .rpc();
+  });
+
+  test("deposit liquidity into a borrow-only reserve", async () => {
+    const reserveAcc = await client.fetchProgramAccount(reservePda, "reserve");
+
+    expect(reserveAcc.lastUpdate.isStale).toBeTrue();
+    expect(reserveAcc.liquidity.availableAmount.toNumber()).toBe(depositAmount);
+
+    const receiptTokenAcc = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(receiptMint, depositor.publicKey),
+    );
+
+    expect(Number(receiptTokenAcc.amount)).toBe(depositAmount);
+  });
+
+  test("redeem receipt tokens while liquidity is borrowed", async () => {
+    // move most of the supply into borrows without changing the exchange rate
+    const borrowedAmount = (depositAmount * 3) / 4;
+    const availableAmount = depositAmount - borrowedAmount;
+    const reserveInfo = await connection.getAccountInfo(reservePda);
+    const reserveAcc = program.coder.accounts.decode(
+      "reserve",
+      reserveInfo.data,
+    );
+
+    reserveAcc.liquidity.availableAmount = new BN(availableAmount);
+    reserveAcc.liquidity.borrowedAmount = new BN(borrowedAmount);
+
+    await Surfpool.setAccount({
+      publicKey: reservePda.toBase58(),
+      data: (
+        await program.coder.accounts.encode("reserve", reserveAcc)
+      ).toHex(),
+      lamports: reserveInfo.lamports,
+      owner: program.programId.toBase58(),
+    });
+
+    // less than the available liquidity, which is itself less than the borrowed liquidity
+    const redeemAmount = availableAmount / 2;
+
+    await program.methods
+      .redeemReserveReceipt(new BN(redeemAmount))
+      .accountsPartial({
+        authority: depositor.publicKey,
+        liquidityMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        reserve: reservePda,
+      })
+      .signers([depositor])
+      .preInstructions([
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: reservePda,
+          })
+          .instruction(),
+      ])
+      .rpc();
+
+    const postReserveAcc = await client.fetchProgramAccount(
+      reservePda,
+      "reserve",
+    );
+
+    expect(postReserveAcc.liquidity.availableAmount.toNumber()).toBeLessThan(
+      availableAmount,
+    );
+
+    const depositorTokenAcc = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(liquidityMint, depositor.publicKey),
+    );
+
+    expect(Number(depositorTokenAcc.amount)).toBeGreaterThanOrEqual(
+      redeemAmount,
+    );
+  });
+
+  test("redeem receipt tokens for liquidity", async () => {
+    await program.methods
+      .redeemReserveReceipt(new BN(depositAmount))
+      .accountsPartial({
+        authority: depositor.publicKey,
+        liquidityMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        reserve: reservePda,
+      })
+      .signers([depositor])
+      .preInstructions([
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: reservePda,
+          })
+          .instruction(),
+      ])
+      .rpc();
+
+    const reserveAcc = await client.fetchProgramAccount(reservePda, "reserve");
+
+    expect(reserveAcc.liquidity.availableAmount.toNumber()).toBe(0);
+
+    const depositorTokenAcc = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(liquidityMint, depositor.publicKey),
+    );
+
+    expect(Number(depositorTokenAcc.amount)).toBe(depositAmount);
+  });
+
+  test("credit only the received amount for transfer-fee mints", async () => {
+    const transferFeeMint = Keypair.generate();
+    const transferFeeBps = 100; // 1%
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const depositorAta = getAssociatedTokenAddressSync(
+      transferFeeMint.publicKey,
+      depositor.publicKey,
+      false,
+      TOKEN_2022_PROGRAM_ID,
+    );
+
+    await buildAndSendv0Tx(
+      [
+        SystemProgram.createAccount({
+          fromPubkey: marketAuthority.publicKey,
+          newAccountPubkey: transferFeeMint.publicKey,
+          space: mintLen,
+          lamports: await connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          transferFeeMint.publicKey,
+          marketAuthority.publicKey,
+          marketAuthority.publicKey,
+          transferFeeBps,
+          BigInt(depositAmount),
+          TOKEN_2022_PROGRAM_ID,
+        ),
+        createInitializeMint2Instruction(
+          transferFeeMint.publicKey,
+          USDC_MINT_DECIMALS,
+          marketAuthority.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID,
+        ),
+        createAssociatedTokenAccountInstruction(
+          marketAuthority.publicKey,
+          depositorAta,
+          depositor.publicKey,
+          transferFeeMint.publicKey,
+          TOKEN_2022_PROGRAM_ID,
+        ),
+        createMintToInstruction(
+          transferFeeMint.publicKey,
+          depositorAta,
+          marketAuthority.publicKey,
+          depositAmount,
+          [],
+          TOKEN_2022_PROGRAM_ID,
+        ),
+      ],
+      [marketAuthority, transferFeeMint],
+    );
+
+    await program.methods
+      .updateMarket({
+        allowedMintExtensions: MINT_EXTENSION_TRANSFER_FEE,
+        listingApprover: null,
+        permissionedListing: null,
+        complianceAuthority: null,
+        permissionedObligations: null,
+        maxObligationDeposits: null,
+        maxObligationBorrows: null,
+        dustThresholdValue: null,
+        receiptTokenProgram: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 0; // borrow-only
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: null,
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint: transferFeeMint.publicKey,
+        liquidityTokenProgram: TOKEN_2022_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2: liquidityMintPriceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const transferFeeReservePda = LendingClient.getReservePda(
+      marketPda,
+      transferFeeMint.publicKey,
+    );
+
+    await program.methods
+      .depositReserveLiquidity(new BN(depositAmount))
+      .accountsPartial({
+        authority: depositor.publicKey,
+        liquidityMint: transferFeeMint.publicKey,
+        liquidityTokenProgram: TOKEN_2022_PROGRAM_ID,
+        reserve: transferFeeReservePda,
+      })
+      .signers([depositor])
+      .preInstructions([
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: transferFeeReservePda,
+          })
+          .instruction(),
+      ])
+      .rpc();
+
+    const receivedAmount =
+      depositAmount - (depositAmount * transferFeeBps) / 10000;
+
+    const reserveAcc = await client.fetchProgramAccount(
+      transferFeeReservePda,
+      "reserve",
+    );
+
+    expect(reserveAcc.liquidity.availableAmount.toNumber()).toBe(
+      receivedAmount,
+    );
+
+    const receiptTokenAcc = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(
+        LendingClient.getReceiptMint(transferFeeReservePda),
+        depositor.publicKey,
+      ),
+    );
+
+    expect(Number(receiptTokenAcc.amount)).toBe(receivedAmount);
+  });
+
+  afterEach(async () => {
+    await resetAccounts([marketPda]);
+  });
+});
@@ -73,24 +73,35 @@ describe("depositReserveLiquidityAndObligationCollateral", () => {
     const maxBorrowRateBps = 8000; // 80%
     const flashLoanFeeBps = 500; // 5%
     const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
     const priceUpdateV2 = collateralMintPriceUpdateV2;
 
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint: collateralMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -102,10 +113,14 @@ describe("depositReserveLiquidityAndObligationCollateral", () => {
 
     // initializes an obligation
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: obligationAuthority.publicKey,
         reserve: reservePda,
+        obligation: LendingClient.getObligationPda(
+          obligationAuthority.publicKey,
+          marketPda,
+        ),
       })
       .signers([obligationAuthority])
       .rpc();
@@ -181,14 +196,14 @@ describe("depositReserveLiquidityAndObligationCollateral", () => {
 
     expect(Number(reserveAtaAcc.amount)).toBe(depositAmount);
 
-    const receiptMintAta = getAssociatedTokenAddressSync(
-      receiptMint,
-      obligationAuthority.publicKey,
+    const collateralEscrowAcc = await getAccount(
+      connection,
+      LendingClient.getCollateralEscrowPda(obligationPda, reservePda),
     );
 
-    const receiptMintAtaAcc = await getAccount(connection, receiptMintAta);
-
-    expect(receiptMintAtaAcc.amount).toBeGreaterThan(0);
+    expect(collateralEscrowAcc.mint).toStrictEqual(receiptMint);
+    expect(collateralEscrowAcc.owner).toStrictEqual(reservePda);
+    expect(Number(collateralEscrowAcc.amount)).toBe(depositAmount);
 
     const reserveAcc = await client.fetchProgramAccount(reservePda, "reserve");
 
@@ -11,6 +11,7 @@ import { BN, Program } from "@coral-xyz/anchor";
 import { Lending } from "../../target/types/lending";
 import { Surfpool } from "../surfpool";
 import {
+  createTransferInstruction,
   getAssociatedTokenAddressSync,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
@@ -96,24 +97,35 @@ describe("flashLoanReserveLiquidity", () => {
     const maxBorrowRateBps = 8000; // 80%
     const flashLoanFeeBps = 500; // 5%
     const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
     const priceUpdateV2 = collateralMintPriceUpdateV2;
 
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint: collateralMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -128,10 +140,14 @@ describe("flashLoanReserveLiquidity", () => {
 
     // initializes an obligation
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: borrowerObligationAuthority.publicKey,
         reserve: collateralReservePda,
+        obligation: LendingClient.getObligationPda(
+          borrowerObligationAuthority.publicKey,
+          marketPda,
+        ),
       })
       .signers([borrowerObligationAuthority])
       .rpc();
@@ -188,19 +204,26 @@ describe("flashLoanReserveLiquidity", () => {
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2: liquidityMintPriceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -216,7 +239,7 @@ describe("flashLoanReserveLiquidity", () => {
 
     // initializes an obligation
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: lenderObligationAuthority.publicKey,
         reserve: liquidityReservePda,
@@ -279,7 +302,7 @@ describe("flashLoanReserveLiquidity", () => {
       borrowerObligationAuthority.publicKey,
     );
 
-    // top up token account with some liquidity to cover platform fees
+    // top up token account with some liquidity to cover flash loan fees
     await Surfpool.setTokenAccount({
       mint: liquidityMint.toBase58(),
       owner: borrowerObligationAuthority.publicKey.toBase58(),
@@ -348,9 +371,7 @@ describe("flashLoanReserveLiquidity", () => {
             authorityTokenAccount: borrowerLiquidityAta,
             liquidityMint,
             liquidityTokenProgram: TOKEN_PROGRAM_ID,
-            marketAuthority: marketAuthority.publicKey,
             reserve: liquidityReservePda,
-            market: marketPda,
           })
           .instruction(),
       ],
@@ -363,11 +384,442 @@ describe("flashLoanReserveLiquidity", () => {
     );
 
     expect(postReserveAcc.lastUpdate.isStale).toBeTrue();
+    // flash loan fee is added to the reserve as supplier yield and platform fees
+    expect(
+      preReserveAcc.liquidity.availableAmount.lt(
+        postReserveAcc.liquidity.availableAmount,
+      ),
+    ).toBeTrue();
+    expect(
+      preReserveAcc.liquidity.accumulatedPlatformFees.lt(
+        postReserveAcc.liquidity.accumulatedPlatformFees,
+      ),
+    ).toBeTrue();
+    expect(
+      preReserveAcc.liquidity.borrowedAmount.eq(
+        postReserveAcc.liquidity.borrowedAmount,
+      ),
+    ).toBeTrue();
+  });
+
+  test("take flash loans from multiple reserves", async () => {
+    const liquidityBorrowAmount = liquidityDepositAmount / 4;
+    const collateralBorrowAmount = collateralDepositAmount / 4;
+
+    const borrowerLiquidityAta = getAssociatedTokenAddressSync(
+      liquidityMint,
+      borrowerObligationAuthority.publicKey,
+    );
+    const borrowerCollateralAta = getAssociatedTokenAddressSync(
+      collateralMint,
+      borrowerObligationAuthority.publicKey,
+    );
+
+    // top up token accounts with some liquidity to cover flash loan fees
+    await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: borrowerObligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: liquidityBorrowAmount / 2,
+      },
+    });
+    await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: borrowerObligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: collateralBorrowAmount / 2,
+      },
+    });
+
+    const preLiquidityReserveAcc = await client.fetchProgramAccount(
+      liquidityReservePda,
+      "reserve",
+    );
+    const preCollateralReserveAcc = await client.fetchProgramAccount(
+      collateralReservePda,
+      "reserve",
+    );
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: collateralMintPriceUpdateV2,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        // flash loans
+        await program.methods
+          .flashBorrowReserveLiquidity(new BN(liquidityBorrowAmount))
+          .accountsPartial({
+            authority: borrowerObligationAuthority.publicKey,
+            authorityTokenAccount: borrowerLiquidityAta,
+            liquidityMint,
+            liquidityTokenProgram: TOKEN_PROGRAM_ID,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .flashBorrowReserveLiquidity(new BN(collateralBorrowAmount))
+          .accountsPartial({
+            authority: borrowerObligationAuthority.publicKey,
+            authorityTokenAccount: borrowerCollateralAta,
+            liquidityMint: collateralMint,
+            liquidityTokenProgram: TOKEN_PROGRAM_ID,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .flashRepayReserveLiquidity(new BN(liquidityBorrowAmount), 2)
+          .accountsPartial({
+            authority: borrowerObligationAuthority.publicKey,
+            authorityTokenAccount: borrowerLiquidityAta,
+            liquidityMint,
+            liquidityTokenProgram: TOKEN_PROGRAM_ID,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .flashRepayReserveLiquidity(new BN(collateralBorrowAmount), 3)
+          .accountsPartial({
+            authority: borrowerObligationAuthority.publicKey,
+            authorityTokenAccount: borrowerCollateralAta,
+            liquidityMint: collateralMint,
+            liquidityTokenProgram: TOKEN_PROGRAM_ID,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+      ],
+      [borrowerObligationAuthority],
+    );
+
+    const postLiquidityReserveAcc = await client.fetchProgramAccount(
+      liquidityReservePda,
+      "reserve",
+    );
+    const postCollateralReserveAcc = await client.fetchProgramAccount(
+      collateralReservePda,
+      "reserve",
+    );
+
+    expect(
+      preLiquidityReserveAcc.liquidity.borrowedAmount.eq(
+        postLiquidityReserveAcc.liquidity.borrowedAmount,
+      ),
+    ).toBeTrue();
+    expect(
+      preCollateralReserveAcc.liquidity.borrowedAmount.eq(
+        postCollateralReserveAcc.liquidity.borrowedAmount,
+      ),
+    ).toBeTrue();
+  });
+
+  test("throws if flash borrows of the same reserve are nested", async () => {
+    const borrowAmount = liquidityDepositAmount / 4;
+
+    const borrowerLiquidityAta = getAssociatedTokenAddressSync(
+      liquidityMint,
+      borrowerObligationAuthority.publicKey,
+    );
+
+    await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: borrowerObligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: borrowAmount,
+      },
+    });
+
+    await expireBlockhash();
+
+    await expect(
+      buildAndSendv0Tx(
+        [
+          await program.methods
+            .refreshReserve()
+            .accounts({
+              priceUpdateV2: liquidityMintPriceUpdateV2,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .flashBorrowReserveLiquidity(new BN(borrowAmount))
+            .accountsPartial({
+              authority: borrowerObligationAuthority.publicKey,
+              authorityTokenAccount: borrowerLiquidityAta,
+              liquidityMint,
+              liquidityTokenProgram: TOKEN_PROGRAM_ID,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .flashBorrowReserveLiquidity(new BN(borrowAmount))
+            .accountsPartial({
+              authority: borrowerObligationAuthority.publicKey,
+              authorityTokenAccount: borrowerLiquidityAta,
+              liquidityMint,
+              liquidityTokenProgram: TOKEN_PROGRAM_ID,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .flashRepayReserveLiquidity(new BN(borrowAmount), 2)
+            .accountsPartial({
+              authority: borrowerObligationAuthority.publicKey,
+              authorityTokenAccount: borrowerLiquidityAta,
+              liquidityMint,
+              liquidityTokenProgram: TOKEN_PROGRAM_ID,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .flashRepayReserveLiquidity(new BN(borrowAmount), 1)
+            .accountsPartial({
+              authority: borrowerObligationAuthority.publicKey,
+              authorityTokenAccount: borrowerLiquidityAta,
+              liquidityMint,
+              liquidityTokenProgram: TOKEN_PROGRAM_ID,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+        ],
+        [borrowerObligationAuthority],
+      ),
+    ).rejects.toThrow("MultipleFlashBorrowsNotAllowed");
+  });
+
+  test("throws if flash loans are disabled", async () => {
+    const borrowAmount = liquidityDepositAmount / 4;
+
+    await program.methods
+      .updateReserve({
+        flashLoanFeeBps: null,
+        flashLoansEnabled: false,
+        insuranceFeeBps: null,
+        liquidationBonusBps: null,
+        liquidationCloseFactorBps: null,
+        liquidationInsuranceFeeBps: null,
+        liquidationThresholdBps: null,
+        loanToValueBps: null,
+        maxBorrowRateBps: null,
+        maxFlashLoanAmount: null,
+        minBorrowRateBps: null,
+        optimalBorrowRateBps: null,
+        optimalUtilizationRateBps: null,
+        platformFeeBps: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        reserve: liquidityReservePda,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const borrowerLiquidityAta = getAssociatedTokenAddressSync(
+      liquidityMint,
+      borrowerObligationAuthority.publicKey,
+    );
+
+    await expireBlockhash();
+
+    await expect(
+      buildAndSendv0Tx(
+        [
+          await program.methods
+            .refreshReserve()
+            .accounts({
+              priceUpdateV2: liquidityMintPriceUpdateV2,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .flashBorrowReserveLiquidity(new BN(borrowAmount))
+            .accountsPartial({
+              authority: borrowerObligationAuthority.publicKey,
+              authorityTokenAccount: borrowerLiquidityAta,
+              liquidityMint,
+              liquidityTokenProgram: TOKEN_PROGRAM_ID,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .flashRepayReserveLiquidity(new BN(borrowAmount), 1)
+            .accountsPartial({
+              authority: borrowerObligationAuthority.publicKey,
+              authorityTokenAccount: borrowerLiquidityAta,
+              liquidityMint,
+              liquidityTokenProgram: TOKEN_PROGRAM_ID,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+        ],
+        [borrowerObligationAuthority],
+      ),
+    ).rejects.toThrow("FlashLoansDisabled");
+  });
+
+  test("take a fee exempt flash loan", async () => {
+    const borrowAmount = liquidityDepositAmount / 4;
+
+    await program.methods
+      .addFlashLoanFeeExemption(
+        borrowerObligationAuthority.publicKey,
+        10000, // 100%
+      )
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const feeExemptionPda = LendingClient.getFlashLoanFeeExemptionPda(
+      marketPda,
+      borrowerObligationAuthority.publicKey,
+    );
+
+    const borrowerLiquidityAta = getAssociatedTokenAddressSync(
+      liquidityMint,
+      borrowerObligationAuthority.publicKey,
+    );
+
+    const preReserveAcc = await client.fetchProgramAccount(
+      liquidityReservePda,
+      "reserve",
+    );
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .flashBorrowReserveLiquidity(new BN(borrowAmount))
+          .accountsPartial({
+            authority: borrowerObligationAuthority.publicKey,
+            authorityTokenAccount: borrowerLiquidityAta,
+            liquidityMint,
+            liquidityTokenProgram: TOKEN_PROGRAM_ID,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .flashRepayReserveLiquidity(new BN(borrowAmount), 1)
+          .accountsPartial({
+            authority: borrowerObligationAuthority.publicKey,
+            authorityTokenAccount: borrowerLiquidityAta,
+            feeExemption: feeExemptionPda,
+            liquidityMint,
+            liquidityTokenProgram: TOKEN_PROGRAM_ID,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+      ],
+      [borrowerObligationAuthority],
+    );
+
+    const postReserveAcc = await client.fetchProgramAccount(
+      liquidityReservePda,
+      "reserve",
+    );
+
     expect(
       preReserveAcc.liquidity.availableAmount.eq(
         postReserveAcc.liquidity.availableAmount,
       ),
     ).toBeTrue();
+
+    await program.methods
+      .removeFlashLoanFeeExemption()
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        feeExemption: feeExemptionPda,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    expect(await connection.getAccountInfo(feeExemptionPda)).toBeNull();
+  });
+
+  test("take a flash loan through a receiver program", async () => {
+    const borrowAmount = liquidityDepositAmount / 4;
+    // 5% flash loan fee + 2.5% platform fee
+    const flashLoanFee = (borrowAmount * 750) / 10000;
+
+    const borrowerLiquidityAta = await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: borrowerObligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: flashLoanFee,
+      },
+    });
+    const liquidityReserveAta = getAssociatedTokenAddressSync(
+      liquidityMint,
+      liquidityReservePda,
+      !PublicKey.isOnCurve(liquidityReservePda),
+    );
+
+    // token program acts as the receiver, repaying the loan plus fee with a transfer
+    const repayIx = createTransferInstruction(
+      borrowerLiquidityAta,
+      liquidityReserveAta,
+      borrowerObligationAuthority.publicKey,
+      borrowAmount + flashLoanFee,
+    );
+
+    const preReserveAcc = await client.fetchProgramAccount(
+      liquidityReservePda,
+      "reserve",
+    );
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      [
+        await program.methods
+          .flashLoan(new BN(borrowAmount), repayIx.data)
+          .accountsPartial({
+            authority: borrowerObligationAuthority.publicKey,
+            liquidityMint,
+            liquidityTokenProgram: TOKEN_PROGRAM_ID,
+            receiverProgram: TOKEN_PROGRAM_ID,
+            receiverTokenAccount: borrowerLiquidityAta,
+            reserve: liquidityReservePda,
+          })
+          .remainingAccounts(repayIx.keys)
+          .instruction(),
+      ],
+      [borrowerObligationAuthority],
+    );
+
+    const postReserveAcc = await client.fetchProgramAccount(
+      liquidityReservePda,
+      "reserve",
+    );
+
+    expect(
+      preReserveAcc.liquidity.availableAmount.lt(
+        postReserveAcc.liquidity.availableAmount,
+      ),
+    ).toBeTrue();
     expect(
       preReserveAcc.liquidity.borrowedAmount.eq(
         postReserveAcc.liquidity.borrowedAmount,
@@ -375,6 +827,79 @@ describe("flashLoanReserveLiquidity", () => {
     ).toBeTrue();
   });
 
+  test("reject a fee exemption for the receiver program", async () => {
+    const borrowAmount = liquidityDepositAmount / 4;
+
+    const feeExemptionPda = LendingClient.getFlashLoanFeeExemptionPda(
+      marketPda,
+      TOKEN_PROGRAM_ID,
+    );
+
+    // exempting the token program must not let any signer routing a flash
+    // loan through it skip the fee
+    await program.methods
+      .addFlashLoanFeeExemption(TOKEN_PROGRAM_ID, 10000)
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const borrowerLiquidityAta = await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: borrowerObligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: 0,
+      },
+    });
+    const liquidityReserveAta = getAssociatedTokenAddressSync(
+      liquidityMint,
+      liquidityReservePda,
+      !PublicKey.isOnCurve(liquidityReservePda),
+    );
+
+    const repayIx = createTransferInstruction(
+      borrowerLiquidityAta,
+      liquidityReserveAta,
+      borrowerObligationAuthority.publicKey,
+      borrowAmount,
+    );
+
+    await expireBlockhash();
+
+    await expect(
+      buildAndSendv0Tx(
+        [
+          await program.methods
+            .flashLoan(new BN(borrowAmount), repayIx.data)
+            .accountsPartial({
+              authority: borrowerObligationAuthority.publicKey,
+              feeExemption: feeExemptionPda,
+              liquidityMint,
+              liquidityTokenProgram: TOKEN_PROGRAM_ID,
+              receiverProgram: TOKEN_PROGRAM_ID,
+              receiverTokenAccount: borrowerLiquidityAta,
+              reserve: liquidityReservePda,
+            })
+            .remainingAccounts(repayIx.keys)
+            .instruction(),
+        ],
+        [borrowerObligationAuthority],
+      ),
+    ).rejects.toThrow("InvalidFlashLoanFeeExemption");
+
+    await program.methods
+      .removeFlashLoanFeeExemption()
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        feeExemption: feeExemptionPda,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+  });
+
   afterEach(async () => {
     await resetAccounts([marketPda]);
   });
@@ -4,6 +4,8 @@ import { Connection, Keypair, PublicKey } from "@solana/web3.js";
 import { getSetup, resetAccounts } from "../setup";
 import { Program } from "@coral-xyz/anchor";
 import { Lending } from "../../target/types/lending";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { Surfpool } from "../surfpool";
 
 describe("initializeMarket", () => {
   let client: LendingClient;
@@ -42,6 +44,74 @@ describe("initializeMarket", () => {
 
     expect(marketAcc.authority.equals(marketAuthority.publicKey)).toBeTrue();
     expect(marketAcc.name).toBe(name);
+    expect(marketAcc.receiptTokenProgram.equals(TOKEN_PROGRAM_ID)).toBeTrue();
+  });
+
+  test("migrate a market created with the original layout", async () => {
+    const name = "Test Market";
+
+    await program.methods
+      .initializeMarket(name)
+      .accounts({
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    marketPda = LendingClient.getMarketPda(name);
+    const initialMarketAcc = await client.fetchProgramAccount(
+      marketPda,
+      "market",
+    );
+
+    // keeps only the authority, bump and name the original layout had
+    const marketInfo = (await connection.getAccountInfo(marketPda))!;
+    const data = marketInfo.data.subarray(0, 8 + 32 + 1 + 4 + name.length);
+
+    await Surfpool.setAccount({
+      publicKey: marketPda.toBase58(),
+      data: data.toString("hex"),
+      lamports: await connection.getMinimumBalanceForRentExemption(
+        data.length,
+      ),
+      owner: program.programId.toBase58(),
+    });
+
+    await expect(
+      client.fetchProgramAccount(marketPda, "market"),
+    ).rejects.toThrow();
+
+    await program.methods
+      .reallocMarket()
+      .accounts({
+        payer: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const marketAcc = await client.fetchProgramAccount(marketPda, "market");
+
+    // appended fields take the defaults of a newly initialized market
+    expect(marketAcc.authority.equals(marketAuthority.publicKey)).toBeTrue();
+    expect(marketAcc.name).toBe(name);
+    expect(marketAcc.dustThresholdValue.toNumber()).toBe(0);
+    expect(marketAcc.receiptTokenProgram.equals(TOKEN_PROGRAM_ID)).toBeTrue();
+    expect(
+      marketAcc.listingApprover.equals(marketAuthority.publicKey),
+    ).toBeTrue();
+    expect(
+      marketAcc.complianceAuthority.equals(marketAuthority.publicKey),
+    ).toBeTrue();
+    expect(marketAcc.maxObligationDeposits).toBe(
+      initialMarketAcc.maxObligationDeposits,
+    );
+    expect(marketAcc.maxObligationBorrows).toBe(
+      initialMarketAcc.maxObligationBorrows,
+    );
+    expect((await connection.getAccountInfo(marketPda))!.data.length).toBe(
+      marketInfo.data.length,
+    );
   });
 
   afterEach(async () => {
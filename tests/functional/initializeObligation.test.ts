@@ -1,8 +1,13 @@
 import { afterEach, beforeEach, describe, expect, test } from "bun:test";
 import { LendingClient } from "../LendingClient";
 import { Connection, Keypair, PublicKey } from "@solana/web3.js";
-import { getSetup, resetAccounts } from "../setup";
-import { Program } from "@coral-xyz/anchor";
+import {
+  buildAndSendv0Tx,
+  expireBlockhash,
+  getSetup,
+  resetAccounts,
+} from "../setup";
+import { BN, Program } from "@coral-xyz/anchor";
 import { Lending } from "../../target/types/lending";
 import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { WBTC_MINT_DECIMALS, WBTC_USD_PRICE_UPDATE_V2 } from "../constants";
@@ -66,24 +71,35 @@ describe("initializeObligation", () => {
     const maxBorrowRateBps = 8000; // 80%
     const flashLoanFeeBps = 500; // 5%
     const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
     const priceUpdateV2 = collateralMintPriceUpdateV2;
 
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint: collateralMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -96,10 +112,14 @@ describe("initializeObligation", () => {
 
   test("initialize an obligation", async () => {
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: obligationAuthority.publicKey,
         reserve: reservePda,
+        obligation: LendingClient.getObligationPda(
+          obligationAuthority.publicKey,
+          marketPda,
+        ),
       })
       .signers([obligationAuthority])
       .rpc();
@@ -119,6 +139,384 @@ describe("initializeObligation", () => {
     expect(obligationAcc.market.equals(marketPda)).toBeTrue();
   });
 
+  test("initialize multiple obligations for an authority", async () => {
+    for (const index of [0, 1]) {
+      await program.methods
+        .initializeObligation(index)
+        .accountsPartial({
+          authority: obligationAuthority.publicKey,
+          reserve: reservePda,
+          obligation: LendingClient.getObligationPda(
+            obligationAuthority.publicKey,
+            marketPda,
+            index,
+          ),
+        })
+        .signers([obligationAuthority])
+        .rpc();
+    }
+
+    const obligationAcc = await client.fetchProgramAccount(
+      LendingClient.getObligationPda(
+        obligationAuthority.publicKey,
+        marketPda,
+        1,
+      ),
+      "obligation",
+    );
+
+    expect(
+      obligationAcc.authority.equals(obligationAuthority.publicKey),
+    ).toBeTrue();
+    expect(obligationAcc.index).toBe(1);
+
+    await expect(
+      program.methods
+        .initializeObligation(2)
+        .accountsPartial({
+          authority: obligationAuthority.publicKey,
+          reserve: reservePda,
+          obligation: LendingClient.getObligationPda(
+            obligationAuthority.publicKey,
+            marketPda,
+            1,
+          ),
+        })
+        .signers([obligationAuthority])
+        .rpc(),
+    ).rejects.toThrow("ConstraintSeeds");
+  });
+
+  test("realloc an obligation after the market raises its limits", async () => {
+    const obligationPda = LendingClient.getObligationPda(
+      obligationAuthority.publicKey,
+      marketPda,
+    );
+
+    await program.methods
+      .initializeObligation(0)
+      .accountsPartial({
+        authority: obligationAuthority.publicKey,
+        reserve: reservePda,
+        obligation: obligationPda,
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    const initialSize = (await connection.getAccountInfo(obligationPda))!.data
+      .length;
+
+    await program.methods
+      .updateMarket({
+        allowedMintExtensions: null,
+        listingApprover: null,
+        permissionedListing: null,
+        complianceAuthority: null,
+        permissionedObligations: null,
+        maxObligationDeposits: 8,
+        maxObligationBorrows: 8,
+        dustThresholdValue: null,
+        receiptTokenProgram: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    await program.methods
+      .reallocObligation()
+      .accountsPartial({
+        payer: obligationAuthority.publicKey,
+        market: marketPda,
+        obligation: obligationPda,
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    const obligationAcc = await client.fetchProgramAccount(
+      obligationPda,
+      "obligation",
+    );
+
+    expect(obligationAcc.maxDeposits).toBe(8);
+    expect(obligationAcc.maxBorrows).toBe(8);
+    expect(
+      (await connection.getAccountInfo(obligationPda))!.data.length,
+    ).toBeGreaterThan(initialSize);
+  });
+
+  test("reject migrating an original obligation holding deposits", async () => {
+    const obligationPda = LendingClient.getObligationPda(
+      obligationAuthority.publicKey,
+      marketPda,
+    );
+    const depositReserves = Array.from({ length: 5 }, () =>
+      Keypair.generate().publicKey,
+    );
+    const borrowReserves = Array.from({ length: 5 }, () =>
+      Keypair.generate().publicKey,
+    );
+
+    // writes an obligation with full deposits and borrows, leaving no spare
+    // space for the fields appended after the original layout
+    const u32 = (value: number) => {
+      const buffer = Buffer.alloc(4);
+      buffer.writeUInt32LE(value);
+      return buffer;
+    };
+    const u64 = (value: bigint) => {
+      const buffer = Buffer.alloc(8);
+      buffer.writeBigUInt64LE(value);
+      return buffer;
+    };
+    const i80f48 = (value: bigint) => {
+      const buffer = Buffer.alloc(16);
+      buffer.writeBigUInt64LE(value << 48n);
+      return buffer;
+    };
+    const data = Buffer.concat([
+      Buffer.from(
+        program.idl.accounts.find((account) => account.name === "obligation")!
+          .discriminator,
+      ),
+      Buffer.from([1]),
+      u64(0n),
+      marketPda.toBuffer(),
+      obligationAuthority.publicKey.toBuffer(),
+      u32(depositReserves.length),
+      ...depositReserves.map((reserve) =>
+        Buffer.concat([reserve.toBuffer(), u64(1_000n), i80f48(0n)]),
+      ),
+      u32(borrowReserves.length),
+      ...borrowReserves.map((reserve) =>
+        Buffer.concat([reserve.toBuffer(), u64(100n), i80f48(1n), i80f48(0n)]),
+      ),
+      i80f48(0n),
+      i80f48(0n),
+      i80f48(0n),
+      i80f48(0n),
+      Buffer.from([255]),
+    ]);
+
+    await Surfpool.setAccount({
+      publicKey: obligationPda.toBase58(),
+      data: data.toString("hex"),
+      lamports: await connection.getMinimumBalanceForRentExemption(
+        data.length,
+      ),
+      owner: program.programId.toBase58(),
+    });
+
+    await expect(
+      client.fetchProgramAccount(obligationPda, "obligation"),
+    ).rejects.toThrow();
+
+    await expect(
+      program.methods
+        .reallocObligation()
+        .accountsPartial({
+          payer: obligationAuthority.publicKey,
+          market: marketPda,
+          obligation: obligationPda,
+        })
+        .signers([obligationAuthority])
+        .rpc(),
+    ).rejects.toThrow("OriginalObligationDeposits");
+  });
+
+  test("require reallocating an original obligation before use", async () => {
+    const obligationPda = LendingClient.getObligationPda(
+      obligationAuthority.publicKey,
+      marketPda,
+    );
+    const depositAmount = Math.pow(10, WBTC_MINT_DECIMALS);
+
+    // writes an empty obligation with the original 794 byte size, so the
+    // appended fields read as zero from its spare space
+    const data = Buffer.alloc(794);
+    Buffer.concat([
+      Buffer.from(
+        program.idl.accounts.find((account) => account.name === "obligation")!
+          .discriminator,
+      ),
+      Buffer.from([1]),
+      Buffer.alloc(8),
+      marketPda.toBuffer(),
+      obligationAuthority.publicKey.toBuffer(),
+      Buffer.alloc(4),
+      Buffer.alloc(4),
+      Buffer.alloc(4 * 16),
+      Buffer.from([255]),
+    ]).copy(data);
+
+    await Surfpool.setAccount({
+      publicKey: obligationPda.toBase58(),
+      data: data.toString("hex"),
+      lamports: await connection.getMinimumBalanceForRentExemption(
+        data.length,
+      ),
+      owner: program.programId.toBase58(),
+    });
+
+    await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: obligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: depositAmount,
+      },
+    });
+
+    const depositInstructions = async () => [
+      await program.methods
+        .refreshReserve()
+        .accounts({
+          priceUpdateV2: collateralMintPriceUpdateV2,
+          reserve: reservePda,
+        })
+        .instruction(),
+      await program.methods
+        .depositReserveLiquidityAndObligationCollateral(new BN(depositAmount))
+        .accountsPartial({
+          authority: obligationAuthority.publicKey,
+          collateralMint,
+          collateralTokenProgram: TOKEN_PROGRAM_ID,
+          obligation: obligationPda,
+          reserve: reservePda,
+        })
+        .instruction(),
+    ];
+
+    expect(await client.requiresRealloc(obligationPda)).toBeTrue();
+
+    await expect(
+      program.methods
+        .refreshObligation()
+        .accounts({
+          obligation: obligationPda,
+        })
+        .rpc(),
+    ).rejects.toThrow("ObligationReallocRequired");
+    await expect(
+      buildAndSendv0Tx(await depositInstructions(), [obligationAuthority]),
+    ).rejects.toThrow("ObligationReallocRequired");
+
+    await program.methods
+      .reallocObligation()
+      .accountsPartial({
+        payer: obligationAuthority.publicKey,
+        market: marketPda,
+        obligation: obligationPda,
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    expect(await client.requiresRealloc(obligationPda)).toBeFalse();
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(await depositInstructions(), [obligationAuthority]);
+
+    const obligationAcc = await client.fetchProgramAccount(
+      obligationPda,
+      "obligation",
+    );
+
+    expect(obligationAcc.deposits).toHaveLength(1);
+    expect(obligationAcc.deposits[0].reserve.equals(reservePda)).toBeTrue();
+  });
+
+  test("require an allowlist entry in a permissioned market", async () => {
+    await program.methods
+      .updateMarket({
+        allowedMintExtensions: null,
+        listingApprover: null,
+        permissionedListing: null,
+        complianceAuthority: null,
+        permissionedObligations: true,
+        maxObligationDeposits: null,
+        maxObligationBorrows: null,
+        dustThresholdValue: null,
+        receiptTokenProgram: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    await expect(
+      program.methods
+        .initializeObligation(0)
+        .accountsPartial({
+          authority: obligationAuthority.publicKey,
+          reserve: reservePda,
+          allowlistEntry: null,
+          obligation: LendingClient.getObligationPda(
+            obligationAuthority.publicKey,
+            marketPda,
+          ),
+        })
+        .signers([obligationAuthority])
+        .rpc(),
+    ).rejects.toThrow("WalletNotAllowlisted");
+
+    await program.methods
+      .addAllowlistEntry(obligationAuthority.publicKey)
+      .accountsPartial({
+        complianceAuthority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const allowlistEntryPda = LendingClient.getAllowlistEntryPda(
+      marketPda,
+      obligationAuthority.publicKey,
+    );
+
+    await program.methods
+      .initializeObligation(0)
+      .accountsPartial({
+        authority: obligationAuthority.publicKey,
+        reserve: reservePda,
+        allowlistEntry: allowlistEntryPda,
+        obligation: LendingClient.getObligationPda(
+          obligationAuthority.publicKey,
+          marketPda,
+        ),
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    const obligationPda = LendingClient.getObligationPda(
+      obligationAuthority.publicKey,
+      marketPda,
+    );
+    const obligationAcc = await client.fetchProgramAccount(
+      obligationPda,
+      "obligation",
+    );
+
+    expect(
+      obligationAcc.authority.equals(obligationAuthority.publicKey),
+    ).toBeTrue();
+
+    await program.methods
+      .removeAllowlistEntry()
+      .accountsPartial({
+        complianceAuthority: marketAuthority.publicKey,
+        market: marketPda,
+        allowlistEntry: allowlistEntryPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    expect(await connection.getAccountInfo(allowlistEntryPda)).toBeNull();
+  });
+
   afterEach(async () => {
     await resetAccounts([marketPda]);
   });
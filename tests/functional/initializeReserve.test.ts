@@ -1,15 +1,36 @@
 import { afterEach, beforeEach, describe, expect, test } from "bun:test";
 import { LendingClient } from "../LendingClient";
-import { Connection, Keypair, PublicKey } from "@solana/web3.js";
-import { getSetup, resetAccounts } from "../setup";
-import { Program } from "@coral-xyz/anchor";
+import {
+  Connection,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+} from "@solana/web3.js";
+import {
+  buildAndSendv0Tx,
+  getSetup,
+  resetAccounts,
+} from "../setup";
+import { BN, Program } from "@coral-xyz/anchor";
 import { Lending } from "../../target/types/lending";
 import {
+  createInitializeMint2Instruction,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeTransferHookInstruction,
+  ExtensionType,
   getAccount,
   getAssociatedTokenAddressSync,
+  getMint,
+  getMintLen,
+  getTokenMetadata,
+  TOKEN_2022_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
-import { WBTC_USD_PRICE_UPDATE_V2, WBTC_MINT_DECIMALS } from "../constants";
+import {
+  MINT_EXTENSION_TRANSFER_FEE,
+  WBTC_USD_PRICE_UPDATE_V2,
+  WBTC_MINT_DECIMALS,
+} from "../constants";
 import { Surfpool } from "../surfpool";
 
 describe("initializeReserve", () => {
@@ -18,6 +39,7 @@ describe("initializeReserve", () => {
   let connection: Connection;
 
   let marketAuthority: Keypair;
+  let listingApprover: Keypair;
   let marketPda: PublicKey;
 
   let collateralMint: PublicKey;
@@ -25,11 +47,15 @@ describe("initializeReserve", () => {
 
   beforeEach(async () => {
     marketAuthority = Keypair.generate();
+    listingApprover = Keypair.generate();
 
     ({ client } = await getSetup([
       {
         publicKey: marketAuthority.publicKey,
       },
+      {
+        publicKey: listingApprover.publicKey,
+      },
     ]));
 
     program = client.program;
@@ -64,24 +90,35 @@ describe("initializeReserve", () => {
     const maxBorrowRateBps = 8000; // 80%
     const flashLoanFeeBps = 500; // 5%
     const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
     const priceUpdateV2 = collateralMintPriceUpdateV2;
 
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint: collateralMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -95,6 +132,10 @@ describe("initializeReserve", () => {
     expect(reserveAcc.market.equals(marketPda)).toBeTrue();
     expect(reserveAcc.config.fees.flashLoanFeeBps).toBe(flashLoanFeeBps);
     expect(reserveAcc.config.fees.platformFeeBps).toBe(platformFeeBps);
+    expect(reserveAcc.config.fees.insuranceFeeBps).toBe(insuranceFeeBps);
+    expect(reserveAcc.config.fees.liquidationInsuranceFeeBps).toBe(
+      liquidationInsuranceFeeBps,
+    );
     expect(reserveAcc.config.liquidationBonusBps).toBe(liquidationBonusBps);
     expect(reserveAcc.config.liquidationCloseFactorBps).toBe(
       liquidationCloseFactorBps,
@@ -109,6 +150,10 @@ describe("initializeReserve", () => {
     expect(reserveAcc.config.minBorrowRateBps).toBe(minBorrowRateBps);
     expect(reserveAcc.config.optimalBorrowRateBps).toBe(optimalBorrowRateBps);
     expect(reserveAcc.config.maxBorrowRateBps).toBe(maxBorrowRateBps);
+    expect(reserveAcc.config.flashLoansEnabled).toBe(flashLoansEnabled);
+    expect(
+      reserveAcc.config.maxFlashLoanAmount.eq(maxFlashLoanAmount),
+    ).toBeTrue();
     expect(reserveAcc.liquidity.mint.equals(collateralMint)).toBeTrue();
     expect(reserveAcc.liquidity.priceUpdateV2.equals(priceUpdateV2)).toBeTrue();
 
@@ -121,6 +166,744 @@ describe("initializeReserve", () => {
     const reserveAtaAcc = await getAccount(connection, reserveAta);
 
     expect(reserveAtaAcc).not.toBeNull();
+
+    const insuranceVaultAcc = await getAccount(
+      connection,
+      LendingClient.getInsuranceVaultPda(reservePda),
+    );
+
+    expect(insuranceVaultAcc.owner.equals(reservePda)).toBeTrue();
+
+    const receiptMintAcc = await getMint(
+      connection,
+      LendingClient.getReceiptMint(reservePda),
+    );
+
+    expect(receiptMintAcc.decimals).toBe(WBTC_MINT_DECIMALS);
+  });
+
+  test("initialize a reserve with a Token-2022 receipt mint", async () => {
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 8000; // 80%
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+    const priceUpdateV2 = collateralMintPriceUpdateV2;
+
+    await program.methods
+      .updateMarket({
+        allowedMintExtensions: null,
+        listingApprover: null,
+        permissionedListing: null,
+        complianceAuthority: null,
+        permissionedObligations: null,
+        maxObligationDeposits: null,
+        maxObligationBorrows: null,
+        dustThresholdValue: null,
+        receiptTokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: null,
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint: collateralMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_2022_PROGRAM_ID,
+        priceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const reservePda = LendingClient.getReservePda(marketPda, collateralMint);
+    const receiptMint = LendingClient.getReceiptMint(reservePda);
+
+    const receiptMintAcc = await getMint(
+      connection,
+      receiptMint,
+      "confirmed",
+      TOKEN_2022_PROGRAM_ID,
+    );
+
+    expect(receiptMintAcc.decimals).toBe(WBTC_MINT_DECIMALS);
+    expect(receiptMintAcc.mintAuthority?.equals(reservePda)).toBeTrue();
+
+    const receiptMetadata = await getTokenMetadata(connection, receiptMint);
+    const liquiditySymbol = collateralMint.toBase58().slice(0, 4);
+
+    expect(receiptMetadata?.name).toBe(`Test Market lend-${liquiditySymbol}`);
+    expect(receiptMetadata?.symbol).toBe(`l${liquiditySymbol}`);
+    expect(receiptMetadata?.updateAuthority?.equals(reservePda)).toBeTrue();
+  });
+
+  test("reject a Token-2022 receipt mint without a liquidity symbol", async () => {
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 8000; // 80%
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+    const priceUpdateV2 = collateralMintPriceUpdateV2;
+
+    await program.methods
+      .updateMarket({
+        allowedMintExtensions: null,
+        listingApprover: null,
+        permissionedListing: null,
+        complianceAuthority: null,
+        permissionedObligations: null,
+        maxObligationDeposits: null,
+        maxObligationBorrows: null,
+        dustThresholdValue: null,
+        receiptTokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    await expect(
+      program.methods
+        .initializeReserve({
+          flashLoanFeeBps,
+          flashLoansEnabled,
+          insuranceFeeBps,
+          liquidationBonusBps,
+          liquidationCloseFactorBps,
+          liquidationInsuranceFeeBps,
+          liquidationThresholdBps,
+          liquiditySymbol: null,
+          loanToValueBps,
+          maxBorrowRateBps,
+          maxFlashLoanAmount,
+          minBorrowRateBps,
+          optimalBorrowRateBps,
+          optimalUtilizationRateBps,
+          platformFeeBps,
+        })
+        .accountsPartial({
+          listingApproval: null,
+          liquidityMint: collateralMint,
+          liquidityTokenProgram: TOKEN_PROGRAM_ID,
+          receiptTokenProgram: TOKEN_2022_PROGRAM_ID,
+          priceUpdateV2,
+          market: marketPda,
+          authority: marketAuthority.publicKey,
+        })
+        .signers([marketAuthority])
+        .rpc(),
+    ).rejects.toThrow("MissingLiquiditySymbol");
+  });
+
+  test("initialize a reserve whose receipt mint address was pre-funded", async () => {
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 8000; // 80%
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+    const priceUpdateV2 = collateralMintPriceUpdateV2;
+
+    await program.methods
+      .updateMarket({
+        allowedMintExtensions: null,
+        listingApprover: null,
+        permissionedListing: null,
+        complianceAuthority: null,
+        permissionedObligations: null,
+        maxObligationDeposits: null,
+        maxObligationBorrows: null,
+        dustThresholdValue: null,
+        receiptTokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const reservePda = LendingClient.getReservePda(marketPda, collateralMint);
+    const receiptMint = LendingClient.getReceiptMint(reservePda);
+
+    // front-runs the reserve by funding its receipt mint address
+    await buildAndSendv0Tx(
+      [
+        SystemProgram.transfer({
+          fromPubkey: marketAuthority.publicKey,
+          toPubkey: receiptMint,
+          lamports: 1_000_000,
+        }),
+      ],
+      [marketAuthority],
+    );
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: "WBTC",
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint: collateralMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_2022_PROGRAM_ID,
+        priceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const receiptMintAcc = await getMint(
+      connection,
+      receiptMint,
+      "confirmed",
+      TOKEN_2022_PROGRAM_ID,
+    );
+
+    expect(receiptMintAcc.decimals).toBe(WBTC_MINT_DECIMALS);
+    expect(receiptMintAcc.mintAuthority?.equals(reservePda)).toBeTrue();
+
+    const receiptMetadata = await getTokenMetadata(connection, receiptMint);
+
+    expect(receiptMetadata?.name).toBe("Test Market lend-WBTC");
+    expect(receiptMetadata?.symbol).toBe("lWBTC");
+    expect(receiptMetadata?.updateAuthority?.equals(reservePda)).toBeTrue();
+  });
+
+  test("reject a liquidity mint with a disallowed extension", async () => {
+    const transferFeeMint = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+
+    await buildAndSendv0Tx(
+      [
+        SystemProgram.createAccount({
+          fromPubkey: marketAuthority.publicKey,
+          newAccountPubkey: transferFeeMint.publicKey,
+          space: mintLen,
+          lamports: await connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          transferFeeMint.publicKey,
+          marketAuthority.publicKey,
+          marketAuthority.publicKey,
+          100, // 1%
+          BigInt(Number.MAX_SAFE_INTEGER),
+          TOKEN_2022_PROGRAM_ID,
+        ),
+        createInitializeMint2Instruction(
+          transferFeeMint.publicKey,
+          WBTC_MINT_DECIMALS,
+          marketAuthority.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID,
+        ),
+      ],
+      [marketAuthority, transferFeeMint],
+    );
+
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 8000; // 80%
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+    const priceUpdateV2 = collateralMintPriceUpdateV2;
+
+    await expect(
+      program.methods
+        .initializeReserve({
+          flashLoanFeeBps,
+          flashLoansEnabled,
+          insuranceFeeBps,
+          liquidationBonusBps,
+          liquidationCloseFactorBps,
+          liquidationInsuranceFeeBps,
+          liquidationThresholdBps,
+          liquiditySymbol: null,
+          loanToValueBps,
+          maxBorrowRateBps,
+          maxFlashLoanAmount,
+          minBorrowRateBps,
+          optimalBorrowRateBps,
+          optimalUtilizationRateBps,
+          platformFeeBps,
+        })
+        .accountsPartial({
+          listingApproval: null,
+          liquidityMint: transferFeeMint.publicKey,
+          liquidityTokenProgram: TOKEN_2022_PROGRAM_ID,
+          receiptTokenProgram: TOKEN_PROGRAM_ID,
+          priceUpdateV2,
+          market: marketPda,
+          authority: marketAuthority.publicKey,
+        })
+        .signers([marketAuthority])
+        .rpc(),
+    ).rejects.toThrow("MintExtensionNotAllowed");
+
+    await program.methods
+      .updateMarket({
+        allowedMintExtensions: MINT_EXTENSION_TRANSFER_FEE,
+        listingApprover: null,
+        permissionedListing: null,
+        complianceAuthority: null,
+        permissionedObligations: null,
+        maxObligationDeposits: null,
+        maxObligationBorrows: null,
+        dustThresholdValue: null,
+        receiptTokenProgram: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: null,
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint: transferFeeMint.publicKey,
+        liquidityTokenProgram: TOKEN_2022_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const reservePda = LendingClient.getReservePda(
+      marketPda,
+      transferFeeMint.publicKey,
+    );
+    const reserveAcc = await client.fetchProgramAccount(reservePda, "reserve");
+
+    expect(
+      reserveAcc.liquidity.mint.equals(transferFeeMint.publicKey),
+    ).toBeTrue();
+  });
+
+  test("reject a liquidity mint with a transfer hook", async () => {
+    const transferHookMint = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferHook]);
+
+    await buildAndSendv0Tx(
+      [
+        SystemProgram.createAccount({
+          fromPubkey: marketAuthority.publicKey,
+          newAccountPubkey: transferHookMint.publicKey,
+          space: mintLen,
+          lamports: await connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferHookInstruction(
+          transferHookMint.publicKey,
+          marketAuthority.publicKey,
+          SystemProgram.programId,
+          TOKEN_2022_PROGRAM_ID,
+        ),
+        createInitializeMint2Instruction(
+          transferHookMint.publicKey,
+          WBTC_MINT_DECIMALS,
+          marketAuthority.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID,
+        ),
+      ],
+      [marketAuthority, transferHookMint],
+    );
+
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 8000; // 80%
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+    const priceUpdateV2 = collateralMintPriceUpdateV2;
+
+    await program.methods
+      .updateMarket({
+        allowedMintExtensions: 0xffff,
+        listingApprover: null,
+        permissionedListing: null,
+        complianceAuthority: null,
+        permissionedObligations: null,
+        maxObligationDeposits: null,
+        maxObligationBorrows: null,
+        dustThresholdValue: null,
+        receiptTokenProgram: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    await expect(
+      program.methods
+        .initializeReserve({
+          flashLoanFeeBps,
+          flashLoansEnabled,
+          insuranceFeeBps,
+          liquidationBonusBps,
+          liquidationCloseFactorBps,
+          liquidationInsuranceFeeBps,
+          liquidationThresholdBps,
+          liquiditySymbol: null,
+          loanToValueBps,
+          maxBorrowRateBps,
+          maxFlashLoanAmount,
+          minBorrowRateBps,
+          optimalBorrowRateBps,
+          optimalUtilizationRateBps,
+          platformFeeBps,
+        })
+        .accountsPartial({
+          listingApproval: null,
+          liquidityMint: transferHookMint.publicKey,
+          liquidityTokenProgram: TOKEN_2022_PROGRAM_ID,
+          receiptTokenProgram: TOKEN_PROGRAM_ID,
+          priceUpdateV2,
+          market: marketPda,
+          authority: marketAuthority.publicKey,
+        })
+        .signers([marketAuthority])
+        .rpc(),
+    ).rejects.toThrow("TransferHookNotSupported");
+  });
+
+  test("require a listing approval in a permissioned market", async () => {
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 8000; // 80%
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+    const priceUpdateV2 = collateralMintPriceUpdateV2;
+
+    await program.methods
+      .updateMarket({
+        allowedMintExtensions: null,
+        listingApprover: listingApprover.publicKey,
+        permissionedListing: true,
+        complianceAuthority: null,
+        permissionedObligations: null,
+        maxObligationDeposits: null,
+        maxObligationBorrows: null,
+        dustThresholdValue: null,
+        receiptTokenProgram: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const initializeReserveArgs = {
+      flashLoanFeeBps,
+      flashLoansEnabled,
+      insuranceFeeBps,
+      liquidationBonusBps,
+      liquidationCloseFactorBps,
+      liquidationInsuranceFeeBps,
+      liquidationThresholdBps,
+      liquiditySymbol: null,
+      loanToValueBps,
+      maxBorrowRateBps,
+      maxFlashLoanAmount,
+      minBorrowRateBps,
+      optimalBorrowRateBps,
+      optimalUtilizationRateBps,
+      platformFeeBps,
+    };
+
+    await expect(
+      program.methods
+        .initializeReserve(initializeReserveArgs)
+        .accountsPartial({
+          listingApproval: null,
+          liquidityMint: collateralMint,
+          liquidityTokenProgram: TOKEN_PROGRAM_ID,
+          receiptTokenProgram: TOKEN_PROGRAM_ID,
+          priceUpdateV2,
+          market: marketPda,
+          authority: marketAuthority.publicKey,
+        })
+        .signers([marketAuthority])
+        .rpc(),
+    ).rejects.toThrow("ListingNotApproved");
+
+    await expect(
+      program.methods
+        .approveReserveListing()
+        .accountsPartial({
+          approver: marketAuthority.publicKey,
+          market: marketPda,
+          mint: collateralMint,
+          priceUpdateV2,
+        })
+        .signers([marketAuthority])
+        .rpc(),
+    ).rejects.toThrow("InvalidListingApprover");
+
+    await program.methods
+      .approveReserveListing()
+      .accountsPartial({
+        approver: listingApprover.publicKey,
+        market: marketPda,
+        mint: collateralMint,
+        priceUpdateV2,
+      })
+      .signers([listingApprover])
+      .rpc();
+
+    const listingApprovalPda = LendingClient.getListingApprovalPda(
+      marketPda,
+      collateralMint,
+    );
+    const listingApprovalAcc = await client.fetchProgramAccount(
+      listingApprovalPda,
+      "listingApproval",
+    );
+
+    expect(listingApprovalAcc.mint.equals(collateralMint)).toBeTrue();
+    expect(listingApprovalAcc.priceUpdateV2.equals(priceUpdateV2)).toBeTrue();
+
+    await program.methods
+      .initializeReserve(initializeReserveArgs)
+      .accountsPartial({
+        listingApproval: listingApprovalPda,
+        liquidityMint: collateralMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const reservePda = LendingClient.getReservePda(marketPda, collateralMint);
+    const reserveAcc = await client.fetchProgramAccount(reservePda, "reserve");
+
+    expect(reserveAcc.liquidity.mint.equals(collateralMint)).toBeTrue();
+
+    await program.methods
+      .revokeReserveListing()
+      .accountsPartial({
+        approver: listingApprover.publicKey,
+        market: marketPda,
+        listingApproval: listingApprovalPda,
+      })
+      .signers([listingApprover])
+      .rpc();
+
+    const listingApprovalInfo =
+      await connection.getAccountInfo(listingApprovalPda);
+
+    expect(listingApprovalInfo).toBeNull();
+  });
+
+  test("migrate a reserve created with the original layout", async () => {
+    const liquidationBonusBps = 200; // 2%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled: false,
+        insuranceFeeBps: 1000,
+        liquidationBonusBps,
+        liquidationCloseFactorBps: 1000,
+        liquidationInsuranceFeeBps: 1000,
+        liquidationThresholdBps: 8500,
+        liquiditySymbol: null,
+        loanToValueBps: 8000,
+        maxBorrowRateBps: 8000,
+        maxFlashLoanAmount: new BN(0),
+        minBorrowRateBps: 200,
+        optimalBorrowRateBps: 2000,
+        optimalUtilizationRateBps: 7500,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint: collateralMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2: collateralMintPriceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const reservePda = LendingClient.getReservePda(marketPda, collateralMint);
+    const reserveInfo = (await connection.getAccountInfo(reservePda))!;
+
+    // drops the insurance amounts, flash loan limits, insurance fees,
+    // pending insurance withdrawal and insurance vault bump added since
+    const data = Buffer.concat([
+      reserveInfo.data.subarray(0, 153),
+      reserveInfo.data.subarray(169, 201),
+      reserveInfo.data.subarray(210, 214),
+      reserveInfo.data.subarray(234, 237),
+    ]);
+
+    await Surfpool.setAccount({
+      publicKey: reservePda.toBase58(),
+      data: data.toString("hex"),
+      lamports: await connection.getMinimumBalanceForRentExemption(
+        data.length,
+      ),
+      owner: program.programId.toBase58(),
+    });
+
+    await expect(
+      client.fetchProgramAccount(reservePda, "reserve"),
+    ).rejects.toThrow();
+
+    await program.methods
+      .reallocReserve()
+      .accountsPartial({
+        payer: marketAuthority.publicKey,
+        reserve: reservePda,
+        liquidityMint: collateralMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const reserveAcc = await client.fetchProgramAccount(reservePda, "reserve");
+
+    expect(reserveAcc.market.equals(marketPda)).toBeTrue();
+    expect(reserveAcc.liquidity.mint.equals(collateralMint)).toBeTrue();
+    expect(reserveAcc.liquidity.insuranceAmount.toNumber()).toBe(0);
+    expect(reserveAcc.config.liquidationBonusBps).toBe(liquidationBonusBps);
+    expect(reserveAcc.config.fees.flashLoanFeeBps).toBe(flashLoanFeeBps);
+    expect(reserveAcc.config.fees.platformFeeBps).toBe(platformFeeBps);
+    expect(reserveAcc.config.fees.insuranceFeeBps).toBe(0);
+    expect(reserveAcc.config.fees.liquidationInsuranceFeeBps).toBe(0);
+    expect(reserveAcc.config.flashLoansEnabled).toBeTrue();
+    expect(
+      reserveAcc.config.maxFlashLoanAmount.eq(new BN("18446744073709551615")),
+    ).toBeTrue();
+    expect(reserveAcc.insuranceVaultBump).toBe(reserveInfo.data[237]);
+    expect((await connection.getAccountInfo(reservePda))!.data.length).toBe(
+      reserveInfo.data.length,
+    );
   });
 
   afterEach(async () => {
@@ -0,0 +1,212 @@
+import { afterEach, beforeEach, describe, expect, test } from "bun:test";
+import { LendingClient } from "../LendingClient";
+import { Connection, Keypair, PublicKey } from "@solana/web3.js";
+import { getSetup, resetAccounts } from "../setup";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Lending } from "../../target/types/lending";
+import { getAccount, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { USDC_MINT_DECIMALS, USDC_USD_PRICE_UPDATE_V2 } from "../constants";
+import { Surfpool } from "../surfpool";
+
+describe("insuranceVault", () => {
+  let client: LendingClient;
+  let program: Program<Lending>;
+  let connection: Connection;
+
+  let marketAuthority: Keypair;
+  let depositor: Keypair;
+  let marketPda: PublicKey;
+  let reservePda: PublicKey;
+  let insuranceVaultPda: PublicKey;
+
+  let liquidityMint: PublicKey;
+  const liquidityMintPriceUpdateV2 = USDC_USD_PRICE_UPDATE_V2;
+
+  const insuranceDepositAmount = 100 * Math.pow(10, USDC_MINT_DECIMALS);
+
+  beforeEach(async () => {
+    [marketAuthority, depositor] = Array.from({ length: 2 }, () =>
+      Keypair.generate(),
+    );
+
+    ({ client } = await getSetup([
+      {
+        publicKey: marketAuthority.publicKey,
+      },
+      {
+        publicKey: depositor.publicKey,
+      },
+    ]));
+
+    program = client.program;
+    connection = client.connection;
+
+    liquidityMint = await Surfpool.initMint({
+      decimals: USDC_MINT_DECIMALS,
+    });
+
+    // initializes a market
+    const name = "Test Market";
+
+    await program.methods
+      .initializeMarket(name)
+      .accounts({
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    marketPda = LendingClient.getMarketPda(name);
+
+    // initializes a reserve
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 8000; // 80%
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: null,
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2: liquidityMintPriceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    reservePda = LendingClient.getReservePda(marketPda, liquidityMint);
+    insuranceVaultPda = LendingClient.getInsuranceVaultPda(reservePda);
+
+    const depositorTokenAccount = await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: depositor.publicKey.toBase58(),
+      update: {
+        amount: insuranceDepositAmount,
+      },
+    });
+
+    await program.methods
+      .depositInsurance(new BN(insuranceDepositAmount))
+      .accountsPartial({
+        depositor: depositor.publicKey,
+        depositorTokenAccount,
+        liquidityMint,
+        reserve: reservePda,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([depositor])
+      .rpc();
+  });
+
+  test("deposit into insurance vault", async () => {
+    const reserveAcc = await client.fetchProgramAccount(reservePda, "reserve");
+
+    expect(reserveAcc.liquidity.insuranceAmount.toNumber()).toBe(
+      insuranceDepositAmount,
+    );
+    expect(reserveAcc.liquidity.accumulatedPlatformFees.toNumber()).toBe(0);
+
+    const insuranceVaultAcc = await getAccount(connection, insuranceVaultPda);
+
+    expect(Number(insuranceVaultAcc.amount)).toBe(insuranceDepositAmount);
+  });
+
+  test("request an insurance withdrawal", async () => {
+    const withdrawAmount = insuranceDepositAmount / 2;
+
+    await program.methods
+      .requestInsuranceWithdrawal(new BN(withdrawAmount))
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+        reserve: reservePda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const reserveAcc = await client.fetchProgramAccount(reservePda, "reserve");
+
+    expect(reserveAcc.insuranceWithdrawal.amount.toNumber()).toBe(
+      withdrawAmount,
+    );
+    expect(
+      reserveAcc.insuranceWithdrawal.unlockTimestamp.toNumber(),
+    ).toBeGreaterThan(
+      await connection.getBlockTime(await connection.getSlot()),
+    );
+  });
+
+  test("throws if insurance withdrawal is still timelocked", async () => {
+    await program.methods
+      .requestInsuranceWithdrawal(new BN(insuranceDepositAmount))
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+        reserve: reservePda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    await expect(
+      program.methods
+        .withdrawInsurance()
+        .accountsPartial({
+          authority: marketAuthority.publicKey,
+          liquidityMint,
+          market: marketPda,
+          reserve: reservePda,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([marketAuthority])
+        .rpc(),
+    ).rejects.toThrow("InsuranceWithdrawalLocked");
+  });
+
+  test("throws if insurance withdrawal exceeds vault balance", async () => {
+    await expect(
+      program.methods
+        .requestInsuranceWithdrawal(new BN(insuranceDepositAmount + 1))
+        .accountsPartial({
+          authority: marketAuthority.publicKey,
+          market: marketPda,
+          reserve: reservePda,
+        })
+        .signers([marketAuthority])
+        .rpc(),
+    ).rejects.toThrow("InsufficientInsurance");
+  });
+
+  afterEach(async () => {
+    await resetAccounts([marketPda]);
+  });
+});
@@ -0,0 +1,624 @@
+import { afterEach, beforeEach, describe, expect, test } from "bun:test";
+import { LendingClient } from "../LendingClient";
+import { Connection, Keypair, PublicKey } from "@solana/web3.js";
+import {
+  airdropAccount,
+  buildAndSendv0Tx,
+  expireBlockhash,
+  getSetup,
+  resetAccounts,
+} from "../setup";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Lending } from "../../target/types/lending";
+import { Surfpool } from "../surfpool";
+import {
+  createTransferInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
+import {
+  DELEGATE_PERMISSION_BORROW,
+  DELEGATE_PERMISSION_DEPOSIT,
+  USDC_MINT_DECIMALS,
+  USDC_USD_PRICE_UPDATE_V2,
+  WBTC_MINT_DECIMALS,
+  WBTC_USD_PRICE_UPDATE_V2,
+} from "../constants";
+
+describe("leverageObligation", () => {
+  let client: LendingClient;
+  let program: Program<Lending>;
+  let connection: Connection;
+
+  let marketAuthority: Keypair;
+  let borrowerObligationAuthority: Keypair;
+  let lenderObligationAuthority: Keypair;
+  let swapAuthority: Keypair;
+  let marketPda: PublicKey;
+  let collateralReservePda: PublicKey;
+  let liquidityReservePda: PublicKey;
+  let borrowerObligationPda: PublicKey;
+  let lenderObligationPda: PublicKey;
+  let collateralReceiptMint: PublicKey;
+
+  let collateralMint: PublicKey;
+  const collateralMintPriceUpdateV2 = WBTC_USD_PRICE_UPDATE_V2;
+  let liquidityMint: PublicKey;
+  const liquidityMintPriceUpdateV2 = USDC_USD_PRICE_UPDATE_V2;
+
+  const collateralDepositAmount = 10 * Math.pow(10, WBTC_MINT_DECIMALS);
+  const liquidityDepositAmount = 1000 * Math.pow(10, USDC_MINT_DECIMALS);
+
+  beforeEach(async () => {
+    [
+      marketAuthority,
+      borrowerObligationAuthority,
+      lenderObligationAuthority,
+      swapAuthority,
+    ] = Array.from({ length: 4 }, () => Keypair.generate());
+
+    ({ client } = await getSetup([
+      {
+        publicKey: marketAuthority.publicKey,
+      },
+      {
+        publicKey: borrowerObligationAuthority.publicKey,
+      },
+      {
+        publicKey: lenderObligationAuthority.publicKey,
+      },
+      {
+        publicKey: swapAuthority.publicKey,
+      },
+    ]));
+
+    program = client.program;
+    connection = client.connection;
+
+    collateralMint = await Surfpool.initMint({
+      decimals: WBTC_MINT_DECIMALS,
+    });
+
+    liquidityMint = await Surfpool.initMint({
+      decimals: USDC_MINT_DECIMALS,
+    });
+
+    // initializes a market
+    const name = "Test Market";
+
+    await program.methods
+      .initializeMarket(name)
+      .accounts({
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    marketPda = LendingClient.getMarketPda(name);
+
+    // initializes collateral reserve
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 8000; // 80%
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+    const priceUpdateV2 = collateralMintPriceUpdateV2;
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: null,
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint: collateralMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    collateralReservePda = LendingClient.getReservePda(
+      marketPda,
+      collateralMint,
+    );
+
+    // initializes an obligation
+    await program.methods
+      .initializeObligation(0)
+      .accountsPartial({
+        authority: borrowerObligationAuthority.publicKey,
+        reserve: collateralReservePda,
+        obligation: LendingClient.getObligationPda(
+          borrowerObligationAuthority.publicKey,
+          marketPda,
+        ),
+      })
+      .signers([borrowerObligationAuthority])
+      .rpc();
+
+    borrowerObligationPda = LendingClient.getObligationPda(
+      borrowerObligationAuthority.publicKey,
+      marketPda,
+    );
+
+    await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: borrowerObligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: collateralDepositAmount,
+      },
+    });
+
+    await buildAndSendv0Tx(
+      // refresh reserves and obligations
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: collateralMintPriceUpdateV2,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: borrowerObligationPda,
+          })
+          .instruction(),
+        // deposit collateral into reserve
+        await program.methods
+          .depositReserveLiquidityAndObligationCollateral(
+            new BN(collateralDepositAmount),
+          )
+          .accountsPartial({
+            authority: borrowerObligationAuthority.publicKey,
+            collateralMint,
+            collateralTokenProgram: TOKEN_PROGRAM_ID,
+            obligation: borrowerObligationPda,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+      ],
+      [borrowerObligationAuthority],
+    );
+
+    collateralReceiptMint = LendingClient.getReceiptMint(collateralReservePda);
+
+    // initialize liquidity reserve
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: null,
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2: liquidityMintPriceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    liquidityReservePda = LendingClient.getReservePda(marketPda, liquidityMint);
+    lenderObligationPda = LendingClient.getObligationPda(
+      lenderObligationAuthority.publicKey,
+      marketPda,
+    );
+
+    // initializes an obligation
+    await program.methods
+      .initializeObligation(0)
+      .accountsPartial({
+        authority: lenderObligationAuthority.publicKey,
+        reserve: liquidityReservePda,
+        obligation: lenderObligationPda,
+      })
+      .signers([lenderObligationAuthority])
+      .rpc();
+
+    await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: lenderObligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: liquidityDepositAmount,
+      },
+    });
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      // refresh reserves and obligations
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: lenderObligationPda,
+          })
+          .instruction(),
+        // deposit liquidity into reserve
+        await program.methods
+          .depositReserveLiquidityAndObligationCollateral(
+            new BN(liquidityDepositAmount),
+          )
+          .accountsPartial({
+            authority: lenderObligationAuthority.publicKey,
+            collateralMint: liquidityMint,
+            collateralTokenProgram: TOKEN_PROGRAM_ID,
+            obligation: lenderObligationPda,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+      ],
+      [lenderObligationAuthority],
+    );
+  });
+
+  test("leverage obligation to a target loan to value ratio", async () => {
+    const leverageDepositAmount = collateralDepositAmount / 1000;
+    const swapAmount = leverageDepositAmount;
+    const targetLtvBps = 1; // 0.01%
+
+    const borrowerCollateralAta = await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: borrowerObligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: leverageDepositAmount,
+      },
+    });
+    const swapCollateralAta = await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: swapAuthority.publicKey.toBase58(),
+      update: {
+        amount: swapAmount,
+      },
+    });
+
+    // token program acts as the receiver, standing in for a swap that pays out collateral for the borrowed liquidity
+    const swapIx = createTransferInstruction(
+      swapCollateralAta,
+      borrowerCollateralAta,
+      swapAuthority.publicKey,
+      swapAmount,
+    );
+
+    const preBorrowerObligationAcc = await client.fetchProgramAccount(
+      borrowerObligationPda,
+      "obligation",
+    );
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      // refresh reserves and obligations
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: collateralMintPriceUpdateV2,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: borrowerObligationPda,
+          })
+          .remainingAccounts([
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReservePda,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReceiptMint,
+            },
+          ])
+          .instruction(),
+        // flash borrow, swap into collateral, deposit and borrow in one step
+        await program.methods
+          .leverageObligation(
+            new BN(leverageDepositAmount),
+            targetLtvBps,
+            swapIx.data,
+          )
+          .accountsPartial({
+            authority: borrowerObligationAuthority.publicKey,
+            borrowReserve: liquidityReservePda,
+            collateralMint,
+            collateralTokenProgram: TOKEN_PROGRAM_ID,
+            depositReserve: collateralReservePda,
+            liquidityMint,
+            liquidityTokenProgram: TOKEN_PROGRAM_ID,
+            obligation: borrowerObligationPda,
+            receiverProgram: TOKEN_PROGRAM_ID,
+          })
+          .remainingAccounts(swapIx.keys)
+          .instruction(),
+      ],
+      [borrowerObligationAuthority, swapAuthority],
+    );
+
+    const postBorrowerObligationAcc = await client.fetchProgramAccount(
+      borrowerObligationPda,
+      "obligation",
+    );
+
+    expect(postBorrowerObligationAcc.lastUpdate.isStale).toBeTrue();
+    expect(
+      postBorrowerObligationAcc.deposits[0].depositedAmount.eq(
+        preBorrowerObligationAcc.deposits[0].depositedAmount.add(
+          new BN(leverageDepositAmount + swapAmount),
+        ),
+      ),
+    ).toBeTrue();
+    expect(
+      postBorrowerObligationAcc.borrows[0].reserve.equals(liquidityReservePda),
+    ).toBeTrue();
+    expect(postBorrowerObligationAcc.borrows[0].borrowedAmount.gtn(0)).toBeTrue();
+
+    // the swap stand-in leaves the flash borrowed liquidity with the borrower
+    const borrowerLiquidityAcc = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(
+        liquidityMint,
+        borrowerObligationAuthority.publicKey,
+      ),
+    );
+
+    expect(
+      postBorrowerObligationAcc.borrows[0].borrowedAmount.eqn(
+        Number(borrowerLiquidityAcc.amount),
+      ),
+    ).toBeTrue();
+  });
+
+  test("throws if the receiver program does not return collateral", async () => {
+    const leverageDepositAmount = collateralDepositAmount / 1000;
+    const targetLtvBps = 1; // 0.01%
+
+    const borrowerCollateralAta = await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: borrowerObligationAuthority.publicKey.toBase58(),
+      update: {
+        amount: leverageDepositAmount,
+      },
+    });
+
+    // transfers collateral to itself, so the borrower receives nothing for the borrowed liquidity
+    const swapIx = createTransferInstruction(
+      borrowerCollateralAta,
+      borrowerCollateralAta,
+      borrowerObligationAuthority.publicKey,
+      leverageDepositAmount,
+    );
+
+    await expireBlockhash();
+
+    await expect(
+      buildAndSendv0Tx(
+        [
+          await program.methods
+            .refreshReserve()
+            .accounts({
+              priceUpdateV2: collateralMintPriceUpdateV2,
+              reserve: collateralReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .refreshReserve()
+            .accounts({
+              priceUpdateV2: liquidityMintPriceUpdateV2,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .refreshObligation()
+            .accounts({
+              obligation: borrowerObligationPda,
+            })
+            .remainingAccounts([
+              {
+                isSigner: false,
+                isWritable: false,
+                pubkey: collateralReservePda,
+              },
+              {
+                isSigner: false,
+                isWritable: false,
+                pubkey: collateralReceiptMint,
+              },
+            ])
+            .instruction(),
+          await program.methods
+            .leverageObligation(
+              new BN(leverageDepositAmount),
+              targetLtvBps,
+              swapIx.data,
+            )
+            .accountsPartial({
+              authority: borrowerObligationAuthority.publicKey,
+              borrowReserve: liquidityReservePda,
+              collateralMint,
+              collateralTokenProgram: TOKEN_PROGRAM_ID,
+              depositReserve: collateralReservePda,
+              liquidityMint,
+              liquidityTokenProgram: TOKEN_PROGRAM_ID,
+              obligation: borrowerObligationPda,
+              receiverProgram: TOKEN_PROGRAM_ID,
+            })
+            .remainingAccounts(swapIx.keys)
+            .instruction(),
+        ],
+        [borrowerObligationAuthority],
+      ),
+    ).rejects.toThrow("LeverageSwapNotReceived");
+  });
+
+  test("reject a delegate routing the borrow through a malicious receiver", async () => {
+    const leverageDepositAmount = collateralDepositAmount / 1000;
+    const targetLtvBps = 1; // 0.01%
+    const manager = Keypair.generate();
+
+    await airdropAccount(manager.publicKey);
+
+    await program.methods
+      .setObligationDelegate(
+        manager.publicKey,
+        DELEGATE_PERMISSION_DEPOSIT | DELEGATE_PERMISSION_BORROW,
+        new BN(0),
+      )
+      .accountsPartial({
+        authority: borrowerObligationAuthority.publicKey,
+        obligation: borrowerObligationPda,
+      })
+      .signers([borrowerObligationAuthority])
+      .rpc();
+
+    const managerCollateralAta = await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: manager.publicKey.toBase58(),
+      update: {
+        amount: leverageDepositAmount,
+      },
+    });
+    const swapCollateralAta = await Surfpool.setTokenAccount({
+      mint: collateralMint.toBase58(),
+      owner: swapAuthority.publicKey.toBase58(),
+      update: {
+        amount: 1,
+      },
+    });
+
+    // the receiver keeps the borrowed liquidity with the delegate and returns a single unit of collateral
+    const swapIx = createTransferInstruction(
+      swapCollateralAta,
+      managerCollateralAta,
+      swapAuthority.publicKey,
+      1,
+    );
+
+    await expireBlockhash();
+
+    await expect(
+      buildAndSendv0Tx(
+        [
+          await program.methods
+            .refreshReserve()
+            .accounts({
+              priceUpdateV2: collateralMintPriceUpdateV2,
+              reserve: collateralReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .refreshReserve()
+            .accounts({
+              priceUpdateV2: liquidityMintPriceUpdateV2,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .refreshObligation()
+            .accounts({
+              obligation: borrowerObligationPda,
+            })
+            .remainingAccounts([
+              {
+                isSigner: false,
+                isWritable: false,
+                pubkey: collateralReservePda,
+              },
+              {
+                isSigner: false,
+                isWritable: false,
+                pubkey: collateralReceiptMint,
+              },
+            ])
+            .instruction(),
+          await program.methods
+            .leverageObligation(
+              new BN(leverageDepositAmount),
+              targetLtvBps,
+              swapIx.data,
+            )
+            .accountsPartial({
+              authority: manager.publicKey,
+              borrowReserve: liquidityReservePda,
+              collateralMint,
+              collateralTokenProgram: TOKEN_PROGRAM_ID,
+              depositReserve: collateralReservePda,
+              liquidityMint,
+              liquidityTokenProgram: TOKEN_PROGRAM_ID,
+              obligation: borrowerObligationPda,
+              receiverProgram: TOKEN_PROGRAM_ID,
+            })
+            .remainingAccounts(swapIx.keys)
+            .instruction(),
+        ],
+        [manager, swapAuthority],
+      ),
+    ).rejects.toThrow("InvalidObligationAuthority");
+  });
+
+  afterEach(async () => {
+    await resetAccounts([marketPda]);
+  });
+});
@@ -106,24 +106,35 @@ describe("liquidateObligation", () => {
     const maxBorrowRateBps = 8000; // 80%
     const flashLoanFeeBps = 500; // 5%
     const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
     const priceUpdateV2 = collateralMintPriceUpdateV2;
 
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint: collateralMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -138,10 +149,14 @@ describe("liquidateObligation", () => {
 
     // initializes an obligation
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: borrowerObligationAuthority.publicKey,
         reserve: collateralReservePda,
+        obligation: LendingClient.getObligationPda(
+          borrowerObligationAuthority.publicKey,
+          marketPda,
+        ),
       })
       .signers([borrowerObligationAuthority])
       .rpc();
@@ -198,19 +213,26 @@ describe("liquidateObligation", () => {
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2: liquidityMintPriceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -226,7 +248,7 @@ describe("liquidateObligation", () => {
 
     // initializes an obligation
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: lenderObligationAuthority.publicKey,
         reserve: liquidityReservePda,
@@ -346,6 +368,7 @@ describe("liquidateObligation", () => {
           .borrowObligationLiquidity(new BN(borrowAmount))
           .accountsPartial({
             authority: borrowerObligationAuthority.publicKey,
+            obligationAuthority: borrowerObligationAuthority.publicKey,
             liquidityMint,
             obligation: borrowerObligationPda,
             reserveTokenAccount: liquidityReserveAta,
@@ -363,11 +386,15 @@ describe("liquidateObligation", () => {
     await program.methods
       .updateReserve({
         flashLoanFeeBps: null,
+        flashLoansEnabled: null,
+        insuranceFeeBps: null,
         liquidationBonusBps: null,
         liquidationCloseFactorBps: 10000, // 100%
+        liquidationInsuranceFeeBps: null,
         liquidationThresholdBps: 1, // 0.01%
         loanToValueBps: null,
         maxBorrowRateBps: null,
+        maxFlashLoanAmount: null,
         minBorrowRateBps: null,
         optimalBorrowRateBps: null,
         optimalUtilizationRateBps: null,
@@ -375,7 +402,7 @@ describe("liquidateObligation", () => {
       })
       .accountsPartial({
         authority: marketAuthority.publicKey,
-        reserve: liquidityReservePda,
+        reserve: collateralReservePda,
         market: marketPda,
       })
       .signers([marketAuthority])
@@ -452,6 +479,7 @@ describe("liquidateObligation", () => {
           .liquidateObligation()
           .accountsPartial({
             liquidator: liquidator.publicKey,
+            market: marketPda,
             liquidityMint,
             collateralMint,
             obligation: borrowerObligationPda,
@@ -503,6 +531,314 @@ describe("liquidateObligation", () => {
     ).toBeTrue();
   });
 
+  test("liquidate dust obligation in full", async () => {
+    // update reserve to meet liquidation requirements
+    await program.methods
+      .updateReserve({
+        flashLoanFeeBps: null,
+        flashLoansEnabled: null,
+        insuranceFeeBps: null,
+        liquidationBonusBps: null,
+        liquidationCloseFactorBps: null,
+        liquidationInsuranceFeeBps: null,
+        liquidationThresholdBps: 1, // 0.01%
+        loanToValueBps: null,
+        maxBorrowRateBps: null,
+        maxFlashLoanAmount: null,
+        minBorrowRateBps: null,
+        optimalBorrowRateBps: null,
+        optimalUtilizationRateBps: null,
+        platformFeeBps: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        reserve: collateralReservePda,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    // treat the whole borrow as dust
+    await program.methods
+      .updateMarket({
+        allowedMintExtensions: null,
+        listingApprover: null,
+        permissionedListing: null,
+        complianceAuthority: null,
+        permissionedObligations: null,
+        maxObligationDeposits: null,
+        maxObligationBorrows: null,
+        dustThresholdValue: new BN(1_000_000_000), // $1000
+        receiptTokenProgram: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    // set liquidator liquidity token account
+    const liquidatorLiquidityTokenAccount = await Surfpool.setTokenAccount({
+      mint: liquidityMint.toBase58(),
+      owner: liquidator.publicKey.toBase58(),
+      update: {
+        amount: borrowAmount * 2,
+      },
+    });
+
+    const collateralReserveAta = getAssociatedTokenAddressSync(
+      collateralMint,
+      collateralReservePda,
+      !PublicKey.isOnCurve(collateralReservePda),
+    );
+
+    const preLiquidityReserveAcc = await client.fetchProgramAccount(
+      liquidityReservePda,
+      "reserve",
+    );
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      [
+        // refresh reserves and obligations
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: collateralMintPriceUpdateV2,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: borrowerObligationPda,
+          })
+          .remainingAccounts([
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReservePda,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReceiptMint,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: liquidityReservePda,
+            },
+          ])
+          .instruction(),
+        // liquidate borrower's obligation
+        await program.methods
+          .liquidateObligation()
+          .accountsPartial({
+            liquidator: liquidator.publicKey,
+            market: marketPda,
+            liquidityMint,
+            collateralMint,
+            obligation: borrowerObligationPda,
+            repayReserve: liquidityReservePda,
+            withdrawReserve: collateralReservePda,
+            reserveCollateralTokenAccount: collateralReserveAta,
+            reserveLiquidityTokenAccount: liquidityReserveAta,
+            liquidatorLiquidityTokenAccount,
+            liquidityTokenProgram: TOKEN_PROGRAM_ID,
+            collateralTokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .instruction(),
+      ],
+      [liquidator],
+    );
+
+    const postLiquidityReserveAcc = await client.fetchProgramAccount(
+      liquidityReservePda,
+      "reserve",
+    );
+
+    expect(preLiquidityReserveAcc.lastUpdate.isStale).toBeTrue();
+    expect(
+      preLiquidityReserveAcc.liquidity.availableAmount.lt(
+        postLiquidityReserveAcc.liquidity.availableAmount,
+      ),
+    ).toBeTrue();
+    expect(
+      preLiquidityReserveAcc.liquidity.borrowedAmount.gt(
+        postLiquidityReserveAcc.liquidity.borrowedAmount,
+      ),
+    ).toBeTrue();
+
+    const postBorrowerObligationAcc = await client.fetchProgramAccount(
+      borrowerObligationPda,
+      "obligation",
+    );
+
+    expect(postBorrowerObligationAcc.lastUpdate.isStale).toBeTrue();
+    expect(postBorrowerObligationAcc.borrows).toHaveLength(0);
+    expect(postBorrowerObligationAcc.deposits).toHaveLength(1);
+  });
+
+  test("get obligation health", async () => {
+    // update reserve to meet liquidation requirements
+    await program.methods
+      .updateReserve({
+        flashLoanFeeBps: null,
+        flashLoansEnabled: null,
+        insuranceFeeBps: null,
+        liquidationBonusBps: null,
+        liquidationCloseFactorBps: null,
+        liquidationInsuranceFeeBps: null,
+        liquidationThresholdBps: 1, // 0.01%
+        loanToValueBps: null,
+        maxBorrowRateBps: null,
+        maxFlashLoanAmount: null,
+        minBorrowRateBps: null,
+        optimalBorrowRateBps: null,
+        optimalUtilizationRateBps: null,
+        platformFeeBps: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        reserve: collateralReservePda,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    await expireBlockhash();
+
+    const obligationHealth = await program.methods
+      .getObligationHealth()
+      .accountsPartial({
+        market: marketPda,
+        obligation: borrowerObligationPda,
+      })
+      .remainingAccounts([
+        {
+          isSigner: false,
+          isWritable: false,
+          pubkey: collateralReservePda,
+        },
+      ])
+      .preInstructions([
+        // refresh reserves and obligations
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: collateralMintPriceUpdateV2,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: borrowerObligationPda,
+          })
+          .remainingAccounts([
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReservePda,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReceiptMint,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: liquidityReservePda,
+            },
+          ])
+          .instruction(),
+      ])
+      .view();
+
+    expect(obligationHealth.isUnhealthy).toBeTrue();
+    expect(obligationHealth.borrows[0].reserve).toStrictEqual(
+      liquidityReservePda,
+    );
+    expect(obligationHealth.borrows[0].maxRepayAmount.toNumber()).toBeGreaterThan(
+      0,
+    );
+    expect(obligationHealth.deposits[0].reserve).toStrictEqual(
+      collateralReservePda,
+    );
+    expect(
+      obligationHealth.deposits[0].maxWithdrawAmount.toNumber(),
+    ).toBeGreaterThan(0);
+  });
+
+  test("throws if deposit reserves are missing from obligation health", async () => {
+    await expect(
+      program.methods
+        .getObligationHealth()
+        .accountsPartial({
+          market: marketPda,
+          obligation: borrowerObligationPda,
+        })
+        .preInstructions([
+          await program.methods
+            .refreshReserve()
+            .accounts({
+              priceUpdateV2: collateralMintPriceUpdateV2,
+              reserve: collateralReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .refreshReserve()
+            .accounts({
+              priceUpdateV2: liquidityMintPriceUpdateV2,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .refreshObligation()
+            .accounts({
+              obligation: borrowerObligationPda,
+            })
+            .remainingAccounts([
+              {
+                isSigner: false,
+                isWritable: false,
+                pubkey: collateralReservePda,
+              },
+              {
+                isSigner: false,
+                isWritable: false,
+                pubkey: collateralReceiptMint,
+              },
+              {
+                isSigner: false,
+                isWritable: false,
+                pubkey: liquidityReservePda,
+              },
+            ])
+            .instruction(),
+        ])
+        .rpc(),
+    ).rejects.toThrow("DepositReserveAccountsMismatch");
+  });
+
   afterEach(async () => {
     await resetAccounts([marketPda]);
   });
@@ -99,24 +99,35 @@ describe("redeemFees", () => {
     const maxBorrowRateBps = 8000; // 80%
     const flashLoanFeeBps = 500; // 5%
     const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
     const priceUpdateV2 = collateralMintPriceUpdateV2;
 
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint: collateralMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -131,10 +142,14 @@ describe("redeemFees", () => {
 
     // initializes an obligation
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: borrowerObligationAuthority.publicKey,
         reserve: collateralReservePda,
+        obligation: LendingClient.getObligationPda(
+          borrowerObligationAuthority.publicKey,
+          marketPda,
+        ),
       })
       .signers([borrowerObligationAuthority])
       .rpc();
@@ -191,19 +206,26 @@ describe("redeemFees", () => {
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2: liquidityMintPriceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -219,7 +241,7 @@ describe("redeemFees", () => {
 
     // initializes an obligation
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: lenderObligationAuthority.publicKey,
         reserve: liquidityReservePda,
@@ -339,6 +361,7 @@ describe("redeemFees", () => {
           .borrowObligationLiquidity(new BN(borrowAmount))
           .accountsPartial({
             authority: borrowerObligationAuthority.publicKey,
+            obligationAuthority: borrowerObligationAuthority.publicKey,
             liquidityMint,
             obligation: borrowerObligationPda,
             reserveTokenAccount: liquidityReserveAta,
@@ -403,13 +426,13 @@ describe("redeemFees", () => {
         await program.methods
           .repayObligationLiquidity(new BN(repayAmount))
           .accountsPartial({
-            authority: borrowerObligationAuthority.publicKey,
+            payer: borrowerObligationAuthority.publicKey,
             liquidityMint,
             obligation: borrowerObligationPda,
             reserveTokenAccount: liquidityReserveAta,
             tokenProgram: TOKEN_PROGRAM_ID,
             reserve: liquidityReservePda,
-            authorityTokenAccount: borrowerLiquidityAta,
+            payerTokenAccount: borrowerLiquidityAta,
           })
           .instruction(),
       ],
@@ -456,6 +479,37 @@ describe("redeemFees", () => {
     expect(marketAuthorityLiquidityAtaAcc.amount).toBeGreaterThan(0);
   });
 
+  test("reject the insurance vault as the reserve token account", async () => {
+    const insuranceVaultPda =
+      LendingClient.getInsuranceVaultPda(liquidityReservePda);
+
+    await expect(
+      buildAndSendv0Tx(
+        [
+          await program.methods
+            .refreshReserve()
+            .accounts({
+              priceUpdateV2: liquidityMintPriceUpdateV2,
+              reserve: liquidityReservePda,
+            })
+            .instruction(),
+          await program.methods
+            .redeemFees()
+            .accountsPartial({
+              authority: marketAuthority.publicKey,
+              liquidityMint,
+              reserveTokenAccount: insuranceVaultPda,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              reserve: liquidityReservePda,
+              market: marketPda,
+            })
+            .instruction(),
+        ],
+        [marketAuthority],
+      ),
+    ).rejects.toThrow("ConstraintAssociated");
+  });
+
   afterEach(async () => {
     await resetAccounts([marketPda]);
   });
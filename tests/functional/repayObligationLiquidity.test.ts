@@ -2,6 +2,7 @@ import { afterEach, beforeEach, describe, expect, test } from "bun:test";
 import { LendingClient } from "../LendingClient";
 import { Connection, Keypair, PublicKey } from "@solana/web3.js";
 import {
+  airdropAccount,
   buildAndSendv0Tx,
   expireBlockhash,
   getSetup,
@@ -15,6 +16,7 @@ import {
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import {
+  DELEGATE_PERMISSION_DEPOSIT,
   USDC_MINT_DECIMALS,
   USDC_USD_PRICE_UPDATE_V2,
   WBTC_MINT_DECIMALS,
@@ -98,24 +100,35 @@ describe("repayObligationLiquidity", () => {
     const maxBorrowRateBps = 8000; // 80%
     const flashLoanFeeBps = 500; // 5%
     const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
     const priceUpdateV2 = collateralMintPriceUpdateV2;
 
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint: collateralMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -130,10 +143,14 @@ describe("repayObligationLiquidity", () => {
 
     // initializes an obligation
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: borrowerObligationAuthority.publicKey,
         reserve: collateralReservePda,
+        obligation: LendingClient.getObligationPda(
+          borrowerObligationAuthority.publicKey,
+          marketPda,
+        ),
       })
       .signers([borrowerObligationAuthority])
       .rpc();
@@ -190,19 +207,26 @@ describe("repayObligationLiquidity", () => {
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2: liquidityMintPriceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -218,7 +242,7 @@ describe("repayObligationLiquidity", () => {
 
     // initializes an obligation
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: lenderObligationAuthority.publicKey,
         reserve: liquidityReservePda,
@@ -338,6 +362,7 @@ describe("repayObligationLiquidity", () => {
           .borrowObligationLiquidity(new BN(borrowAmount))
           .accountsPartial({
             authority: borrowerObligationAuthority.publicKey,
+            obligationAuthority: borrowerObligationAuthority.publicKey,
             liquidityMint,
             obligation: borrowerObligationPda,
             reserveTokenAccount: liquidityReserveAta,
@@ -413,13 +438,13 @@ describe("repayObligationLiquidity", () => {
         await program.methods
           .repayObligationLiquidity(new BN(repayAmount))
           .accountsPartial({
-            authority: borrowerObligationAuthority.publicKey,
+            payer: borrowerObligationAuthority.publicKey,
             liquidityMint,
             obligation: borrowerObligationPda,
             reserveTokenAccount: liquidityReserveAta,
             tokenProgram: TOKEN_PROGRAM_ID,
             reserve: liquidityReservePda,
-            authorityTokenAccount: borrowerLiquidityAta,
+            payerTokenAccount: borrowerLiquidityAta,
           })
           .instruction(),
       ],
@@ -460,6 +485,138 @@ describe("repayObligationLiquidity", () => {
     ).toBeTrue();
   });
 
+  test("reject a borrow by a delegate without borrow permission", async () => {
+    const manager = Keypair.generate();
+
+    await airdropAccount(manager.publicKey);
+
+    await program.methods
+      .setObligationDelegate(
+        manager.publicKey,
+        DELEGATE_PERMISSION_DEPOSIT,
+        new BN(0),
+      )
+      .accountsPartial({
+        authority: borrowerObligationAuthority.publicKey,
+        obligation: borrowerObligationPda,
+      })
+      .signers([borrowerObligationAuthority])
+      .rpc();
+
+    const obligationDelegatePda = LendingClient.getObligationDelegatePda(
+      borrowerObligationPda,
+      manager.publicKey,
+    );
+
+    await expect(
+      program.methods
+        .borrowObligationLiquidity(new BN(borrowAmount))
+        .accountsPartial({
+          authority: manager.publicKey,
+          obligationAuthority: borrowerObligationAuthority.publicKey,
+          liquidityMint,
+          obligation: borrowerObligationPda,
+          obligationDelegate: obligationDelegatePda,
+          reserveTokenAccount: liquidityReserveAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          reserve: liquidityReservePda,
+        })
+        .signers([manager])
+        .rpc(),
+    ).rejects.toThrow("DelegatePermissionDenied");
+  });
+
+  test("repay liquidity on behalf of another obligation", async () => {
+    const repayAmount = borrowAmount / 2;
+    const rescuer = Keypair.generate();
+
+    await airdropAccount(rescuer.publicKey);
+
+    const rescuerLiquidityAta = await Surfpool.setTokenAccount({
+      owner: rescuer.publicKey.toBase58(),
+      mint: liquidityMint.toBase58(),
+      update: {
+        amount: repayAmount,
+      },
+    });
+
+    const preBorrowerObligationAcc = await client.fetchProgramAccount(
+      borrowerObligationPda,
+      "obligation",
+    );
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: collateralMintPriceUpdateV2,
+            reserve: collateralReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: liquidityMintPriceUpdateV2,
+            reserve: liquidityReservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: borrowerObligationPda,
+          })
+          .remainingAccounts([
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReservePda,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: collateralReceiptMint,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: liquidityReservePda,
+            },
+          ])
+          .instruction(),
+        await program.methods
+          .repayObligationLiquidity(new BN(repayAmount))
+          .accountsPartial({
+            payer: rescuer.publicKey,
+            liquidityMint,
+            obligation: borrowerObligationPda,
+            reserveTokenAccount: liquidityReserveAta,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            reserve: liquidityReservePda,
+            payerTokenAccount: rescuerLiquidityAta,
+          })
+          .instruction(),
+      ],
+      [rescuer],
+    );
+
+    const postBorrowerObligationAcc = await client.fetchProgramAccount(
+      borrowerObligationPda,
+      "obligation",
+    );
+
+    // pre is lower due to accrued interest
+    expect(
+      preBorrowerObligationAcc.borrows[0].borrowedAmount.lt(
+        postBorrowerObligationAcc.borrows[0].borrowedAmount.add(
+          new BN(repayAmount),
+        ),
+      ),
+    ).toBeTrue();
+  });
+
   afterEach(async () => {
     await resetAccounts([marketPda]);
   });
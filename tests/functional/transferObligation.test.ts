@@ -0,0 +1,241 @@
+import { afterEach, beforeEach, describe, expect, test } from "bun:test";
+import { LendingClient } from "../LendingClient";
+import { Connection, Keypair, PublicKey } from "@solana/web3.js";
+import { getSetup, resetAccounts } from "../setup";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Lending } from "../../target/types/lending";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { WBTC_MINT_DECIMALS, WBTC_USD_PRICE_UPDATE_V2 } from "../constants";
+import { Surfpool } from "../surfpool";
+
+describe("transferObligation", () => {
+  let client: LendingClient;
+  let program: Program<Lending>;
+  let connection: Connection;
+
+  let marketAuthority: Keypair;
+  let obligationAuthority: Keypair;
+  let marketPda: PublicKey;
+  let reservePda: PublicKey;
+
+  let collateralMint: PublicKey;
+  const collateralMintPriceUpdateV2 = WBTC_USD_PRICE_UPDATE_V2;
+
+  beforeEach(async () => {
+    [marketAuthority, obligationAuthority] = Array.from({ length: 2 }, () =>
+      Keypair.generate(),
+    );
+
+    ({ client } = await getSetup([
+      {
+        publicKey: marketAuthority.publicKey,
+      },
+      {
+        publicKey: obligationAuthority.publicKey,
+      },
+    ]));
+
+    program = client.program;
+    connection = client.connection;
+
+    collateralMint = await Surfpool.initMint({
+      decimals: WBTC_MINT_DECIMALS,
+    });
+
+    // initializes a market
+    const name = "Test Market";
+
+    await program.methods
+      .initializeMarket(name)
+      .accounts({
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    marketPda = LendingClient.getMarketPda(name);
+
+    // initializes a reserve
+    const optimalUtilizationRateBps = 7500; // 75%
+    const loanToValueBps = 8000; // 80%
+    const liquidationBonusBps = 200; // 2%
+    const liquidationThresholdBps = 8500; // 85%
+    const liquidationCloseFactorBps = 1000; // 10%
+    const minBorrowRateBps = 200; // 2%
+    const optimalBorrowRateBps = 2000; // 20%
+    const maxBorrowRateBps = 8000; // 80%
+    const flashLoanFeeBps = 500; // 5%
+    const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
+    const priceUpdateV2 = collateralMintPriceUpdateV2;
+
+    await program.methods
+      .initializeReserve({
+        flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
+        liquidationBonusBps,
+        liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
+        liquidationThresholdBps,
+        liquiditySymbol: null,
+        loanToValueBps,
+        maxBorrowRateBps,
+        maxFlashLoanAmount,
+        minBorrowRateBps,
+        optimalBorrowRateBps,
+        optimalUtilizationRateBps,
+        platformFeeBps,
+      })
+      .accountsPartial({
+        listingApproval: null,
+        liquidityMint: collateralMint,
+        liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
+        priceUpdateV2,
+        market: marketPda,
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    reservePda = LendingClient.getReservePda(marketPda, collateralMint);
+  });
+
+  test("transfer an obligation to a new authority", async () => {
+    const obligationPda = LendingClient.getObligationPda(
+      obligationAuthority.publicKey,
+      marketPda,
+    );
+    const newAuthority = Keypair.generate();
+
+    await program.methods
+      .initializeObligation(0)
+      .accountsPartial({
+        authority: obligationAuthority.publicKey,
+        reserve: reservePda,
+        obligation: obligationPda,
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    await program.methods
+      .transferObligation(newAuthority.publicKey)
+      .accountsPartial({
+        authority: obligationAuthority.publicKey,
+        obligation: obligationPda,
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    await expect(
+      program.methods
+        .acceptObligation()
+        .accountsPartial({
+          authority: obligationAuthority.publicKey,
+          obligation: obligationPda,
+          allowlistEntry: null,
+        })
+        .signers([obligationAuthority])
+        .rpc(),
+    ).rejects.toThrow("InvalidPendingObligationAuthority");
+
+    await program.methods
+      .acceptObligation()
+      .accountsPartial({
+        authority: newAuthority.publicKey,
+        obligation: obligationPda,
+        allowlistEntry: null,
+      })
+      .signers([newAuthority])
+      .rpc();
+
+    const obligationAcc = await client.fetchProgramAccount(
+      obligationPda,
+      "obligation",
+    );
+
+    expect(obligationAcc.authority.equals(newAuthority.publicKey)).toBeTrue();
+    expect(obligationAcc.pendingAuthority.equals(PublicKey.default)).toBeTrue();
+
+    const newAuthorityObligations = await client.getObligationsByAuthority(
+      newAuthority.publicKey,
+      marketPda,
+    );
+
+    expect(newAuthorityObligations).toHaveLength(1);
+    expect(
+      newAuthorityObligations[0].publicKey.equals(obligationPda),
+    ).toBeTrue();
+    expect(
+      await client.getObligationsByAuthority(
+        obligationAuthority.publicKey,
+        marketPda,
+      ),
+    ).toHaveLength(0);
+  });
+
+  test("cancel a pending obligation transfer", async () => {
+    const obligationPda = LendingClient.getObligationPda(
+      obligationAuthority.publicKey,
+      marketPda,
+    );
+    const newAuthority = Keypair.generate();
+
+    await program.methods
+      .initializeObligation(0)
+      .accountsPartial({
+        authority: obligationAuthority.publicKey,
+        reserve: reservePda,
+        obligation: obligationPda,
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    await program.methods
+      .transferObligation(newAuthority.publicKey)
+      .accountsPartial({
+        authority: obligationAuthority.publicKey,
+        obligation: obligationPda,
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    await program.methods
+      .transferObligation(PublicKey.default)
+      .accountsPartial({
+        authority: obligationAuthority.publicKey,
+        obligation: obligationPda,
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    await expect(
+      program.methods
+        .acceptObligation()
+        .accountsPartial({
+          authority: newAuthority.publicKey,
+          obligation: obligationPda,
+          allowlistEntry: null,
+        })
+        .signers([newAuthority])
+        .rpc(),
+    ).rejects.toThrow("InvalidPendingObligationAuthority");
+
+    const obligationAcc = await client.fetchProgramAccount(
+      obligationPda,
+      "obligation",
+    );
+
+    expect(
+      obligationAcc.authority.equals(obligationAuthority.publicKey),
+    ).toBeTrue();
+  });
+
+  afterEach(async () => {
+    await resetAccounts([marketPda]);
+  });
+});
@@ -0,0 +1,72 @@
+import { afterEach, beforeEach, describe, expect, test } from "bun:test";
+import { LendingClient } from "../LendingClient";
+import { Connection, Keypair, PublicKey } from "@solana/web3.js";
+import { getSetup, resetAccounts } from "../setup";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Lending } from "../../target/types/lending";
+
+describe("updateMarket", () => {
+  let client: LendingClient;
+  let program: Program<Lending>;
+  let connection: Connection;
+
+  let marketAuthority: Keypair;
+  let marketPda: PublicKey;
+
+  beforeEach(async () => {
+    marketAuthority = Keypair.generate();
+
+    ({ client } = await getSetup([
+      {
+        publicKey: marketAuthority.publicKey,
+      },
+    ]));
+
+    program = client.program;
+    connection = client.connection;
+
+    // initializes a market
+    const name = "Test Market";
+
+    await program.methods
+      .initializeMarket(name)
+      .accounts({
+        authority: marketAuthority.publicKey,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    marketPda = LendingClient.getMarketPda(name);
+  });
+
+  test("update a market", async () => {
+    const dustThresholdValue = new BN(5_000_000); // $5
+
+    await program.methods
+      .updateMarket({
+        allowedMintExtensions: null,
+        listingApprover: null,
+        permissionedListing: null,
+        complianceAuthority: null,
+        permissionedObligations: null,
+        maxObligationDeposits: null,
+        maxObligationBorrows: null,
+        dustThresholdValue,
+        receiptTokenProgram: null,
+      })
+      .accountsPartial({
+        authority: marketAuthority.publicKey,
+        market: marketPda,
+      })
+      .signers([marketAuthority])
+      .rpc();
+
+    const marketAcc = await client.fetchProgramAccount(marketPda, "market");
+
+    expect(marketAcc.dustThresholdValue.eq(dustThresholdValue)).toBeTrue();
+  });
+
+  afterEach(async () => {
+    await resetAccounts([marketPda]);
+  });
+});
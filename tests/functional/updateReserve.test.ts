@@ -2,7 +2,7 @@ import { afterEach, beforeEach, describe, expect, test } from "bun:test";
 import { LendingClient } from "../LendingClient";
 import { Connection, Keypair, PublicKey } from "@solana/web3.js";
 import { getSetup, resetAccounts } from "../setup";
-import { Program } from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
 import { Lending } from "../../target/types/lending";
 import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { WBTC_MINT_DECIMALS, WBTC_USD_PRICE_UPDATE_V2 } from "../constants";
@@ -66,24 +66,35 @@ describe("updateReserve", () => {
     const maxBorrowRateBps = 8000; // 80%
     const flashLoanFeeBps = 500; // 5%
     const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
     const priceUpdateV2 = collateralMintPriceUpdateV2;
 
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint: collateralMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -105,16 +116,24 @@ describe("updateReserve", () => {
     const maxBorrowRateBps = 8500; // 85%
     const flashLoanFeeBps = 400; // 4%
     const platformFeeBps = 30; // 3%
+    const insuranceFeeBps = 2000; // 20%
+    const liquidationInsuranceFeeBps = 500; // 5%
+    const flashLoansEnabled = false;
+    const maxFlashLoanAmount = new BN(1_000_000);
     const priceUpdateV2 = collateralMintPriceUpdateV2;
 
     await program.methods
       .updateReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
@@ -133,6 +152,10 @@ describe("updateReserve", () => {
     expect(reserveAcc.market.equals(marketPda)).toBeTrue();
     expect(reserveAcc.config.fees.flashLoanFeeBps).toBe(flashLoanFeeBps);
     expect(reserveAcc.config.fees.platformFeeBps).toBe(platformFeeBps);
+    expect(reserveAcc.config.fees.insuranceFeeBps).toBe(insuranceFeeBps);
+    expect(reserveAcc.config.fees.liquidationInsuranceFeeBps).toBe(
+      liquidationInsuranceFeeBps,
+    );
     expect(reserveAcc.config.liquidationBonusBps).toBe(liquidationBonusBps);
     expect(reserveAcc.config.liquidationCloseFactorBps).toBe(
       liquidationCloseFactorBps,
@@ -147,6 +170,10 @@ describe("updateReserve", () => {
     expect(reserveAcc.config.minBorrowRateBps).toBe(minBorrowRateBps);
     expect(reserveAcc.config.optimalBorrowRateBps).toBe(optimalBorrowRateBps);
     expect(reserveAcc.config.maxBorrowRateBps).toBe(maxBorrowRateBps);
+    expect(reserveAcc.config.flashLoansEnabled).toBe(flashLoansEnabled);
+    expect(
+      reserveAcc.config.maxFlashLoanAmount.eq(maxFlashLoanAmount),
+    ).toBeTrue();
     expect(reserveAcc.liquidity.mint.equals(collateralMint)).toBeTrue();
     expect(reserveAcc.liquidity.priceUpdateV2.equals(priceUpdateV2)).toBeTrue();
   });
@@ -2,6 +2,7 @@ import { afterEach, beforeEach, describe, expect, test } from "bun:test";
 import { LendingClient } from "../LendingClient";
 import { Connection, Keypair, PublicKey } from "@solana/web3.js";
 import {
+  airdropAccount,
   buildAndSendv0Tx,
   expireBlockhash,
   getSetup,
@@ -15,7 +16,11 @@ import {
   getAssociatedTokenAddressSync,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
-import { WBTC_MINT_DECIMALS, WBTC_USD_PRICE_UPDATE_V2 } from "../constants";
+import {
+  DELEGATE_PERMISSION_WITHDRAW,
+  WBTC_MINT_DECIMALS,
+  WBTC_USD_PRICE_UPDATE_V2,
+} from "../constants";
 
 describe("withdrawObligationCollateral", () => {
   let client: LendingClient;
@@ -79,24 +84,35 @@ describe("withdrawObligationCollateral", () => {
     const maxBorrowRateBps = 8000; // 80%
     const flashLoanFeeBps = 500; // 5%
     const platformFeeBps = 250; // 2.5%
+    const insuranceFeeBps = 1000; // 10%
+    const liquidationInsuranceFeeBps = 1000; // 10%
+    const flashLoansEnabled = true;
+    const maxFlashLoanAmount = new BN(Number.MAX_SAFE_INTEGER);
     const priceUpdateV2 = collateralMintPriceUpdateV2;
 
     await program.methods
       .initializeReserve({
         flashLoanFeeBps,
+        flashLoansEnabled,
+        insuranceFeeBps,
         liquidationBonusBps,
         liquidationCloseFactorBps,
+        liquidationInsuranceFeeBps,
         liquidationThresholdBps,
+        liquiditySymbol: null,
         loanToValueBps,
         maxBorrowRateBps,
+        maxFlashLoanAmount,
         minBorrowRateBps,
         optimalBorrowRateBps,
         optimalUtilizationRateBps,
         platformFeeBps,
       })
       .accountsPartial({
+        listingApproval: null,
         liquidityMint: collateralMint,
         liquidityTokenProgram: TOKEN_PROGRAM_ID,
+        receiptTokenProgram: TOKEN_PROGRAM_ID,
         priceUpdateV2,
         market: marketPda,
         authority: marketAuthority.publicKey,
@@ -108,10 +124,14 @@ describe("withdrawObligationCollateral", () => {
 
     // initializes an obligation
     await program.methods
-      .initializeObligation()
+      .initializeObligation(0)
       .accountsPartial({
         authority: obligationAuthority.publicKey,
         reserve: reservePda,
+        obligation: LendingClient.getObligationPda(
+          obligationAuthority.publicKey,
+          marketPda,
+        ),
       })
       .signers([obligationAuthority])
       .rpc();
@@ -182,12 +202,15 @@ describe("withdrawObligationCollateral", () => {
 
     const preReserveAtaAcc = await getAccount(connection, reserveAta);
 
-    const receiptMintAta = getAssociatedTokenAddressSync(
-      receiptMint,
-      obligationAuthority.publicKey,
+    const collateralEscrowPda = LendingClient.getCollateralEscrowPda(
+      obligationPda,
+      reservePda,
     );
 
-    const preReceiptMintAtaAcc = await getAccount(connection, receiptMintAta);
+    const preCollateralEscrowAcc = await getAccount(
+      connection,
+      collateralEscrowPda,
+    );
 
     const preReserveAcc = await client.fetchProgramAccount(
       reservePda,
@@ -235,12 +258,12 @@ describe("withdrawObligationCollateral", () => {
           .withdrawObligationCollateral(new BN(withdrawAmount))
           .accountsPartial({
             authority: obligationAuthority.publicKey,
+            obligationAuthority: obligationAuthority.publicKey,
             collateralMint,
             collateralTokenProgram: TOKEN_PROGRAM_ID,
             obligation: obligationPda,
             reserve: reservePda,
             reserveTokenAccount: reserveAta,
-            receiptTokenAccount: receiptMintAta,
           })
           .instruction(),
       ],
@@ -262,10 +285,13 @@ describe("withdrawObligationCollateral", () => {
       postReserveAtaAcc.amount + BigInt(withdrawAmount),
     );
 
-    const postReceiptMintAtaAcc = await getAccount(connection, receiptMintAta);
+    const postCollateralEscrowAcc = await getAccount(
+      connection,
+      collateralEscrowPda,
+    );
 
-    expect(preReceiptMintAtaAcc.amount).toBeGreaterThan(
-      postReceiptMintAtaAcc.amount,
+    expect(preCollateralEscrowAcc.amount).toBe(
+      postCollateralEscrowAcc.amount + BigInt(withdrawAmount),
     );
 
     const postReserveAcc = await client.fetchProgramAccount(
@@ -295,6 +321,157 @@ describe("withdrawObligationCollateral", () => {
     ).toBeTrue();
   });
 
+  test("withdraw by a delegate into the owner's token account", async () => {
+    const manager = Keypair.generate();
+
+    await airdropAccount(manager.publicKey);
+
+    await program.methods
+      .setObligationDelegate(
+        manager.publicKey,
+        DELEGATE_PERMISSION_WITHDRAW,
+        new BN(0),
+      )
+      .accountsPartial({
+        authority: obligationAuthority.publicKey,
+        obligation: obligationPda,
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    const obligationDelegatePda = LendingClient.getObligationDelegatePda(
+      obligationPda,
+      manager.publicKey,
+    );
+
+    const obligationAuthorityAta = getAssociatedTokenAddressSync(
+      collateralMint,
+      obligationAuthority.publicKey,
+    );
+
+    const preObligationAuthorityAtaAcc = await getAccount(
+      connection,
+      obligationAuthorityAta,
+    );
+
+    const reserveAta = getAssociatedTokenAddressSync(
+      collateralMint,
+      reservePda,
+      !PublicKey.isOnCurve(reservePda),
+    );
+
+    const withdrawAmount = depositAmount / 2;
+
+    await expireBlockhash();
+
+    await buildAndSendv0Tx(
+      [
+        await program.methods
+          .refreshReserve()
+          .accounts({
+            priceUpdateV2: collateralMintPriceUpdateV2,
+            reserve: reservePda,
+          })
+          .instruction(),
+        await program.methods
+          .refreshObligation()
+          .accounts({
+            obligation: obligationPda,
+          })
+          .remainingAccounts([
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: reservePda,
+            },
+            {
+              isSigner: false,
+              isWritable: false,
+              pubkey: receiptMint,
+            },
+          ])
+          .instruction(),
+        // the delegate signs, but the owner receives the withdrawn collateral
+        await program.methods
+          .withdrawObligationCollateral(new BN(withdrawAmount))
+          .accountsPartial({
+            authority: manager.publicKey,
+            obligationAuthority: obligationAuthority.publicKey,
+            collateralMint,
+            collateralTokenProgram: TOKEN_PROGRAM_ID,
+            obligation: obligationPda,
+            obligationDelegate: obligationDelegatePda,
+            reserve: reservePda,
+            reserveTokenAccount: reserveAta,
+          })
+          .instruction(),
+      ],
+      [manager],
+    );
+
+    const postObligationAuthorityAtaAcc = await getAccount(
+      connection,
+      obligationAuthorityAta,
+    );
+
+    expect(preObligationAuthorityAtaAcc.amount).toBe(
+      postObligationAuthorityAtaAcc.amount - BigInt(withdrawAmount),
+    );
+    expect(
+      await connection.getAccountInfo(
+        getAssociatedTokenAddressSync(collateralMint, manager.publicKey),
+      ),
+    ).toBeNull();
+  });
+
+  test("reject a withdraw by an expired delegate", async () => {
+    const manager = Keypair.generate();
+
+    await airdropAccount(manager.publicKey);
+
+    // any timestamp in the past has already expired
+    await program.methods
+      .setObligationDelegate(
+        manager.publicKey,
+        DELEGATE_PERMISSION_WITHDRAW,
+        new BN(1),
+      )
+      .accountsPartial({
+        authority: obligationAuthority.publicKey,
+        obligation: obligationPda,
+      })
+      .signers([obligationAuthority])
+      .rpc();
+
+    const obligationDelegatePda = LendingClient.getObligationDelegatePda(
+      obligationPda,
+      manager.publicKey,
+    );
+
+    const reserveAta = getAssociatedTokenAddressSync(
+      collateralMint,
+      reservePda,
+      !PublicKey.isOnCurve(reservePda),
+    );
+
+    await expect(
+      program.methods
+        .withdrawObligationCollateral(new BN(depositAmount / 2))
+        .accountsPartial({
+          authority: manager.publicKey,
+          obligationAuthority: obligationAuthority.publicKey,
+          collateralMint,
+          collateralTokenProgram: TOKEN_PROGRAM_ID,
+          obligation: obligationPda,
+          obligationDelegate: obligationDelegatePda,
+          reserve: reservePda,
+          reserveTokenAccount: reserveAta,
+        })
+        .signers([manager])
+        .rpc(),
+    ).rejects.toThrow("DelegateExpired");
+  });
+
   afterEach(async () => {
     await resetAccounts([marketPda]);
   });